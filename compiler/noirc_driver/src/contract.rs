@@ -6,6 +6,7 @@ use fm::FileId;
 use noirc_abi::{Abi, ContractEvent};
 use noirc_errors::debug_info::DebugInfo;
 use noirc_evaluator::errors::SsaReport;
+use noirc_evaluator::LoopReport;
 
 use super::debug::DebugFile;
 
@@ -68,6 +69,10 @@ pub struct ContractFunction {
     pub bytecode: Circuit,
 
     pub debug: DebugInfo,
+
+    /// The loops left in the function once it is optimized, which are executed by Brillig.
+    #[serde(default)]
+    pub loops: LoopReport,
 }
 
 impl ContractFunctionType {
@@ -3,7 +3,7 @@ use noirc_errors::debug_info::DebugInfo;
 use serde::{Deserialize, Serialize};
 use std::{
     collections::{BTreeMap, BTreeSet},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
 /// For a given file, we store the source code and the path to the file
@@ -41,3 +41,17 @@ pub(crate) fn filter_relevant_files(
     }
     file_map
 }
+
+/// Rewrites the paths in `file_map` to be relative to the innermost of `roots` which they lie inside,
+/// so that the debug artifact does not depend on where the project and its dependencies are located on disk.
+pub(crate) fn strip_roots_from_paths(file_map: &mut BTreeMap<FileId, DebugFile>, roots: &[&Path]) {
+    for file in file_map.values_mut() {
+        let relative_path = roots
+            .iter()
+            .filter_map(|root| file.path.strip_prefix(root).ok())
+            .min_by_key(|relative_path| relative_path.components().count());
+        if let Some(relative_path) = relative_path {
+            file.path = relative_path.to_path_buf();
+        }
+    }
+}
@@ -4,13 +4,14 @@
 #![warn(clippy::semicolon_if_nothing_returned)]
 
 use acvm::acir::circuit::ExpressionWidth;
-use clap::Args;
+use clap::{builder::PossibleValuesParser, Args};
 use fm::{FileId, FileManager};
 use iter_extended::vecmap;
 use noirc_abi::{AbiParameter, AbiType, ContractEvent};
 use noirc_errors::{CustomDiagnostic, FileDiagnostic};
 use noirc_evaluator::create_circuit;
 use noirc_evaluator::errors::RuntimeError;
+use noirc_evaluator::ssa::OPTIONAL_PASSES;
 use noirc_frontend::debug::build_debug_crate_file;
 use noirc_frontend::graph::{CrateId, CrateName};
 use noirc_frontend::hir::def_map::{Contract, CrateDefMap};
@@ -28,7 +29,7 @@ mod debug;
 mod program;
 mod stdlib;
 
-use debug::filter_relevant_files;
+use debug::{filter_relevant_files, strip_roots_from_paths};
 
 pub use contract::{CompiledContract, ContractFunction, ContractFunctionType};
 pub use debug::DebugFile;
@@ -94,6 +95,44 @@ pub struct CompileOptions {
     /// Force Brillig output (for step debugging)
     #[arg(long, hide = true)]
     pub force_brillig: bool,
+
+    /// Unroll Brillig loops over arrays with at most this many elements
+    #[arg(long, default_value_t = 0, env = "NARGO_UNROLL_THRESHOLD")]
+    pub brillig_unroll_threshold: usize,
+
+    /// Make Brillig truncations trap if the value does not fit in the target bit size instead of wrapping
+    #[arg(long)]
+    pub brillig_checked_truncation: bool,
+
+    /// Comma separated list of the SSA optimization passes to skip
+    #[arg(
+        long,
+        value_delimiter = ',',
+        env = "NARGO_DISABLE_PASSES",
+        value_parser = PossibleValuesParser::new(OPTIONAL_PASSES)
+    )]
+    pub disable_passes: Vec<String>,
+
+    /// Print the memory allocated by each SSA instruction of the Brillig functions
+    #[arg(long)]
+    pub profile_memory: bool,
+
+    /// Print the number of loops and their maximum nesting depth in each function
+    #[arg(long)]
+    pub show_loops: bool,
+
+    /// Fail compilation if an unconstrained function returns a value which it never assigns
+    #[arg(long)]
+    pub fail_on_unconstrained_return: bool,
+
+    /// Fail compilation if any unconstrained function compiles to more than this many opcodes
+    #[arg(long)]
+    pub max_opcodes: Option<usize>,
+
+    /// Produce byte-identical artifacts across runs and machines by removing the
+    /// location of the project and the generation time from the files written
+    #[arg(long)]
+    pub deterministic_build: bool,
 }
 
 fn parse_expression_width(input: &str) -> Result<ExpressionWidth, std::io::Error> {
@@ -343,6 +382,11 @@ pub fn compile_contract(
         assert_eq!(compiled_contracts.len(), 1);
         let compiled_contract = compiled_contracts.remove(0);
 
+        let compilation_warnings = vecmap(compiled_contract.warnings.clone(), FileDiagnostic::from);
+        if options.deny_warnings && !compilation_warnings.is_empty() {
+            return Err(compilation_warnings);
+        }
+
         if options.print_acir {
             for contract_function in &compiled_contract.functions {
                 println!(
@@ -411,12 +455,16 @@ fn compile_contract_inner(
             abi: function.abi,
             bytecode: function.circuit,
             debug: function.debug,
+            loops: function.loops,
         });
     }
 
     if errors.is_empty() {
         let debug_infos: Vec<_> = functions.iter().map(|function| function.debug.clone()).collect();
-        let file_map = filter_relevant_files(&debug_infos, &context.file_manager);
+        let mut file_map = filter_relevant_files(&debug_infos, &context.file_manager);
+        if options.deterministic_build {
+            strip_roots_from_paths(&mut file_map, &crate_dirs(context));
+        }
 
         Ok(CompiledContract {
             name: contract.name,
@@ -456,7 +504,14 @@ pub fn compile_no_check(
         monomorphize(main_function, &mut context.def_interner)?
     };
 
-    let hash = fxhash::hash64(&program);
+    // The options which change the generated bytecode, as resolved from the command line or the environment,
+    // are part of the hash so that an artifact compiled with different ones is not reused.
+    let hash = fxhash::hash64(&(
+        &program,
+        options.brillig_unroll_threshold,
+        &options.disable_passes,
+        options.brillig_checked_truncation,
+    ));
     let hashes_match = cached_program.as_ref().map_or(false, |program| program.hash == hash);
     if options.show_monomorphized {
         println!("{program}");
@@ -464,20 +519,44 @@ pub fn compile_no_check(
 
     // If user has specified that they want to see intermediate steps printed then we should
     // force compilation even if the program hasn't changed.
-    let force_compile =
-        force_compile || options.print_acir || options.show_brillig || options.show_ssa;
+    let force_compile = force_compile
+        || options.print_acir
+        || options.show_brillig
+        || options.show_ssa
+        || options.profile_memory
+        || options.show_loops
+        || options.fail_on_unconstrained_return
+        || options.max_opcodes.is_some()
+        || options.deterministic_build;
 
     if !force_compile && hashes_match {
         info!("Program matches existing artifact, returning early");
         return Ok(cached_program.expect("cache must exist for hashes to match"));
     }
     let visibility = program.return_visibility;
-    let (circuit, debug, input_witnesses, return_witnesses, warnings) =
-        create_circuit(program, options.show_ssa, options.show_brillig, options.force_brillig)?;
+    let (circuit, debug, input_witnesses, return_witnesses, warnings, loops) = create_circuit(
+        program,
+        options.show_ssa,
+        options.show_brillig,
+        options.force_brillig,
+        options.brillig_unroll_threshold,
+        options.brillig_checked_truncation,
+        options.profile_memory,
+        options.show_loops,
+        options.fail_on_unconstrained_return,
+        &options.disable_passes,
+        options.max_opcodes,
+    )?;
 
     let abi =
         abi_gen::gen_abi(context, &main_function, input_witnesses, return_witnesses, visibility);
-    let file_map = filter_relevant_files(&[debug.clone()], &context.file_manager);
+    let mut file_map = filter_relevant_files(&[debug.clone()], &context.file_manager);
+    // Registers and constants need no renumbering or reordering for a deterministic build: they are allocated
+    // and emitted in the order of the SSA, whose passes only iterate over ordered or deterministically hashed
+    // collections, so they are already the same across runs and machines.
+    if options.deterministic_build {
+        strip_roots_from_paths(&mut file_map, &crate_dirs(context));
+    }
 
     Ok(CompiledProgram {
         hash,
@@ -487,5 +566,98 @@ pub fn compile_no_check(
         file_map,
         noir_version: NOIR_ARTIFACT_VERSION_STRING.to_string(),
         warnings,
+        loops,
     })
 }
+
+/// Returns the directory containing the entry file of the crate being compiled.
+/// Returns the directories holding the root files of the crates on disk, i.e. the project and its dependencies.
+///
+/// The standard library and the debug crate have virtual paths, which are already the same on every machine.
+fn crate_dirs(context: &Context) -> Vec<&Path> {
+    context
+        .crate_graph
+        .iter_keys()
+        .filter_map(|crate_id| {
+            let root_file_id = context.crate_graph[crate_id].root_file_id;
+            context.file_manager.path(root_file_id).and_then(Path::parent)
+        })
+        .filter(|dir| dir.is_absolute())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsString;
+    use std::sync::{Mutex, MutexGuard};
+
+    use clap::Parser;
+
+    use super::CompileOptions;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        options: CompileOptions,
+    }
+
+    /// Serializes the tests which set environment variables, as they are shared by the whole process.
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    /// Sets environment variables until it is dropped, even by a failing test,
+    /// when their previous values are restored.
+    struct ScopedEnv {
+        previous: Vec<(&'static str, Option<OsString>)>,
+        _lock: MutexGuard<'static, ()>,
+    }
+
+    impl ScopedEnv {
+        fn set(vars: &[(&'static str, &str)]) -> ScopedEnv {
+            let lock = ENV_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            let previous = vars
+                .iter()
+                .map(|(name, value)| {
+                    let previous = std::env::var_os(name);
+                    std::env::set_var(name, value);
+                    (*name, previous)
+                })
+                .collect();
+            ScopedEnv { previous, _lock: lock }
+        }
+    }
+
+    impl Drop for ScopedEnv {
+        fn drop(&mut self) {
+            for (name, previous) in &self.previous {
+                match previous {
+                    Some(value) => std::env::set_var(name, value),
+                    None => std::env::remove_var(name),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reads_defaults_from_environment() {
+        let _env = ScopedEnv::set(&[
+            ("NARGO_UNROLL_THRESHOLD", "8"),
+            ("NARGO_DISABLE_PASSES", "fold_constants,dead_instruction_elimination"),
+        ]);
+
+        let options = Cli::parse_from(["nargo"]).options;
+        assert_eq!(options.brillig_unroll_threshold, 8);
+        assert_eq!(options.disable_passes, vec!["fold_constants", "dead_instruction_elimination"]);
+
+        // Flags take precedence over the environment
+        let options = Cli::parse_from([
+            "nargo",
+            "--brillig-unroll-threshold",
+            "2",
+            "--disable-passes",
+            "remove_redundant_truncates",
+        ])
+        .options;
+        assert_eq!(options.brillig_unroll_threshold, 2);
+        assert_eq!(options.disable_passes, vec!["remove_redundant_truncates"]);
+    }
+}
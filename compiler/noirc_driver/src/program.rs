@@ -5,6 +5,7 @@ use fm::FileId;
 
 use noirc_errors::debug_info::DebugInfo;
 use noirc_evaluator::errors::SsaReport;
+use noirc_evaluator::LoopReport;
 use serde::{Deserialize, Serialize};
 
 use super::debug::DebugFile;
@@ -27,4 +28,7 @@ pub struct CompiledProgram {
     pub debug: DebugInfo,
     pub file_map: BTreeMap<FileId, DebugFile>,
     pub warnings: Vec<SsaReport>,
+    /// The loops left in the program once it is optimized, which are executed by Brillig.
+    #[serde(default)]
+    pub loops: LoopReport,
 }
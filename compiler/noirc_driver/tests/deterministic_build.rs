@@ -0,0 +1,92 @@
+use std::path::Path;
+
+use noirc_driver::{
+    add_dep, compile_main, file_manager_with_stdlib, prepare_crate, prepare_dependency,
+    CompileOptions,
+};
+use noirc_frontend::hir::{def_map::parse_file, Context};
+
+const DEPENDENCY_SOURCE: &str = "
+    pub fn check(x: u32, y: u32) {
+        assert(x != y);
+    }
+";
+
+/// Compiles `source` as if it was the entry point of a project located at `root`, which depends
+/// on a `lib` crate located at `dependency_root`, and returns the serialized artifact.
+fn compile_artifact(
+    root: &Path,
+    dependency_root: &Path,
+    source: &str,
+    options: &CompileOptions,
+) -> Vec<u8> {
+    let file_name = root.join("src/main.nr");
+    let dependency_file_name = dependency_root.join("src/lib.nr");
+    let mut file_manager = file_manager_with_stdlib(root);
+    file_manager.add_file_with_source(&file_name, source.to_owned()).expect(
+        "Adding source buffer to file manager should never fail when file manager is empty",
+    );
+    file_manager
+        .add_file_with_source(&dependency_file_name, DEPENDENCY_SOURCE.to_owned())
+        .expect("Adding the dependency source should not fail");
+    let parsed_files = file_manager
+        .as_file_map()
+        .all_file_ids()
+        .map(|&file_id| (file_id, parse_file(&file_manager, file_id)))
+        .collect();
+
+    let mut context = Context::new(file_manager, parsed_files);
+    let root_crate_id = prepare_crate(&mut context, &file_name);
+    let dependency_crate_id = prepare_dependency(&mut context, &dependency_file_name);
+    add_dep(&mut context, root_crate_id, dependency_crate_id, "lib".parse().unwrap());
+
+    let (program, _) =
+        compile_main(&mut context, root_crate_id, options, None).expect("program should compile");
+    serde_json::to_vec(&program).unwrap()
+}
+
+#[test]
+fn deterministic_builds_are_byte_identical() {
+    let source = "
+        fn main(x: u32, y: pub u32) -> pub u32 {
+            lib::check(x, y);
+            helper(x, y)
+        }
+
+        unconstrained fn helper(x: u32, y: u32) -> u32 {
+            let mut sum = 0;
+            for i in 0..4 {
+                sum += x * i + y;
+            }
+            sum
+        }
+    ";
+    let options = CompileOptions { deterministic_build: true, ..Default::default() };
+
+    let compile_in = |home: &str| {
+        let home = Path::new(home);
+        let dependency_root = home.join("nargo/github.com/noir-lang/lib/v0.1.0");
+        compile_artifact(&home.join("project"), &dependency_root, source, &options)
+    };
+    let first = compile_in("/home/alice");
+    let second = compile_in("/tmp/checkout");
+    assert_eq!(first, second);
+    // The paths of the dependency are stripped as well as those of the project.
+    assert!(!String::from_utf8(first).unwrap().contains("/home/alice"));
+
+    // Without the flag the location of the project leaks into the artifact.
+    let options = CompileOptions::default();
+    let first = compile_artifact(
+        Path::new("/home/alice/project"),
+        Path::new("/home/alice/lib"),
+        source,
+        &options,
+    );
+    let second = compile_artifact(
+        Path::new("/tmp/checkout/project"),
+        Path::new("/tmp/checkout/lib"),
+        source,
+        &options,
+    );
+    assert_ne!(first, second);
+}
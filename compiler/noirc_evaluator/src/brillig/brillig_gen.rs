@@ -4,6 +4,7 @@ pub(crate) mod brillig_block_variables;
 pub(crate) mod brillig_directive;
 pub(crate) mod brillig_fn;
 pub(crate) mod brillig_slice_ops;
+pub(crate) mod memory_profile;
 mod variable_liveness;
 
 use self::{brillig_block::BrilligBlock, brillig_fn::FunctionContext};
@@ -11,11 +12,24 @@ use super::brillig_ir::{artifact::BrilligArtifact, BrilligContext};
 use crate::ssa::ir::function::Function;
 
 /// Converting an SSA function into Brillig bytecode.
-pub(crate) fn convert_ssa_function(func: &Function, enable_debug_trace: bool) -> BrilligArtifact {
+pub(crate) fn convert_ssa_function(
+    func: &Function,
+    enable_debug_trace: bool,
+    unroll_threshold: usize,
+    checked_truncation: bool,
+) -> BrilligArtifact {
     let mut brillig_context = BrilligContext::new(enable_debug_trace);
+    brillig_context.set_unroll_threshold(unroll_threshold);
+    brillig_context.set_checked_truncation(checked_truncation);
 
     let mut function_context = FunctionContext::new(func, &mut brillig_context);
 
+    // The parameters of the function are written by its caller.
+    for parameter in func.parameters() {
+        let variable = function_context.ssa_value_allocations[parameter];
+        brillig_context.mark_registers_as_assigned(variable.extract_registers());
+    }
+
     brillig_context.enter_context(FunctionContext::function_id_to_function_label(func.id()));
 
     for block in function_context.blocks.clone() {
@@ -24,3 +38,49 @@ pub(crate) fn convert_ssa_function(func: &Function, enable_debug_trace: bool) ->
 
     brillig_context.artifact()
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use acvm::acir::brillig::Opcode as BrilligOpcode;
+
+    use super::convert_ssa_function;
+    use crate::ssa::function_builder::FunctionBuilder;
+    use crate::ssa::ir::{
+        function::RuntimeType, instruction::ConstrainError, map::Id, types::Type,
+    };
+
+    #[test]
+    fn attaches_assert_messages_to_traps() {
+        // brillig fn main f0 {
+        //   b0(v0: Field, v1: Field):
+        //     constrain v0 == v1 "values differ"
+        //     return
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Brillig);
+        let v0 = builder.add_parameter(Type::field());
+        let v1 = builder.add_parameter(Type::field());
+        let message = ConstrainError::Static("values differ".to_string());
+        builder.insert_constrain(v0, v1, Some(Box::new(message)));
+        builder.terminate_with_return(vec![]);
+
+        let ssa = builder.finish();
+        let artifact = convert_ssa_function(ssa.main(), false, 0, false);
+
+        let trap_positions: Vec<_> = artifact
+            .byte_code
+            .iter()
+            .enumerate()
+            .filter_map(|(position, opcode)| {
+                matches!(opcode, BrilligOpcode::Trap).then_some(position)
+            })
+            .collect();
+        assert_eq!(trap_positions.len(), 1);
+        assert_eq!(
+            artifact.assert_messages,
+            BTreeMap::from([(trap_positions[0], "values differ".to_string())])
+        );
+    }
+}
@@ -79,6 +79,8 @@ impl<'block> BrilligBlock<'block> {
             self.convert_ssa_instruction(*instruction_id, dfg);
         }
 
+        self.brillig_context.set_allocation_site(None);
+
         // Process the block's terminator instruction
         let terminator_instruction =
             block.terminator().expect("block is expected to be constructed");
@@ -145,7 +147,8 @@ impl<'block> BrilligBlock<'block> {
                     self.create_block_label_for_current_function(*destination_block),
                 );
             }
-            TerminatorInstruction::Return { return_values, .. } => {
+            TerminatorInstruction::Return { return_values, call_stack } => {
+                self.brillig_context.set_call_stack(call_stack.clone());
                 let return_registers: Vec<_> = return_values
                     .iter()
                     .flat_map(|value_id| {
@@ -236,6 +239,7 @@ impl<'block> BrilligBlock<'block> {
     fn convert_ssa_instruction(&mut self, instruction_id: InstructionId, dfg: &DataFlowGraph) {
         let instruction = &dfg[instruction_id];
         self.brillig_context.set_call_stack(dfg.get_call_stack(instruction_id));
+        self.brillig_context.set_allocation_site(Some(instruction_id));
 
         match instruction {
             Instruction::Binary(binary) => {
@@ -849,11 +853,19 @@ impl<'block> BrilligBlock<'block> {
                 // First issue a array copy to the destination
                 ctx.allocate_array_instruction(destination_pointer, source_size_as_register);
 
-                ctx.copy_array_instruction(
-                    source_pointer,
-                    destination_pointer,
-                    source_size_as_register,
-                );
+                if let BrilligVariable::BrilligArray(BrilligArray { size, .. }) = source_variable {
+                    ctx.copy_fixed_length_array_instruction(
+                        source_pointer,
+                        destination_pointer,
+                        size,
+                    );
+                } else {
+                    ctx.copy_array_instruction(
+                        source_pointer,
+                        destination_pointer,
+                        source_size_as_register,
+                    );
+                }
             }
         });
 
@@ -0,0 +1,82 @@
+//! Reports which SSA instructions are responsible for the statically sized memory allocations
+//! of a Brillig function, so that the arrays driving memory usage can be found.
+use std::fmt::{Display, Formatter, Write};
+
+use crate::{
+    brillig::brillig_ir::artifact::BrilligArtifact,
+    ssa::ir::{function::Function, instruction::InstructionId, printer::display_instruction},
+};
+
+struct DisplayInstruction<'a>(&'a Function, InstructionId);
+
+impl Display for DisplayInstruction<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        display_instruction(self.0, self.1, f)
+    }
+}
+
+/// Renders the allocation sites of a compiled Brillig function, largest first.
+///
+/// Only allocations whose size is known at compile time are attributed to a site.
+pub(crate) fn allocation_report(func: &Function, artifact: &BrilligArtifact) -> String {
+    let mut sites: Vec<_> = artifact.allocation_sites.iter().collect();
+    sites.sort_by(|(_, lhs), (_, rhs)| rhs.cmp(lhs));
+
+    let total: usize = sites.iter().map(|(_, size)| **size).sum();
+    let mut report = format!("Memory allocated by {} ({total} slots):\n", func.name());
+    for (instruction, size) in sites {
+        let instruction = DisplayInstruction(func, *instruction).to_string();
+        writeln!(report, "{size:>8} slots: {}", instruction.trim())
+            .expect("writing to a string cannot fail");
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use acvm::FieldElement;
+    use im::vector;
+
+    use super::allocation_report;
+    use crate::brillig::brillig_gen::convert_ssa_function;
+    use crate::ssa::function_builder::FunctionBuilder;
+    use crate::ssa::ir::{function::RuntimeType, map::Id, types::Type};
+
+    #[test]
+    fn reports_allocation_sizes_per_site() {
+        // brillig fn main f0 {
+        //   b0():
+        //     v0 = allocate
+        //     store [Field 1, Field 2, Field 3] at v0
+        //     return
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Brillig);
+
+        let array_type = Type::Array(Rc::new(vec![Type::field()]), 3);
+        let v0 = builder.insert_allocate(array_type.clone());
+        let one = builder.field_constant(FieldElement::one());
+        let two = builder.field_constant(2u128);
+        let three = builder.field_constant(3u128);
+        let array = builder.array_constant(vector![one, two, three], array_type);
+        builder.insert_store(v0, array);
+        builder.terminate_with_return(vec![]);
+
+        let ssa = builder.finish();
+        let main = ssa.main();
+        let artifact = convert_ssa_function(main, false, 0, false);
+
+        let report = allocation_report(main, &artifact);
+        let lines: Vec<_> = report.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Memory allocated by main (5 slots):",
+                "       3 slots: store [Field 1, Field 2, Field 3] at v0",
+                "       2 slots: v0 = allocate",
+            ]
+        );
+    }
+}
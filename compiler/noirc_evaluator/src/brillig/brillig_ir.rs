@@ -9,9 +9,13 @@ pub(crate) mod brillig_variable;
 pub(crate) mod debug_show;
 pub(crate) mod registers;
 
+mod codegen_binary;
+mod codegen_memory;
 mod entry_point;
 
-use crate::ssa::ir::dfg::CallStack;
+use std::collections::{BTreeMap, HashSet};
+
+use crate::ssa::ir::{dfg::CallStack, instruction::InstructionId};
 
 use self::{
     artifact::{BrilligArtifact, UnresolvedJumpLocation},
@@ -85,6 +89,21 @@ pub(crate) struct BrilligContext {
     next_section: usize,
     /// IR printer
     debug_show: DebugShow,
+    /// Loops with a number of iterations known at compile time and no greater than
+    /// this threshold are emitted fully unrolled.
+    unroll_threshold: usize,
+    /// Whether [`BrilligContext::truncate_instruction`] traps on values which do not fit in the target
+    /// bit size instead of wrapping them.
+    checked_truncation: bool,
+    /// Registers loaded with constants in the current section by [`BrilligContext::cached_constant`].
+    cached_constants: BTreeMap<(Value, u32), MemoryAddress>,
+    /// Registers which are written to by the bytecode emitted so far, or by the caller of the function.
+    assigned_registers: HashSet<MemoryAddress>,
+    /// Whether the last opcode emitted stops execution, so that the opcodes emitted before the next label
+    /// can never be reached.
+    terminated: bool,
+    /// Whether opcodes which can never be reached are dropped instead of being emitted.
+    drop_unreachable_opcodes: bool,
 }
 
 impl BrilligContext {
@@ -97,18 +116,68 @@ impl BrilligContext {
             section_label: 0,
             next_section: 1,
             debug_show: DebugShow::new(enable_debug_trace),
+            unroll_threshold: 0,
+            checked_truncation: false,
+            cached_constants: BTreeMap::new(),
+            assigned_registers: HashSet::new(),
+            terminated: false,
+            drop_unreachable_opcodes: false,
         }
     }
 
+    /// Sets the maximum number of iterations for which loops of a known length are unrolled.
+    pub(crate) fn set_unroll_threshold(&mut self, unroll_threshold: usize) {
+        self.unroll_threshold = unroll_threshold;
+    }
+
+    /// Sets whether truncations trap on values which do not fit in the target bit size.
+    pub(crate) fn set_checked_truncation(&mut self, checked_truncation: bool) {
+        self.checked_truncation = checked_truncation;
+    }
+
+    /// Sets whether opcodes emitted after a stop, return, trap or unconditional jump, without a label
+    /// in between through which they could be jumped to, are dropped as unreachable.
+    pub(crate) fn set_drop_unreachable_opcodes(&mut self, drop_unreachable_opcodes: bool) {
+        self.drop_unreachable_opcodes = drop_unreachable_opcodes;
+    }
+
     pub(crate) fn set_allocated_registers(&mut self, allocated_registers: Vec<MemoryAddress>) {
+        // The cached registers belong to the register space being replaced.
+        self.cached_constants.clear();
         self.registers = BrilligRegistersContext::from_preallocated_registers(allocated_registers);
     }
 
     /// Adds a brillig instruction to the brillig byte code
     pub(crate) fn push_opcode(&mut self, opcode: BrilligOpcode) {
+        if self.is_unreachable() {
+            return;
+        }
+        self.assigned_registers.extend(registers_written_by(&opcode));
+        self.terminated = terminates_execution(&opcode);
         self.obj.push_opcode(opcode);
     }
 
+    /// Same as [`BrilligContext::push_opcode`], attaching a `comment` to the opcode which is shown
+    /// in the [bytecode listing][BrilligArtifact::bytecode_listing] of the artifact.
+    pub(crate) fn push_opcode_with_comment(&mut self, opcode: BrilligOpcode, comment: String) {
+        if self.is_unreachable() {
+            return;
+        }
+        self.push_opcode(opcode);
+        self.obj.add_comment_to_last_opcode(comment);
+    }
+
+    /// Returns whether an opcode emitted now would be dropped, see [`BrilligContext::set_drop_unreachable_opcodes`].
+    fn is_unreachable(&self) -> bool {
+        self.terminated && self.drop_unreachable_opcodes
+    }
+
+    /// Marks registers as holding a value without emitting any opcode, e.g. function parameters
+    /// which are written by the caller.
+    pub(crate) fn mark_registers_as_assigned(&mut self, registers: Vec<MemoryAddress>) {
+        self.assigned_registers.extend(registers);
+    }
+
     /// Returns the artifact
     pub(crate) fn artifact(self) -> BrilligArtifact {
         self.obj
@@ -122,6 +191,7 @@ impl BrilligContext {
         size: usize,
     ) {
         // debug_show handled by allocate_array_instruction
+        self.obj.add_allocation(size);
         let size_register = self.make_usize_constant(size.into());
         self.allocate_array_instruction(pointer_register, size_register);
         self.deallocate_register(size_register);
@@ -164,6 +234,7 @@ impl BrilligContext {
         size: usize,
     ) {
         self.debug_show.allocate_instruction(pointer_register);
+        self.obj.add_allocation(size);
         // A variable can be stored in up to three values, so we reserve three values for that.
         let size_register = self.make_usize_constant(size.into());
         self.push_opcode(BrilligOpcode::Mov {
@@ -271,6 +342,47 @@ impl BrilligContext {
         self.deallocate_register(value_register);
     }
 
+    /// Copies `num_elements` values of the array pointed by source into the array pointed by destination.
+    /// The copy is unrolled if `num_elements` is within the unroll threshold of this context.
+    pub(crate) fn copy_fixed_length_array_instruction(
+        &mut self,
+        source_pointer: MemoryAddress,
+        destination_pointer: MemoryAddress,
+        num_elements: usize,
+    ) {
+        let value_register = self.allocate_register();
+
+        self.fixed_length_loop_instruction(num_elements, |ctx, iterator| {
+            ctx.array_get(source_pointer, iterator, value_register);
+            ctx.array_set(destination_pointer, iterator, value_register);
+        });
+
+        self.deallocate_register(value_register);
+    }
+
+    /// Same as `loop_instruction` but for an iteration count known at compile time.
+    /// If the iteration count does not exceed the unroll threshold, the body is emitted
+    /// once per iteration with a constant iterator instead of issuing a loop.
+    pub(crate) fn fixed_length_loop_instruction<F>(
+        &mut self,
+        iteration_count: usize,
+        mut on_iteration: F,
+    ) where
+        F: FnMut(&mut BrilligContext, MemoryAddress),
+    {
+        if iteration_count <= self.unroll_threshold {
+            for iteration in 0..iteration_count {
+                let iterator_register = self.make_usize_constant(iteration.into());
+                on_iteration(self, iterator_register);
+                self.deallocate_register(iterator_register);
+            }
+        } else {
+            let iteration_count_register = self.make_usize_constant(iteration_count.into());
+            self.loop_instruction(iteration_count_register, on_iteration);
+            self.deallocate_register(iteration_count_register);
+        }
+    }
+
     /// This instruction will issue a loop that will iterate iteration_count times
     /// The body of the loop should be issued by the caller in the on_iteration closure.
     pub(crate) fn loop_instruction<F>(&mut self, iteration_count: MemoryAddress, on_iteration: F)
@@ -278,19 +390,47 @@ impl BrilligContext {
         F: FnOnce(&mut BrilligContext, MemoryAddress),
     {
         let iterator_register = self.make_usize_constant(0_u128.into());
+        self.loop_from_instruction(iterator_register, iteration_count, on_iteration);
+    }
 
+    /// This instruction will issue a loop whose iterator takes every value from the one held in `start`
+    /// up to, but excluding, the one held in `end`.
+    /// The body of the loop should be issued by the caller in the on_iteration closure.
+    pub(crate) fn range_loop_instruction<F>(
+        &mut self,
+        start: MemoryAddress,
+        end: MemoryAddress,
+        on_iteration: F,
+    ) where
+        F: FnOnce(&mut BrilligContext, MemoryAddress),
+    {
+        let iterator_register = self.allocate_register();
+        self.mov_instruction(iterator_register, start);
+        self.loop_from_instruction(iterator_register, end, on_iteration);
+    }
+
+    /// Issues a loop incrementing `iterator_register` from its current value until it reaches `end`,
+    /// then deallocates `iterator_register`.
+    fn loop_from_instruction<F>(
+        &mut self,
+        iterator_register: MemoryAddress,
+        end: MemoryAddress,
+        on_iteration: F,
+    ) where
+        F: FnOnce(&mut BrilligContext, MemoryAddress),
+    {
         let (loop_section, loop_label) = self.reserve_next_section_label();
         self.enter_section(loop_section);
 
         // Loop body
 
-        // Check if iterator < iteration_count
+        // Check if iterator < end
         let iterator_less_than_iterations =
             SingleAddrVariable { address: self.allocate_register(), bit_size: 1 };
 
         self.memory_op(
             iterator_register,
-            iteration_count,
+            end,
             iterator_less_than_iterations.address,
             BinaryIntOp::LessThan,
         );
@@ -317,6 +457,67 @@ impl BrilligContext {
         self.deallocate_register(iterator_register);
     }
 
+    /// This instruction will issue a loop that keeps iterating while the boolean returned by `condition` is true.
+    /// The condition is issued by the caller in the `condition` closure and evaluated before each iteration,
+    /// and the body of the loop is issued by the caller in the `on_iteration` closure.
+    ///
+    /// As the number of iterations is only known at runtime, the loop traps instead of starting
+    /// an iteration past `max_iterations`.
+    pub(crate) fn while_loop_instruction<C, F>(
+        &mut self,
+        max_iterations: usize,
+        condition: C,
+        on_iteration: F,
+    ) where
+        C: FnOnce(&mut BrilligContext) -> MemoryAddress,
+        F: FnOnce(&mut BrilligContext),
+    {
+        let iterator_register = self.make_usize_constant(0_u128.into());
+        let max_iterations_register = self.make_usize_constant(max_iterations.into());
+
+        let (loop_section, loop_label) = self.reserve_next_section_label();
+        self.enter_section(loop_section);
+
+        // Exit the loop once the condition is false
+        let condition = SingleAddrVariable { address: condition(self), bit_size: 1 };
+        let condition_is_false =
+            SingleAddrVariable { address: self.allocate_register(), bit_size: 1 };
+        self.not_instruction(condition, condition_is_false);
+
+        let (exit_loop_section, exit_loop_label) = self.reserve_next_section_label();
+        self.jump_if_instruction(condition_is_false.address, exit_loop_label);
+
+        // Check that iterator < max_iterations before running the body
+        let iterator_less_than_max = self.allocate_register();
+        self.memory_op(
+            iterator_register,
+            max_iterations_register,
+            iterator_less_than_max,
+            BinaryIntOp::LessThan,
+        );
+        self.constrain_instruction(
+            iterator_less_than_max,
+            Some("Loop exceeded its maximum number of iterations".to_string()),
+        );
+        self.deallocate_register(iterator_less_than_max);
+
+        // Call the on iteration function
+        on_iteration(self);
+
+        // Increment the iterator register
+        self.usize_op_in_place(iterator_register, BinaryIntOp::Add, 1);
+
+        self.jump_instruction(loop_label);
+
+        // Exit the loop
+        self.enter_section(exit_loop_section);
+
+        // Deallocate our temporary registers
+        self.deallocate_register(condition_is_false.address);
+        self.deallocate_register(max_iterations_register);
+        self.deallocate_register(iterator_register);
+    }
+
     /// This instruction will issue an if-then branch that will check if the condition is true
     /// and if so, perform the instructions given in `f(self, true)` and otherwise perform the
     /// instructions given in `f(self, false)`. A boolean is passed instead of two separate
@@ -363,6 +564,8 @@ impl BrilligContext {
     /// Adds a label to the next opcode
     pub(crate) fn enter_context<T: ToString>(&mut self, label: T) {
         self.debug_show.enter_context(label.to_string());
+        self.clear_cached_constants();
+        self.terminated = false;
         self.context_label = label.to_string();
         self.section_label = 0;
         // Add a context label to the next opcode
@@ -374,6 +577,8 @@ impl BrilligContext {
 
     /// Enter the given section
     fn enter_section(&mut self, section: usize) {
+        self.clear_cached_constants();
+        self.terminated = false;
         self.section_label = section;
         self.obj
             .add_label_at_position(self.current_section_label(), self.obj.index_of_next_opcode());
@@ -403,11 +608,20 @@ impl BrilligContext {
     }
 
     /// Adds a unresolved `JumpIf` instruction to the bytecode.
+    ///
+    /// If `condition` is known to hold a constant, this emits an unconditional `Jump` when it is true
+    /// and nothing at all when it is false.
     pub(crate) fn jump_if_instruction<T: ToString>(
         &mut self,
         condition: MemoryAddress,
         target_label: T,
     ) {
+        if let Some(condition) = self.obj.constant_at_next_opcode(condition) {
+            if !condition.is_zero() {
+                self.jump_instruction(target_label);
+            }
+            return;
+        }
         self.debug_show.jump_if_instruction(condition, target_label.to_string());
         self.add_unresolved_jump(
             BrilligOpcode::JumpIf { condition, location: 0 },
@@ -415,12 +629,36 @@ impl BrilligContext {
         );
     }
 
+    /// Adds a unresolved `Call` instruction to the bytecode, calling the subroutine at `target_label`.
+    ///
+    /// The VM keeps the return address on its call stack, so the same subroutine can be called from
+    /// several sites as long as it ends with [`Self::return_from_call_instruction`].
+    /// The subroutine shares its registers with the caller.
+    pub(crate) fn call_instruction<T: ToString>(&mut self, target_label: T) {
+        self.debug_show.call_instruction(target_label.to_string());
+        self.add_unresolved_jump(BrilligOpcode::Call { location: 0 }, target_label.to_string());
+        // The subroutine may write to the registers of the cached constants.
+        self.clear_cached_constants();
+    }
+
+    /// Returns from a subroutine to the opcode after its calling `Call` instruction.
+    pub(crate) fn return_from_call_instruction(&mut self) {
+        self.debug_show.return_from_call_instruction();
+        self.push_opcode(BrilligOpcode::Return);
+        // The constants cached by the subroutine are not loaded in the code which follows it.
+        self.clear_cached_constants();
+    }
+
     /// Adds a unresolved `Jump` instruction to the bytecode.
     fn add_unresolved_jump(
         &mut self,
         jmp_instruction: BrilligOpcode,
         destination: UnresolvedJumpLocation,
     ) {
+        if self.is_unreachable() {
+            return;
+        }
+        self.terminated = terminates_execution(&jmp_instruction);
         self.obj.add_unresolved_jump(jmp_instruction, destination);
     }
 
@@ -448,13 +686,20 @@ impl BrilligContext {
         self.debug_show.constrain_instruction(condition);
         let (next_section, next_label) = self.reserve_next_section_label();
         self.add_unresolved_jump(BrilligOpcode::JumpIf { condition, location: 0 }, next_label);
+        let reachable = !self.is_unreachable();
         self.push_opcode(BrilligOpcode::Trap);
-        if let Some(assert_message) = assert_message {
+        if let Some(assert_message) = assert_message.filter(|_| reachable) {
             self.obj.add_assert_message_to_last_opcode(assert_message);
         }
         self.enter_section(next_section);
     }
 
+    /// Same as [`Self::constrain_instruction`], always recording `message` in the artifact against the trap,
+    /// so that a failure can be reported with a human-readable reason.
+    pub(crate) fn constrain_with_message(&mut self, condition: MemoryAddress, message: String) {
+        self.constrain_instruction(condition, Some(message));
+    }
+
     /// Processes a return instruction.
     ///
     /// For Brillig, the return is implicit, since there is no explicit return instruction.
@@ -466,6 +711,9 @@ impl BrilligContext {
     /// the VM.
     pub(crate) fn return_instruction(&mut self, return_registers: &[MemoryAddress]) {
         self.debug_show.return_instruction(return_registers);
+        if return_registers.iter().any(|register| !self.assigned_registers.contains(register)) {
+            self.obj.add_unassigned_return();
+        }
         let mut sources = Vec::with_capacity(return_registers.len());
         let mut destinations = Vec::with_capacity(return_registers.len());
 
@@ -582,7 +830,7 @@ impl BrilligContext {
         // Compile !x as ((-1) - x)
         let u_max = FieldElement::from(2_i128).pow(&FieldElement::from(input.bit_size as i128))
             - FieldElement::one();
-        let max = self.make_constant(Value::from(u_max), input.bit_size);
+        let max = self.cached_constant(Value::from(u_max), input.bit_size);
         let opcode = BrilligOpcode::BinaryIntOp {
             destination: result.address,
             op: BinaryIntOp::Sub,
@@ -591,7 +839,6 @@ impl BrilligContext {
             rhs: input.address,
         };
         self.push_opcode(opcode);
-        self.deallocate_register(max);
     }
 
     /// Processes a foreign call instruction.
@@ -725,11 +972,69 @@ impl BrilligContext {
     /// Instead truncation instructions are emitted as to when a
     /// truncation should be done.
     /// For Brillig, all integer operations will overflow as its cheap.
+    ///
+    /// The truncation wraps unless checked truncation is enabled on the context,
+    /// see [`Self::checked_truncate_instruction`].
     pub(crate) fn truncate_instruction(
         &mut self,
         destination_of_truncated_value: SingleAddrVariable,
         value_to_truncate: SingleAddrVariable,
         bit_size: u32,
+    ) {
+        if self.checked_truncation {
+            self.checked_truncate_instruction(
+                destination_of_truncated_value,
+                value_to_truncate,
+                bit_size,
+            );
+        } else {
+            self.wrapping_truncate_instruction(
+                destination_of_truncated_value,
+                value_to_truncate,
+                bit_size,
+            );
+        }
+    }
+
+    /// Same as [`Self::wrapping_truncate_instruction`], but traps if `value_to_truncate`
+    /// does not fit in `bit_size` bits.
+    pub(crate) fn checked_truncate_instruction(
+        &mut self,
+        destination_of_truncated_value: SingleAddrVariable,
+        value_to_truncate: SingleAddrVariable,
+        bit_size: u32,
+    ) {
+        if bit_size < value_to_truncate.bit_size {
+            let max = BigUint::from(2_u32).pow(bit_size) - BigUint::from(1_u32);
+            let max_constant = self.make_constant(
+                FieldElement::from_be_bytes_reduce(&max.to_bytes_be()).into(),
+                value_to_truncate.bit_size,
+            );
+            let fits = self.allocate_register();
+            self.less_than_equal_instruction(
+                value_to_truncate.address,
+                max_constant,
+                fits,
+                value_to_truncate.bit_size,
+                false,
+            );
+            self.constrain_instruction(fits, Some("attempt to truncate with overflow".to_string()));
+            self.deallocate_register(fits);
+            self.deallocate_register(max_constant);
+        }
+        self.wrapping_truncate_instruction(
+            destination_of_truncated_value,
+            value_to_truncate,
+            bit_size,
+        );
+    }
+
+    /// Stores the lowest `bit_size` bits of `value_to_truncate` in `destination_of_truncated_value`.
+    pub(crate) fn wrapping_truncate_instruction(
+        &mut self,
+        destination_of_truncated_value: SingleAddrVariable,
+        value_to_truncate: SingleAddrVariable,
+        bit_size: u32,
     ) {
         self.debug_show.truncate_instruction(
             destination_of_truncated_value.address,
@@ -743,6 +1048,17 @@ impl BrilligContext {
             value_to_truncate.bit_size
         );
 
+        // A value which is already `bit_size` wide has no high bits to clear.
+        if bit_size == value_to_truncate.bit_size {
+            if destination_of_truncated_value.address != value_to_truncate.address {
+                self.mov_instruction(
+                    destination_of_truncated_value.address,
+                    value_to_truncate.address,
+                );
+            }
+            return;
+        }
+
         let mask = BigUint::from(2_u32).pow(bit_size) - BigUint::from(1_u32);
         let mask_constant = self.make_constant(
             FieldElement::from_be_bytes_reduce(&mask.to_bytes_be()).into(),
@@ -772,6 +1088,28 @@ impl BrilligContext {
         register
     }
 
+    /// Returns a register which holds the value of a constant, reusing the register loaded by a previous
+    /// call with the same constant in the current section instead of emitting another `Const` opcode.
+    ///
+    /// The register is shared so it must not be written to or deallocated. It is freed when the current
+    /// section ends or a function is called or returned from, so it must be read before emitting a loop,
+    /// a branch or a call.
+    pub(crate) fn cached_constant(&mut self, constant: Value, bit_size: u32) -> MemoryAddress {
+        if let Some(register) = self.cached_constants.get(&(constant, bit_size)) {
+            return *register;
+        }
+        let register = self.make_constant(constant, bit_size);
+        self.cached_constants.insert((constant, bit_size), register);
+        register
+    }
+
+    /// Frees the registers of the cached constants, as they are not loaded along every path into the code which follows.
+    fn clear_cached_constants(&mut self) {
+        for register in std::mem::take(&mut self.cached_constants).into_values() {
+            self.deallocate_register(register);
+        }
+    }
+
     /// Returns a register which holds the value of an usize constant
     pub(crate) fn make_usize_constant(&mut self, constant: Value) -> MemoryAddress {
         let register = self.allocate_register();
@@ -834,14 +1172,51 @@ impl BrilligContext {
         self.deallocate_register(scratch_register_j);
     }
 
+    /// Same as [`Self::modulo_instruction`], for a divisor known at compile time.
+    ///
+    /// The remainder of an unsigned division by a power of two is its low bits,
+    /// so it is computed with a single `And` against `divisor - 1`.
+    pub(crate) fn modulo_by_constant(
+        &mut self,
+        result_register: MemoryAddress,
+        left: MemoryAddress,
+        divisor: Value,
+        bit_size: u32,
+        signed: bool,
+    ) {
+        let divisor_field = divisor.to_field();
+        let is_power_of_two = divisor_field.fits_in_u128()
+            && divisor_field.to_u128().is_power_of_two()
+            && divisor_field.num_bits() <= bit_size;
+        if !signed && is_power_of_two {
+            let mask = self.make_constant(Value::from(divisor_field.to_u128() - 1), bit_size);
+            self.binary_instruction(
+                left,
+                mask,
+                result_register,
+                BrilligBinaryOp::Integer { op: BinaryIntOp::And, bit_size },
+            );
+            self.deallocate_register(mask);
+        } else {
+            let divisor = self.make_constant(divisor, bit_size);
+            self.modulo_instruction(result_register, left, divisor, bit_size, signed);
+            self.deallocate_register(divisor);
+        }
+    }
+
     /// Adds a unresolved external `Call` instruction to the bytecode.
     /// This calls into another function compiled into this brillig artifact.
     pub(crate) fn add_external_call_instruction<T: ToString>(&mut self, func_label: T) {
         self.debug_show.add_external_call_instruction(func_label.to_string());
+        if self.is_unreachable() {
+            return;
+        }
         self.obj.add_unresolved_external_call(
             BrilligOpcode::Call { location: 0 },
             func_label.to_string(),
         );
+        // The called function shares the register space, so it may write to the registers of the cached constants.
+        self.clear_cached_constants();
     }
 
     /// Returns the i'th register after the reserved ones
@@ -1045,44 +1420,27 @@ impl BrilligContext {
 
     /// This instruction will reverse the order of the elements in a vector.
     pub(crate) fn reverse_vector_in_place_instruction(&mut self, vector: BrilligVector) {
-        let iteration_count = self.allocate_register();
-        self.usize_op(vector.size, iteration_count, BinaryIntOp::UnsignedDiv, 2);
-
-        let start_value_register = self.allocate_register();
-        let index_at_end_of_array = self.allocate_register();
-        let end_value_register = self.allocate_register();
-
-        self.loop_instruction(iteration_count, |ctx, iterator_register| {
-            // Load both values
-            ctx.array_get(vector.pointer, iterator_register, start_value_register);
-
-            // The index at the end of array is size - 1 - iterator
-            ctx.mov_instruction(index_at_end_of_array, vector.size);
-            ctx.usize_op_in_place(index_at_end_of_array, BinaryIntOp::Sub, 1);
-            ctx.memory_op(
-                index_at_end_of_array,
-                iterator_register,
-                index_at_end_of_array,
-                BinaryIntOp::Sub,
-            );
-
-            ctx.array_get(vector.pointer, index_at_end_of_array, end_value_register);
-
-            // Write both values
-            ctx.array_set(vector.pointer, iterator_register, end_value_register);
-            ctx.array_set(vector.pointer, index_at_end_of_array, start_value_register);
-        });
-
-        self.deallocate_register(iteration_count);
-        self.deallocate_register(start_value_register);
-        self.deallocate_register(end_value_register);
-        self.deallocate_register(index_at_end_of_array);
+        self.array_reverse(vector.pointer, vector.size);
     }
 
     /// Sets a current call stack that the next pushed opcodes will be associated with.
     pub(crate) fn set_call_stack(&mut self, call_stack: CallStack) {
         self.obj.set_call_stack(call_stack);
     }
+
+    /// Sets the SSA instruction that the next statically sized allocations will be attributed to.
+    pub(crate) fn set_allocation_site(&mut self, allocation_site: Option<InstructionId>) {
+        self.obj.set_allocation_site(allocation_site);
+    }
+
+    /// Returns the number of memory slots statically allocated by the bytecode emitted so far.
+    ///
+    /// Memory is never freed, so this is the highest end address reached by these allocations, relative to
+    /// the initial stack pointer. It is an upper bound when allocations are made on exclusive branches,
+    /// and arrays whose size is only known at runtime are not counted.
+    pub(crate) fn memory_size(&self) -> usize {
+        self.obj.allocated_memory
+    }
 }
 
 /// Type to encapsulate the binary operation types in Brillig
@@ -1095,6 +1453,52 @@ pub(crate) enum BrilligBinaryOp {
     Modulo { is_signed_integer: bool, bit_size: u32 },
 }
 
+/// Returns whether execution never continues with the opcode following `opcode`.
+fn terminates_execution(opcode: &BrilligOpcode) -> bool {
+    matches!(
+        opcode,
+        BrilligOpcode::Stop { .. }
+            | BrilligOpcode::Return
+            | BrilligOpcode::Trap
+            | BrilligOpcode::Jump { .. }
+    )
+}
+
+/// Returns the registers which `opcode` writes a value to.
+///
+/// Writes through pointers only touch memory beyond the registers, so they are not included.
+fn registers_written_by(opcode: &BrilligOpcode) -> Vec<MemoryAddress> {
+    match opcode {
+        BrilligOpcode::BinaryFieldOp { destination, .. }
+        | BrilligOpcode::BinaryIntOp { destination, .. }
+        | BrilligOpcode::Cast { destination, .. }
+        | BrilligOpcode::Const { destination, .. }
+        | BrilligOpcode::Mov { destination, .. }
+        | BrilligOpcode::Load { destination, .. } => vec![*destination],
+        BrilligOpcode::ForeignCall { destinations, .. } => destinations
+            .iter()
+            .filter_map(|destination| match destination {
+                ValueOrArray::MemoryAddress(address) => Some(*address),
+                // The VM writes the length of the returned vector to its size register.
+                ValueOrArray::HeapVector(vector) => Some(vector.size),
+                ValueOrArray::HeapArray(_) => None,
+            })
+            .collect(),
+        BrilligOpcode::BlackBox(
+            BlackBoxOp::EcdsaSecp256k1 { result, .. }
+            | BlackBoxOp::EcdsaSecp256r1 { result, .. }
+            | BlackBoxOp::SchnorrVerify { result, .. }
+            | BlackBoxOp::PedersenHash { output: result, .. }
+            | BlackBoxOp::BigIntAdd { output: result, .. }
+            | BlackBoxOp::BigIntSub { output: result, .. }
+            | BlackBoxOp::BigIntMul { output: result, .. }
+            | BlackBoxOp::BigIntDiv { output: result, .. }
+            | BlackBoxOp::BigIntFromLeBytes { output: result, .. },
+        ) => vec![*result],
+        _ => Vec::new(),
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
     use std::vec;
@@ -1107,10 +1511,11 @@ pub(crate) mod tests {
     use acvm::brillig_vm::{VMStatus, VM};
     use acvm::{BlackBoxFunctionSolver, BlackBoxResolutionError, FieldElement};
 
+    use crate::brillig::brillig_ir::brillig_variable::SingleAddrVariable;
     use crate::brillig::brillig_ir::BrilligContext;
 
-    use super::artifact::{BrilligParameter, GeneratedBrillig};
-    use super::{BrilligOpcode, ReservedRegisters};
+    use super::artifact::{BrilligArtifact, BrilligParameter, GeneratedBrillig};
+    use super::{BrilligBinaryOp, BrilligOpcode, ReservedRegisters};
 
     pub(crate) struct DummyBlackBoxSolver;
 
@@ -1197,6 +1602,579 @@ pub(crate) mod tests {
         }
     }
 
+    /// Runs the bytecode of `artifact` with the memory starting with `initial_registers`,
+    /// and returns the memory once it finishes.
+    ///
+    /// Unlike [`create_entry_point_bytecode`], no entry point is generated, so values can be placed
+    /// directly at the registers which the context allocated, and arrays further along in memory.
+    pub(crate) fn execute(artifact: BrilligArtifact, initial_registers: Vec<Value>) -> Vec<Value> {
+        // The initial values are copied from the calldata by a prepended opcode.
+        let offset_location = |location: usize| location + 1;
+        let mut bytecode = vec![BrilligOpcode::CalldataCopy {
+            destination_address: MemoryAddress::from(0),
+            size: initial_registers.len(),
+            offset: 0,
+        }];
+        bytecode.extend(artifact.finish().byte_code.into_iter().map(|opcode| match opcode {
+            BrilligOpcode::Jump { location } => {
+                BrilligOpcode::Jump { location: offset_location(location) }
+            }
+            BrilligOpcode::JumpIf { condition, location } => {
+                BrilligOpcode::JumpIf { condition, location: offset_location(location) }
+            }
+            BrilligOpcode::JumpIfNot { condition, location } => {
+                BrilligOpcode::JumpIfNot { condition, location: offset_location(location) }
+            }
+            BrilligOpcode::Call { location } => {
+                BrilligOpcode::Call { location: offset_location(location) }
+            }
+            opcode => opcode,
+        }));
+
+        let mut vm = VM::new(initial_registers, &bytecode, vec![], &DummyBlackBoxSolver);
+        let status = vm.process_opcodes();
+        assert!(matches!(status, VMStatus::Finished { .. }), "VM did not finish: {status:?}");
+        vm.get_memory().to_vec()
+    }
+
+    /// Returns `registers` as memory values, zero-extended to `len` values.
+    pub(crate) fn memory_of(registers: &[u128], len: usize) -> Vec<Value> {
+        let mut memory: Vec<Value> = registers.iter().copied().map(Value::from).collect();
+        memory.resize(len, Value::from(0_u128));
+        memory
+    }
+
+    #[test]
+    fn lists_opcodes_with_their_comments() {
+        let mut context = create_context();
+        let register = context.allocate_register();
+        context.push_opcode_with_comment(
+            BrilligOpcode::Const {
+                destination: register,
+                bit_size: 32,
+                value: Value::from(7_u128),
+            },
+            "load the answer".to_string(),
+        );
+        context.push_opcode(BrilligOpcode::Mov { destination: register, source: register });
+        context.push_opcode_with_comment(BrilligOpcode::Trap, "unreachable".to_string());
+
+        let artifact = context.artifact();
+        assert_eq!(artifact.comments.get(&0).map(String::as_str), Some("load the answer"));
+        assert_eq!(artifact.comments.get(&1), None);
+        assert_eq!(artifact.comments.get(&2).map(String::as_str), Some("unreachable"));
+
+        let listing = artifact.bytecode_listing();
+        let lines: Vec<_> = listing.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].ends_with("// load the answer"));
+        assert!(!lines[1].contains("//"));
+        assert!(lines[2].ends_with("Trap // unreachable"));
+    }
+
+    #[test]
+    fn drops_opcodes_after_stop_until_the_next_label() {
+        let emit = |drop_unreachable_opcodes: bool| {
+            let mut context = create_context();
+            context.set_drop_unreachable_opcodes(drop_unreachable_opcodes);
+            let register = context.allocate_register();
+            context.stop_instruction();
+            context.const_instruction(register, Value::from(1_u128), 32);
+            context.constrain_with_message(register, "unreachable".to_string());
+            context.enter_context("after_stop");
+            context.const_instruction(register, Value::from(2_u128), 32);
+            context.artifact().finish()
+        };
+
+        let generated = emit(true);
+        assert_eq!(generated.byte_code.len(), 2);
+        assert!(matches!(generated.byte_code[0], BrilligOpcode::Stop { .. }));
+        assert!(matches!(generated.byte_code[1], BrilligOpcode::Const { bit_size: 32, .. }));
+        assert!(generated.assert_messages.is_empty());
+
+        // Unreachable opcodes are kept unless they are asked to be dropped.
+        let generated = emit(false);
+        assert_eq!(generated.byte_code.len(), 5);
+        assert_eq!(generated.assert_messages.len(), 1);
+    }
+
+    #[test]
+    fn drops_opcodes_after_returns_traps_and_jumps() {
+        let terminators: [fn(&mut BrilligContext, MemoryAddress); 4] = [
+            |context, register| context.return_instruction(&[register]),
+            |context, _| context.return_from_call_instruction(),
+            |context, _| context.push_opcode(BrilligOpcode::Trap),
+            |context, _| context.jump_instruction("elsewhere"),
+        ];
+        for terminate in terminators {
+            let mut context = create_context();
+            context.set_drop_unreachable_opcodes(true);
+            let register = context.allocate_register();
+            context.const_instruction(register, Value::from(1_u128), 32);
+            terminate(&mut context, register);
+
+            let length = context.obj.index_of_next_opcode();
+            context.const_instruction(register, Value::from(2_u128), 32);
+            assert_eq!(context.obj.index_of_next_opcode(), length);
+
+            // A label makes the following opcodes reachable again.
+            let (next_section, _) = context.reserve_next_section_label();
+            context.enter_section(next_section);
+            context.const_instruction(register, Value::from(2_u128), 32);
+            assert_eq!(context.obj.index_of_next_opcode(), length + 1);
+        }
+    }
+
+    #[test]
+    fn records_constraint_messages_at_their_trap() {
+        let mut context = create_context();
+        let condition = context.allocate_register();
+        context.const_instruction(condition, Value::from(1_u128), 1);
+        context.constrain_with_message(condition, "condition must hold".to_string());
+
+        let artifact = context.artifact();
+        let generated = artifact.clone().finish();
+        let trap_index = generated
+            .byte_code
+            .iter()
+            .position(|opcode| matches!(opcode, BrilligOpcode::Trap))
+            .expect("the constraint should trap");
+        assert_eq!(trap_index, 2);
+        assert_eq!(
+            generated.assert_messages.get(&trap_index).map(String::as_str),
+            Some("condition must hold")
+        );
+        assert_eq!(generated.assert_messages.len(), 1);
+
+        // The condition holds, so the trap is jumped over.
+        execute(artifact, memory_of(&[0, 0], 3));
+    }
+
+    #[test]
+    fn reports_the_memory_allocated_by_arrays() {
+        let mut context = create_context();
+        let first_array = context.allocate_register();
+        let second_array = context.allocate_register();
+        context.allocate_fixed_length_array(first_array, 3);
+        context.allocate_fixed_length_array(second_array, 5);
+        assert_eq!(context.memory_size(), 3 + 5);
+
+        // The stack pointer ends right after the second array.
+        let memory = execute(context.artifact(), memory_of(&[100], 4));
+        assert_eq!(memory[ReservedRegisters::stack_pointer().to_usize()], Value::from(108_u128));
+        assert_eq!(memory[second_array.to_usize()], Value::from(103_u128));
+    }
+
+    #[test]
+    fn computes_unsigned_and_signed_modulo() {
+        let modulo = |left: u128, right: u128, signed: bool| {
+            let mut context = create_context();
+            let left_register = context.allocate_register();
+            let right_register = context.allocate_register();
+            let result = context.allocate_register();
+            context.modulo_instruction(result, left_register, right_register, 8, signed);
+
+            let memory =
+                execute(context.artifact(), memory_of(&[0, 0, left, right], result.to_usize() + 1));
+            memory[result.to_usize()]
+        };
+
+        assert_eq!(modulo(17, 5, false), Value::from(2_u128));
+        assert_eq!(modulo(20, 5, false), Value::from(0_u128));
+        assert_eq!(modulo(5, 17, false), Value::from(5_u128));
+        // -17 % 5 == -2 for 8-bit signed integers, as the remainder has the sign of the dividend.
+        assert_eq!(modulo(256 - 17, 5, true), Value::from(256_u128 - 2));
+        assert_eq!(modulo(17, 256 - 5, true), Value::from(2_u128));
+    }
+
+    #[test]
+    fn masks_modulo_by_powers_of_two() {
+        let modulo_by_constant = |left: u128, divisor: u128, signed: bool| {
+            let mut context = create_context();
+            let left_register = context.allocate_register();
+            let result = context.allocate_register();
+            context.modulo_by_constant(result, left_register, Value::from(divisor), 8, signed);
+
+            let artifact = context.artifact();
+            let opcode_count = artifact.clone().finish().byte_code.len();
+            let memory = execute(artifact, memory_of(&[0, 0, left], result.to_usize() + 1));
+            (opcode_count, memory[result.to_usize()])
+        };
+
+        // A mask constant and an `And` for 16, against a constant and division, multiplication
+        // and subtraction for 17.
+        assert_eq!(modulo_by_constant(200, 16, false), (2, Value::from(200_u128 % 16)));
+        assert_eq!(modulo_by_constant(200, 17, false), (4, Value::from(200_u128 % 17)));
+        // The low bits of negative integers are not their remainder.
+        assert_eq!(modulo_by_constant(256 - 17, 16, true), (4, Value::from(256_u128 - 1)));
+    }
+
+    fn copy_fixed_length_array_bytecode(
+        num_elements: usize,
+        unroll_threshold: usize,
+    ) -> Vec<BrilligOpcode> {
+        let mut context = create_context();
+        context.set_unroll_threshold(unroll_threshold);
+
+        let source_pointer = context.allocate_register();
+        let destination_pointer = context.allocate_register();
+        context.copy_fixed_length_array_instruction(
+            source_pointer,
+            destination_pointer,
+            num_elements,
+        );
+        context.stop_instruction();
+
+        context.artifact().finish().byte_code
+    }
+
+    /// Decrements `counter` down to zero in a while loop allowed to run for at most `max_iterations`,
+    /// returning the final counter alongside the number of iterations which were run.
+    fn count_down_bytecode(max_iterations: usize) -> Vec<BrilligOpcode> {
+        let mut context = create_context();
+        let counter = context.allocate_register();
+        let iterations = context.make_usize_constant(0_u128.into());
+
+        context.while_loop_instruction(
+            max_iterations,
+            |ctx| {
+                let zero = ctx.make_usize_constant(0_u128.into());
+                let is_zero = ctx.allocate_register();
+                ctx.memory_op(counter, zero, is_zero, BinaryIntOp::Equals);
+                ctx.deallocate_register(zero);
+                ctx.not_instruction(
+                    SingleAddrVariable { address: is_zero, bit_size: 1 },
+                    SingleAddrVariable { address: is_zero, bit_size: 1 },
+                );
+                is_zero
+            },
+            |ctx| {
+                ctx.usize_op_in_place(counter, BinaryIntOp::Sub, 1);
+                ctx.usize_op_in_place(iterations, BinaryIntOp::Add, 1);
+            },
+        );
+        context.return_instruction(&[counter, iterations]);
+
+        let arguments = vec![BrilligParameter::SingleAddr(32)];
+        let returns = vec![BrilligParameter::SingleAddr(32), BrilligParameter::SingleAddr(32)];
+        create_entry_point_bytecode(context, arguments, returns).byte_code
+    }
+
+    #[test]
+    fn while_loop_runs_until_condition_is_false() {
+        let bytecode = count_down_bytecode(10);
+        let (vm, return_data_offset, return_data_size) =
+            create_and_run_vm(vec![Value::from(5_usize)], &bytecode);
+        assert_eq!(return_data_size, 2);
+        assert_eq!(
+            vm.get_memory()[return_data_offset..(return_data_offset + 2)],
+            [Value::from(0_usize), Value::from(5_usize)]
+        );
+
+        // The body never runs if the condition is initially false
+        let (vm, return_data_offset, _) = create_and_run_vm(vec![Value::from(0_usize)], &bytecode);
+        assert_eq!(
+            vm.get_memory()[return_data_offset..(return_data_offset + 2)],
+            [Value::from(0_usize), Value::from(0_usize)]
+        );
+    }
+
+    #[test]
+    fn while_loop_traps_past_max_iterations() {
+        let bytecode = count_down_bytecode(3);
+        let mut vm = VM::new(vec![Value::from(5_usize)], &bytecode, vec![], &DummyBlackBoxSolver);
+        assert!(matches!(vm.process_opcodes(), VMStatus::Failure { .. }));
+    }
+
+    fn contains_loop(bytecode: &[BrilligOpcode]) -> bool {
+        bytecode.iter().any(|opcode| matches!(opcode, BrilligOpcode::JumpIf { .. }))
+    }
+
+    /// Emits a jump over a `Stop` opcode if `condition` holds, placing a label at the jump
+    /// itself when `jump_is_labelled`.
+    fn jump_over_stop_bytecode(condition: bool, jump_is_labelled: bool) -> Vec<BrilligOpcode> {
+        let mut context = create_context();
+        let condition = context.make_constant(Value::from(condition), 1);
+        if jump_is_labelled {
+            let (jump_section, _) = context.reserve_next_section_label();
+            context.enter_section(jump_section);
+        }
+        let (exit_section, exit_label) = context.reserve_next_section_label();
+        context.jump_if_instruction(condition, exit_label);
+        context.stop_instruction();
+        context.enter_section(exit_section);
+        context.stop_instruction();
+        context.artifact().finish().byte_code
+    }
+
+    #[test]
+    fn replaces_jump_if_on_true_constant_with_jump() {
+        let bytecode = jump_over_stop_bytecode(true, false);
+        assert_eq!(bytecode.len(), 4);
+        assert_eq!(bytecode[1], BrilligOpcode::Jump { location: 3 });
+    }
+
+    #[test]
+    fn removes_jump_if_on_false_constant() {
+        let bytecode = jump_over_stop_bytecode(false, false);
+        assert_eq!(bytecode.len(), 3);
+        assert!(matches!(bytecode[0], BrilligOpcode::Const { .. }));
+        assert!(matches!(bytecode[1], BrilligOpcode::Stop { .. }));
+    }
+
+    #[test]
+    fn keeps_jump_if_which_is_a_jump_target() {
+        for condition in [true, false] {
+            let bytecode = jump_over_stop_bytecode(condition, true);
+            assert_eq!(bytecode.len(), 4);
+            assert!(matches!(bytecode[1], BrilligOpcode::JumpIf { location: 3, .. }));
+        }
+    }
+
+    #[test]
+    fn range_loop_visits_each_index_in_range() {
+        let returns = vec![BrilligParameter::SingleAddr(32)];
+
+        let mut context = create_context();
+        let sum = context.make_usize_constant(0_usize.into());
+        let start = context.make_usize_constant(2_usize.into());
+        let end = context.make_usize_constant(5_usize.into());
+        context.range_loop_instruction(start, end, |ctx, iterator| {
+            ctx.memory_op(sum, iterator, sum, BinaryIntOp::Add);
+        });
+        context.return_instruction(&[sum]);
+
+        let bytecode = create_entry_point_bytecode(context, vec![], returns).byte_code;
+        let (vm, return_data_offset, return_data_size) = create_and_run_vm(vec![], &bytecode);
+        assert_eq!(return_data_size, 1);
+        assert_eq!(vm.get_memory()[return_data_offset], Value::from(2_usize + 3 + 4));
+    }
+
+    #[test]
+    fn array_loops_do_not_grow_with_the_number_of_elements() {
+        let arrays_binary_bytecode = |num_elements: usize| {
+            let mut context = create_context();
+            let lhs = context.allocate_register();
+            let rhs = context.allocate_register();
+            let result = context.allocate_register();
+            let num_elements = context.make_usize_constant(num_elements.into());
+            context.arrays_binary_instruction(
+                lhs,
+                rhs,
+                result,
+                num_elements,
+                BrilligBinaryOp::Integer { op: BinaryIntOp::Add, bit_size: 32 },
+            );
+            context.artifact().finish().byte_code.len()
+        };
+        assert_eq!(arrays_binary_bytecode(4), arrays_binary_bytecode(1000));
+    }
+
+    #[test]
+    fn reuses_cached_constants_within_a_section() {
+        let count_consts = |bytecode: &[BrilligOpcode]| {
+            bytecode.iter().filter(|opcode| matches!(opcode, BrilligOpcode::Const { .. })).count()
+        };
+
+        let mut context = create_context();
+        let first = context.cached_constant(Value::from(1_usize), 32);
+        let second = context.cached_constant(Value::from(1_usize), 32);
+        assert_eq!(first, second);
+        assert_ne!(context.cached_constant(Value::from(1_usize), 64), first);
+        let (next_section, _) = context.reserve_next_section_label();
+        context.enter_section(next_section);
+        // The registers are freed in the next section, so the constant is loaded again.
+        let reloaded = context.cached_constant(Value::from(1_usize), 32);
+        assert!(reloaded == first || context.allocate_register() == first);
+        assert_eq!(count_consts(&context.artifact().finish().byte_code), 3);
+
+        let mut context = create_context();
+        let value = SingleAddrVariable { address: context.allocate_register(), bit_size: 8 };
+        context.not_instruction(value, value);
+        context.not_instruction(value, value);
+        assert_eq!(count_consts(&context.artifact().finish().byte_code), 1);
+    }
+
+    #[test]
+    fn masks_field_values_truncated_to_non_byte_aligned_widths() {
+        let field_bit_size = FieldElement::max_num_bits();
+
+        let mut context = create_context();
+        let value =
+            SingleAddrVariable { address: context.allocate_register(), bit_size: field_bit_size };
+        let result = SingleAddrVariable { address: context.allocate_register(), bit_size: 17 };
+        context.wrapping_truncate_instruction(result, value, 17);
+        let bytecode = context.artifact().finish().byte_code;
+
+        let BrilligOpcode::Const { destination: mask, .. } = bytecode[0] else {
+            panic!("expected the mask constant to be emitted first, got {bytecode:?}");
+        };
+        assert_eq!(
+            bytecode,
+            vec![
+                BrilligOpcode::Const {
+                    destination: mask,
+                    value: Value::from((1_usize << 17) - 1),
+                    bit_size: field_bit_size,
+                },
+                BrilligOpcode::BinaryIntOp {
+                    destination: result.address,
+                    op: BinaryIntOp::And,
+                    bit_size: field_bit_size,
+                    lhs: value.address,
+                    rhs: mask,
+                },
+            ]
+        );
+
+        // The high bits of the field value are cleared.
+        let returns = vec![BrilligParameter::SingleAddr(17)];
+        let mut context = create_context();
+        let value =
+            SingleAddrVariable { address: context.allocate_register(), bit_size: field_bit_size };
+        let result = SingleAddrVariable { address: context.allocate_register(), bit_size: 17 };
+        context.const_instruction(value.address, Value::from((5_usize << 17) + 3), field_bit_size);
+        context.wrapping_truncate_instruction(result, value, 17);
+        context.return_instruction(&[result.address]);
+
+        let bytecode = create_entry_point_bytecode(context, vec![], returns).byte_code;
+        let (vm, return_data_offset, _) = create_and_run_vm(vec![], &bytecode);
+        assert_eq!(vm.get_memory()[return_data_offset], Value::from(3_usize));
+    }
+
+    #[test]
+    fn truncates_values_of_the_target_width_with_a_mov() {
+        let mut context = create_context();
+        let value = SingleAddrVariable { address: context.allocate_register(), bit_size: 17 };
+        let result = SingleAddrVariable { address: context.allocate_register(), bit_size: 17 };
+        context.wrapping_truncate_instruction(result, value, 17);
+        assert_eq!(
+            context.artifact().finish().byte_code,
+            vec![BrilligOpcode::Mov { destination: result.address, source: value.address }]
+        );
+    }
+
+    #[test]
+    fn calls_subroutines_from_multiple_sites() {
+        let returns = vec![BrilligParameter::SingleAddr(32)];
+
+        let mut context = create_context();
+        let counter = context.make_usize_constant(0_usize.into());
+        let one = context.make_usize_constant(1_usize.into());
+        let (subroutine_section, subroutine_label) = context.reserve_next_section_label();
+        let (end_section, end_label) = context.reserve_next_section_label();
+        context.call_instruction(&subroutine_label);
+        context.call_instruction(&subroutine_label);
+        context.jump_instruction(&end_label);
+
+        context.enter_section(subroutine_section);
+        context.memory_op(counter, one, counter, BinaryIntOp::Add);
+        context.return_from_call_instruction();
+
+        context.enter_section(end_section);
+        context.return_instruction(&[counter]);
+
+        let bytecode = create_entry_point_bytecode(context, vec![], returns).byte_code;
+        let call_locations: Vec<_> = bytecode
+            .iter()
+            .filter_map(|opcode| match opcode {
+                BrilligOpcode::Call { location } => Some(*location),
+                _ => None,
+            })
+            .collect();
+        // The entry point calls the function, which calls the subroutine twice.
+        assert_eq!(call_locations.len(), 3);
+        assert_eq!(call_locations[1], call_locations[2]);
+        assert!(matches!(bytecode[call_locations[1] + 1], BrilligOpcode::Return));
+
+        let (vm, return_data_offset, return_data_size) = create_and_run_vm(vec![], &bytecode);
+        assert_eq!(return_data_size, 1);
+        assert_eq!(vm.get_memory()[return_data_offset], Value::from(2_usize));
+    }
+
+    #[test]
+    fn reloads_cached_constants_after_calling_subroutines() {
+        let returns = vec![BrilligParameter::SingleAddr(32)];
+
+        let mut context = create_context();
+        let (subroutine_section, subroutine_label) = context.reserve_next_section_label();
+        let (main_section, main_label) = context.reserve_next_section_label();
+        context.jump_instruction(&main_label);
+
+        // The subroutine is emitted first, so its freed register is handed out to the caller's constant.
+        context.enter_section(subroutine_section);
+        let seven = context.make_constant(Value::from(7_usize), 32);
+        context.deallocate_register(seven);
+        context.return_from_call_instruction();
+
+        context.enter_section(main_section);
+        let two = context.cached_constant(Value::from(2_usize), 32);
+        assert_eq!(two, seven);
+        context.call_instruction(&subroutine_label);
+        let two = context.cached_constant(Value::from(2_usize), 32);
+        context.return_instruction(&[two]);
+
+        let bytecode = create_entry_point_bytecode(context, vec![], returns).byte_code;
+        let (vm, return_data_offset, return_data_size) = create_and_run_vm(vec![], &bytecode);
+        assert_eq!(return_data_size, 1);
+        assert_eq!(vm.get_memory()[return_data_offset], Value::from(2_usize));
+    }
+
+    #[test]
+    fn reloads_cached_constants_after_external_calls() {
+        let returns = vec![BrilligParameter::SingleAddr(8)];
+
+        let mut context = create_context();
+        let value = SingleAddrVariable { address: context.allocate_register(), bit_size: 8 };
+        context.const_instruction(value.address, Value::from(5_usize), 8);
+        let result = SingleAddrVariable { address: context.allocate_register(), bit_size: 8 };
+        context.not_instruction(value, result);
+        context.add_external_call_instruction("callee");
+        context.not_instruction(value, result);
+        context.return_instruction(&[result.address]);
+
+        // The callee overwrites every low register but the one holding `value`.
+        context.enter_context("callee");
+        for index in 1..8 {
+            let register = context.register(index);
+            context.const_instruction(register, Value::from(0_usize), 8);
+        }
+        context.return_from_call_instruction();
+
+        let bytecode = create_entry_point_bytecode(context, vec![], returns).byte_code;
+        let (vm, return_data_offset, return_data_size) = create_and_run_vm(vec![], &bytecode);
+        assert_eq!(return_data_size, 1);
+        assert_eq!(vm.get_memory()[return_data_offset], Value::from(250_usize));
+    }
+
+    #[test]
+    fn records_returns_of_unassigned_registers() {
+        let mut context = create_context();
+        let assigned = context.allocate_register();
+        context.const_instruction(assigned, Value::from(1_usize), 32);
+        context.return_instruction(&[assigned]);
+        assert!(context.artifact().unassigned_return.is_none());
+
+        let mut context = create_context();
+        let assigned = context.allocate_register();
+        let unassigned = context.allocate_register();
+        context.const_instruction(assigned, Value::from(1_usize), 32);
+        context.return_instruction(&[assigned, unassigned]);
+        assert!(context.artifact().unassigned_return.is_some());
+    }
+
+    #[test]
+    fn unrolls_array_copies_within_threshold() {
+        assert!(!contains_loop(&copy_fixed_length_array_bytecode(4, 4)));
+        assert!(!contains_loop(&copy_fixed_length_array_bytecode(1, 4)));
+    }
+
+    #[test]
+    fn emits_loops_for_array_copies_above_threshold() {
+        assert!(contains_loop(&copy_fixed_length_array_bytecode(5, 4)));
+        assert!(contains_loop(&copy_fixed_length_array_bytecode(2, 0)));
+    }
+
     /// Test a Brillig foreign call returning a vector
     #[test]
     fn test_brillig_ir_foreign_call_return_vector() {
@@ -1,7 +1,8 @@
-use acvm::acir::brillig::Opcode as BrilligOpcode;
+use acvm::acir::brillig::{MemoryAddress, Opcode as BrilligOpcode, Value};
 use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
 
-use crate::ssa::ir::dfg::CallStack;
+use crate::ssa::ir::{dfg::CallStack, instruction::InstructionId};
 
 /// Represents a parameter or a return value of a function.
 #[derive(Debug, Clone)]
@@ -30,6 +31,8 @@ pub(crate) struct BrilligArtifact {
     pub(crate) byte_code: Vec<BrilligOpcode>,
     /// A map of bytecode positions to assertion messages
     pub(crate) assert_messages: BTreeMap<OpcodeLocation, String>,
+    /// A map of bytecode positions to comments describing the opcodes, to help debugging the generated bytecode.
+    pub(crate) comments: BTreeMap<OpcodeLocation, String>,
     /// The set of jumps that need to have their locations
     /// resolved.
     unresolved_jumps: Vec<(JumpInstructionPosition, UnresolvedJumpLocation)>,
@@ -46,6 +49,14 @@ pub(crate) struct BrilligArtifact {
     locations: BTreeMap<OpcodeLocation, CallStack>,
     /// The current call stack. All opcodes that are pushed will be associated with this call stack.
     call_stack: CallStack,
+    /// The SSA instruction currently being compiled. All statically sized allocations will be attributed to it.
+    allocation_site: Option<InstructionId>,
+    /// Maps SSA instructions to the number of memory slots which they statically allocate.
+    pub(crate) allocation_sites: BTreeMap<InstructionId, usize>,
+    /// The number of memory slots statically allocated by the bytecode, whether or not they have an allocation site.
+    pub(crate) allocated_memory: usize,
+    /// The call stack of the first return which reads a register that the function never writes to.
+    pub(crate) unassigned_return: Option<CallStack>,
 }
 
 /// A pointer to a location in the opcode.
@@ -145,6 +156,10 @@ impl BrilligArtifact {
             self.assert_messages.insert(position_in_bytecode + offset, message.clone());
         }
 
+        for (position_in_bytecode, comment) in &obj.comments {
+            self.comments.insert(position_in_bytecode + offset, comment.clone());
+        }
+
         for (position_in_bytecode, call_stack) in obj.locations.iter() {
             self.locations.insert(position_in_bytecode + offset, call_stack.clone());
         }
@@ -191,6 +206,7 @@ impl BrilligArtifact {
             BrilligOpcode::JumpIfNot { .. }
                 | BrilligOpcode::JumpIf { .. }
                 | BrilligOpcode::Jump { .. }
+                | BrilligOpcode::Call { .. }
         )
     }
 
@@ -204,6 +220,23 @@ impl BrilligArtifact {
         );
     }
 
+    /// Returns the value of `register` when execution reaches the next opcode, if it is known.
+    ///
+    /// This is only the case if the last opcode loads a constant into `register` and no label points
+    /// to the next opcode, as jumping there could skip the load.
+    pub(crate) fn constant_at_next_opcode(&self, register: MemoryAddress) -> Option<Value> {
+        let next_opcode = self.index_of_next_opcode();
+        if self.labels.values().any(|position| *position == next_opcode) {
+            return None;
+        }
+        match self.byte_code.last()? {
+            BrilligOpcode::Const { destination, value, .. } if *destination == register => {
+                Some(*value)
+            }
+            _ => None,
+        }
+    }
+
     /// Returns the index of the next opcode.
     ///
     /// This is useful for labelling regions of code
@@ -257,8 +290,45 @@ impl BrilligArtifact {
         self.call_stack = call_stack;
     }
 
+    pub(crate) fn set_allocation_site(&mut self, allocation_site: Option<InstructionId>) {
+        self.allocation_site = allocation_site;
+    }
+
+    /// Records an allocation of `size` memory slots, attributing it to the current allocation site, if any.
+    pub(crate) fn add_allocation(&mut self, size: usize) {
+        self.allocated_memory += size;
+        if let Some(allocation_site) = self.allocation_site {
+            *self.allocation_sites.entry(allocation_site).or_default() += size;
+        }
+    }
+
+    /// Records that the return being compiled reads a register which was never assigned a value.
+    pub(crate) fn add_unassigned_return(&mut self) {
+        if self.unassigned_return.is_none() {
+            self.unassigned_return = Some(self.call_stack.clone());
+        }
+    }
+
     pub(crate) fn add_assert_message_to_last_opcode(&mut self, message: String) {
         let position = self.index_of_next_opcode() - 1;
         self.assert_messages.insert(position, message);
     }
+
+    pub(crate) fn add_comment_to_last_opcode(&mut self, comment: String) {
+        let position = self.index_of_next_opcode() - 1;
+        self.comments.insert(position, comment);
+    }
+
+    /// Lists the opcodes of the bytecode one per line, followed by their comment if they have one.
+    pub(crate) fn bytecode_listing(&self) -> String {
+        let mut listing = String::new();
+        for (position, opcode) in self.byte_code.iter().enumerate() {
+            match self.comments.get(&position) {
+                Some(comment) => writeln!(listing, "{position:>6}: {opcode:?} // {comment}"),
+                None => writeln!(listing, "{position:>6}: {opcode:?}"),
+            }
+            .expect("writing to a string cannot fail");
+        }
+        listing
+    }
 }
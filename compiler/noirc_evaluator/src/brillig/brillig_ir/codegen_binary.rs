@@ -0,0 +1,1784 @@
+use acvm::{
+    acir::brillig::{BinaryFieldOp, BinaryIntOp, MemoryAddress, Value},
+    FieldElement,
+};
+
+use super::{brillig_variable::SingleAddrVariable, BrilligBinaryOp, BrilligContext};
+
+impl BrilligContext {
+    /// Emits a `lhs < rhs` comparison between two integers of `bit_size` bits
+    /// and stores the boolean outcome in `result`.
+    pub(crate) fn less_than_instruction(
+        &mut self,
+        lhs: MemoryAddress,
+        rhs: MemoryAddress,
+        result: MemoryAddress,
+        bit_size: u32,
+        signed: bool,
+    ) {
+        self.integer_comparison_instruction(
+            lhs,
+            rhs,
+            result,
+            BinaryIntOp::LessThan,
+            bit_size,
+            signed,
+        );
+    }
+
+    /// Emits a `lhs <= rhs` comparison between two integers of `bit_size` bits
+    /// and stores the boolean outcome in `result`.
+    pub(crate) fn less_than_equal_instruction(
+        &mut self,
+        lhs: MemoryAddress,
+        rhs: MemoryAddress,
+        result: MemoryAddress,
+        bit_size: u32,
+        signed: bool,
+    ) {
+        self.integer_comparison_instruction(
+            lhs,
+            rhs,
+            result,
+            BinaryIntOp::LessThanEquals,
+            bit_size,
+            signed,
+        );
+    }
+
+    /// Emits a `lhs == rhs` comparison between two integers of `bit_size` bits
+    /// and stores the boolean outcome in `result`.
+    ///
+    /// Equality doesn't depend on the signedness of the operands.
+    pub(crate) fn equal_instruction(
+        &mut self,
+        lhs: MemoryAddress,
+        rhs: MemoryAddress,
+        result: MemoryAddress,
+        bit_size: u32,
+    ) {
+        self.binary_instruction(
+            lhs,
+            rhs,
+            result,
+            BrilligBinaryOp::Integer { op: BinaryIntOp::Equals, bit_size },
+        );
+    }
+
+    /// Brillig only supports unsigned integer comparisons.
+    /// These always store either 0 or 1 in `result`.
+    ///
+    /// Signed operands are compared by flipping their sign bit first, which maps
+    /// the two's complement ordering onto the unsigned ordering of the same width.
+    /// This is the same as biasing both operands by `2^(bit_size - 1)` modulo `2^bit_size`.
+    fn integer_comparison_instruction(
+        &mut self,
+        lhs: MemoryAddress,
+        rhs: MemoryAddress,
+        result: MemoryAddress,
+        op: BinaryIntOp,
+        bit_size: u32,
+        signed: bool,
+    ) {
+        if !signed {
+            self.binary_instruction(lhs, rhs, result, BrilligBinaryOp::Integer { op, bit_size });
+            return;
+        }
+
+        let sign_bit = FieldElement::from(2_i128).pow(&FieldElement::from((bit_size - 1) as i128));
+        let sign_bit_register = self.make_constant(sign_bit.into(), bit_size);
+        let biased_lhs = self.allocate_register();
+        let biased_rhs = self.allocate_register();
+
+        let flip_sign_bit = BrilligBinaryOp::Integer { op: BinaryIntOp::Xor, bit_size };
+        self.binary_instruction(lhs, sign_bit_register, biased_lhs, flip_sign_bit);
+        self.binary_instruction(rhs, sign_bit_register, biased_rhs, flip_sign_bit);
+        self.binary_instruction(
+            biased_lhs,
+            biased_rhs,
+            result,
+            BrilligBinaryOp::Integer { op, bit_size },
+        );
+
+        self.deallocate_register(sign_bit_register);
+        self.deallocate_register(biased_lhs);
+        self.deallocate_register(biased_rhs);
+    }
+
+    /// Traps unless the unsigned integer `tag` is the tag of one of the `num_variants` variants
+    /// of a tagged union, i.e. `tag < num_variants`.
+    ///
+    /// `num_variants` must be representable in the bit size of `tag`.
+    #[allow(dead_code)]
+    pub(crate) fn assert_valid_tag(&mut self, tag: SingleAddrVariable, num_variants: usize) {
+        let num_variants_register = self.make_constant(num_variants.into(), tag.bit_size);
+        let is_valid_tag = self.allocate_register();
+        self.less_than_instruction(
+            tag.address,
+            num_variants_register,
+            is_valid_tag,
+            tag.bit_size,
+            false,
+        );
+        self.constrain_instruction(is_valid_tag, Some("Invalid enum tag".to_string()));
+        self.deallocate_register(is_valid_tag);
+        self.deallocate_register(num_variants_register);
+    }
+
+    /// Stores `left / right` in `result` for integers of `bit_size` bits.
+    ///
+    /// The outcome of a division by zero is left up to the VM, see [`Self::checked_divide_instruction`].
+    pub(crate) fn divide_instruction(
+        &mut self,
+        result: MemoryAddress,
+        left: MemoryAddress,
+        right: MemoryAddress,
+        bit_size: u32,
+        signed: bool,
+    ) {
+        let op = if signed { BinaryIntOp::SignedDiv } else { BinaryIntOp::UnsignedDiv };
+        self.binary_instruction(left, right, result, BrilligBinaryOp::Integer { op, bit_size });
+    }
+
+    /// Stores `left / right` in `result` for integers of `bit_size` bits, trapping if `right` is zero.
+    #[allow(dead_code)]
+    pub(crate) fn checked_divide_instruction(
+        &mut self,
+        result: MemoryAddress,
+        left: MemoryAddress,
+        right: MemoryAddress,
+        bit_size: u32,
+        signed: bool,
+    ) {
+        self.trap_if_zero(right, bit_size);
+        self.divide_instruction(result, left, right, bit_size, signed);
+    }
+
+    /// Stores `left % right` in `result` for integers of `bit_size` bits, trapping if `right` is zero.
+    #[allow(dead_code)]
+    pub(crate) fn checked_modulo_instruction(
+        &mut self,
+        result: MemoryAddress,
+        left: MemoryAddress,
+        right: MemoryAddress,
+        bit_size: u32,
+        signed: bool,
+    ) {
+        self.trap_if_zero(right, bit_size);
+        self.modulo_instruction(result, left, right, bit_size, signed);
+    }
+
+    /// Traps with a division by zero error if the integer `divisor` is zero.
+    fn trap_if_zero(&mut self, divisor: MemoryAddress, bit_size: u32) {
+        let zero = self.make_constant(0_usize.into(), bit_size);
+        let is_nonzero = SingleAddrVariable { address: self.allocate_register(), bit_size: 1 };
+        self.equal_instruction(divisor, zero, is_nonzero.address, bit_size);
+        self.not_instruction(is_nonzero, is_nonzero);
+        self.constrain_instruction(
+            is_nonzero.address,
+            Some("Attempted to divide by zero".to_string()),
+        );
+        self.deallocate_register(is_nonzero.address);
+        self.deallocate_register(zero);
+    }
+
+    /// Stores the smaller of the integers `lhs` and `rhs` in `result`, without branching.
+    pub(crate) fn min_instruction(
+        &mut self,
+        lhs: MemoryAddress,
+        rhs: MemoryAddress,
+        result: MemoryAddress,
+        bit_size: u32,
+        signed: bool,
+    ) {
+        let lhs_is_less = self.allocate_register();
+        self.less_than_instruction(lhs, rhs, lhs_is_less, bit_size, signed);
+        self.select_instruction(lhs_is_less, lhs, rhs, result, bit_size);
+        self.deallocate_register(lhs_is_less);
+    }
+
+    /// Stores the greater of the integers `lhs` and `rhs` in `result`, without branching.
+    pub(crate) fn max_instruction(
+        &mut self,
+        lhs: MemoryAddress,
+        rhs: MemoryAddress,
+        result: MemoryAddress,
+        bit_size: u32,
+        signed: bool,
+    ) {
+        let rhs_is_less = self.allocate_register();
+        self.less_than_instruction(rhs, lhs, rhs_is_less, bit_size, signed);
+        self.select_instruction(rhs_is_less, lhs, rhs, result, bit_size);
+        self.deallocate_register(rhs_is_less);
+    }
+
+    /// Clamps the integer `value` into the inclusive range `[min, max]`, storing it in `result`.
+    #[allow(dead_code)]
+    pub(crate) fn clamp_instruction(
+        &mut self,
+        value: MemoryAddress,
+        min: MemoryAddress,
+        max: MemoryAddress,
+        result: MemoryAddress,
+        bit_size: u32,
+        signed: bool,
+    ) {
+        // `result` may alias `max`, so the lower bound is applied in a scratch register.
+        let at_least_min = self.allocate_register();
+        self.max_instruction(value, min, at_least_min, bit_size, signed);
+        self.min_instruction(at_least_min, max, result, bit_size, signed);
+        self.deallocate_register(at_least_min);
+    }
+
+    /// Increments the integer `counter` of `bit_size` bits in place, unless it already equals `max`.
+    ///
+    /// The increment is always computed and discarded with [`Self::select_instruction`] at the cap,
+    /// so that no branch is needed.
+    #[allow(dead_code)]
+    pub(crate) fn increment_saturating(
+        &mut self,
+        counter: MemoryAddress,
+        max: MemoryAddress,
+        bit_size: u32,
+    ) {
+        let at_max = self.allocate_register();
+        let incremented = self.allocate_register();
+        let one = self.cached_constant(Value::from(1_u128), bit_size);
+        self.equal_instruction(counter, max, at_max, bit_size);
+        self.binary_instruction(
+            counter,
+            one,
+            incremented,
+            BrilligBinaryOp::Integer { op: BinaryIntOp::Add, bit_size },
+        );
+        self.select_instruction(at_max, counter, incremented, counter, bit_size);
+        self.deallocate_register(at_max);
+        self.deallocate_register(incremented);
+    }
+
+    /// Casts the integer `source` to the bit size of `destination`.
+    ///
+    /// Narrowing casts truncate the value, as does [`BrilligContext::cast_instruction`]. Widening casts of
+    /// `signed` values replicate the sign bit into the new high bits so that negative values are preserved,
+    /// which is done without branching by adding `2^to_bits - 2^from_bits` to negative values.
+    #[allow(dead_code)]
+    pub(crate) fn integer_cast_instruction(
+        &mut self,
+        destination: SingleAddrVariable,
+        source: SingleAddrVariable,
+        signed: bool,
+    ) {
+        let (from_bits, to_bits) = (source.bit_size, destination.bit_size);
+        if !signed || to_bits <= from_bits {
+            self.cast_instruction(destination, source);
+            return;
+        }
+
+        // `destination` may alias `source`, so the sign is read before the cast.
+        let power_of_two =
+            |bits: u32| FieldElement::from(2_i128).pow(&FieldElement::from(bits as i128));
+        let sign_bit = self.make_constant(power_of_two(from_bits - 1).into(), from_bits);
+        let is_negative = self.allocate_register();
+        self.less_than_equal_instruction(sign_bit, source.address, is_negative, from_bits, false);
+
+        let extension = power_of_two(to_bits) - power_of_two(from_bits);
+        let high_bits = self.make_constant(extension.into(), to_bits);
+        self.binary_instruction(
+            high_bits,
+            is_negative,
+            high_bits,
+            BrilligBinaryOp::Integer { op: BinaryIntOp::Mul, bit_size: to_bits },
+        );
+        self.cast_instruction(destination, source);
+        self.binary_instruction(
+            destination.address,
+            high_bits,
+            destination.address,
+            BrilligBinaryOp::Integer { op: BinaryIntOp::Add, bit_size: to_bits },
+        );
+
+        self.deallocate_register(sign_bit);
+        self.deallocate_register(is_negative);
+        self.deallocate_register(high_bits);
+    }
+
+    /// Stores `then_value` in `result` if the boolean `condition` is true, and `else_value` otherwise.
+    ///
+    /// The selection is computed arithmetically as `else_value + condition * (then_value - else_value)`,
+    /// without branching.
+    pub(crate) fn conditional_select(
+        &mut self,
+        condition: MemoryAddress,
+        then_value: MemoryAddress,
+        else_value: MemoryAddress,
+        result: MemoryAddress,
+    ) {
+        let difference = self.allocate_register();
+        self.binary_instruction(
+            then_value,
+            else_value,
+            difference,
+            BrilligBinaryOp::Field { op: BinaryFieldOp::Sub },
+        );
+        self.binary_instruction(
+            difference,
+            condition,
+            difference,
+            BrilligBinaryOp::Field { op: BinaryFieldOp::Mul },
+        );
+        self.binary_instruction(
+            else_value,
+            difference,
+            result,
+            BrilligBinaryOp::Field { op: BinaryFieldOp::Add },
+        );
+        self.deallocate_register(difference);
+    }
+
+    /// Stores `if_true` in `result` if the boolean `condition` is true, and `if_false` otherwise,
+    /// for integers of `bit_size` bits.
+    ///
+    /// This is the integer counterpart of [`Self::conditional_select`], computing
+    /// `if_false + condition * (if_true - if_false)` with wrapping integer operations of `bit_size` bits.
+    /// The subtraction wraps when `if_true < if_false` and the addition wraps back, so the outcome is
+    /// exact as long as both values fit in `bit_size` bits.
+    pub(crate) fn select_instruction(
+        &mut self,
+        condition: MemoryAddress,
+        if_true: MemoryAddress,
+        if_false: MemoryAddress,
+        result: MemoryAddress,
+        bit_size: u32,
+    ) {
+        let difference = self.allocate_register();
+        self.binary_instruction(
+            if_true,
+            if_false,
+            difference,
+            BrilligBinaryOp::Integer { op: BinaryIntOp::Sub, bit_size },
+        );
+        self.binary_instruction(
+            difference,
+            condition,
+            difference,
+            BrilligBinaryOp::Integer { op: BinaryIntOp::Mul, bit_size },
+        );
+        self.binary_instruction(
+            if_false,
+            difference,
+            result,
+            BrilligBinaryOp::Integer { op: BinaryIntOp::Add, bit_size },
+        );
+        self.deallocate_register(difference);
+    }
+
+    /// Stores `value << shift_amount` in `result`, for unsigned integers of `bit_size` bits.
+    ///
+    /// Bits shifted past `bit_size` are dropped, so shifting by `bit_size` or more results in zero.
+    #[allow(dead_code)]
+    pub(crate) fn shift_left_instruction(
+        &mut self,
+        value: MemoryAddress,
+        shift_amount: MemoryAddress,
+        result: MemoryAddress,
+        bit_size: u32,
+    ) {
+        self.shift_instruction(value, shift_amount, result, bit_size, BinaryIntOp::Shl);
+    }
+
+    /// Stores `value >> shift_amount` in `result`, for unsigned integers of `bit_size` bits.
+    ///
+    /// The shift is logical, so shifting by `bit_size` or more results in zero.
+    #[allow(dead_code)]
+    pub(crate) fn shift_right_instruction(
+        &mut self,
+        value: MemoryAddress,
+        shift_amount: MemoryAddress,
+        result: MemoryAddress,
+        bit_size: u32,
+    ) {
+        self.shift_instruction(value, shift_amount, result, bit_size, BinaryIntOp::Shr);
+    }
+
+    /// Stores `value` rotated left by `rotation` bits in `result`, for unsigned integers of `bit_size` bits.
+    /// `rotation` must not exceed `bit_size`.
+    #[allow(dead_code)]
+    pub(crate) fn rotate_left_instruction(
+        &mut self,
+        value: MemoryAddress,
+        rotation: MemoryAddress,
+        result: MemoryAddress,
+        bit_size: u32,
+    ) {
+        self.rotate_instruction(value, rotation, result, bit_size, true);
+    }
+
+    /// Stores `value` rotated right by `rotation` bits in `result`, for unsigned integers of `bit_size` bits.
+    /// `rotation` must not exceed `bit_size`.
+    #[allow(dead_code)]
+    pub(crate) fn rotate_right_instruction(
+        &mut self,
+        value: MemoryAddress,
+        rotation: MemoryAddress,
+        result: MemoryAddress,
+        bit_size: u32,
+    ) {
+        self.rotate_instruction(value, rotation, result, bit_size, false);
+    }
+
+    /// Computes `(value << r) | (value >> (bit_size - r))`, where `r` is `rotation` for a left rotation
+    /// and `bit_size - rotation` for a right rotation.
+    fn rotate_instruction(
+        &mut self,
+        value: MemoryAddress,
+        rotation: MemoryAddress,
+        result: MemoryAddress,
+        bit_size: u32,
+        left: bool,
+    ) {
+        assert!(bit_size <= 128, "Brillig can only shift integers of up to 128 bits");
+        let integer_op = |op| BrilligBinaryOp::Integer { op, bit_size };
+
+        // Registers hold field elements, so any bits of `value` above `bit_size` are cleared first
+        // as the right shift would otherwise bring them into the result.
+        let mask = self.make_constant(Value::from(u128::MAX >> (128 - bit_size)), bit_size);
+        let masked_value = self.allocate_register();
+        self.binary_instruction(value, mask, masked_value, integer_op(BinaryIntOp::And));
+        self.deallocate_register(mask);
+
+        let bit_size_constant = self.make_constant(Value::from(bit_size as u128), bit_size);
+        let complement = self.allocate_register();
+        self.binary_instruction(
+            bit_size_constant,
+            rotation,
+            complement,
+            integer_op(BinaryIntOp::Sub),
+        );
+        self.deallocate_register(bit_size_constant);
+        let (left_shift, right_shift) =
+            if left { (rotation, complement) } else { (complement, rotation) };
+
+        let high_bits = self.allocate_register();
+        self.binary_instruction(masked_value, left_shift, high_bits, integer_op(BinaryIntOp::Shl));
+        self.binary_instruction(
+            masked_value,
+            right_shift,
+            masked_value,
+            integer_op(BinaryIntOp::Shr),
+        );
+        self.binary_instruction(high_bits, masked_value, result, integer_op(BinaryIntOp::Or));
+
+        self.deallocate_register(masked_value);
+        self.deallocate_register(complement);
+        self.deallocate_register(high_bits);
+    }
+
+    /// Emits the `Shl` or `Shr` opcode `op` only for shift amounts below `bit_size`,
+    /// so that the VM never shifts by an arbitrarily large amount, and stores zero otherwise.
+    fn shift_instruction(
+        &mut self,
+        value: MemoryAddress,
+        shift_amount: MemoryAddress,
+        result: MemoryAddress,
+        bit_size: u32,
+        op: BinaryIntOp,
+    ) {
+        assert!(bit_size <= 128, "Brillig can only shift integers of up to 128 bits");
+
+        let max_shift = self.make_constant(Value::from(bit_size as u128), bit_size);
+        let in_range = self.allocate_register();
+        self.less_than_instruction(shift_amount, max_shift, in_range, bit_size, false);
+        self.branch_instruction(in_range, |ctx, in_range| {
+            if in_range {
+                ctx.binary_instruction(
+                    value,
+                    shift_amount,
+                    result,
+                    BrilligBinaryOp::Integer { op, bit_size },
+                );
+            } else {
+                ctx.const_instruction(result, Value::from(0_u128), bit_size);
+            }
+        });
+        self.deallocate_register(max_shift);
+        self.deallocate_register(in_range);
+    }
+
+    /// Stores `-value` in `result`. Values with a `bit_size` of [`FieldElement::max_num_bits`] are
+    /// negated as field elements, others as two's complement integers of `bit_size` bits.
+    ///
+    /// The zero which `value` is subtracted from is shared through the constant cache.
+    pub(crate) fn negate_instruction(
+        &mut self,
+        value: MemoryAddress,
+        result: MemoryAddress,
+        bit_size: u32,
+    ) {
+        let zero = self.cached_constant(Value::from(0_u128), bit_size);
+        let operation = if bit_size == FieldElement::max_num_bits() {
+            BrilligBinaryOp::Field { op: BinaryFieldOp::Sub }
+        } else {
+            BrilligBinaryOp::Integer { op: BinaryIntOp::Sub, bit_size }
+        };
+        self.binary_instruction(zero, value, result, operation);
+    }
+
+    /// Stores the inverse `1 / value` of the field element `value` in `result`, trapping if `value` is zero.
+    ///
+    /// This is only valid for field elements: integers have no inverse, see [`Self::divide_instruction`].
+    #[allow(dead_code)]
+    pub(crate) fn field_inverse_instruction(
+        &mut self,
+        value: MemoryAddress,
+        result: MemoryAddress,
+    ) {
+        self.trap_if_zero(value, FieldElement::max_num_bits());
+        let one = self.cached_constant(Value::from(1_u128), FieldElement::max_num_bits());
+        self.binary_instruction(
+            one,
+            value,
+            result,
+            BrilligBinaryOp::Field { op: BinaryFieldOp::Div },
+        );
+    }
+
+    /// Stores the absolute value of the signed integer `value` of `bit_size` bits in `result`,
+    /// selecting between `value` and its negation without branching.
+    ///
+    /// The absolute value of the minimum integer `-2^(bit_size - 1)` overflows: as in two's complement,
+    /// it wraps around to the minimum integer itself.
+    pub(crate) fn abs_instruction(
+        &mut self,
+        value: MemoryAddress,
+        result: MemoryAddress,
+        bit_size: u32,
+    ) {
+        let zero = self.cached_constant(Value::from(0_u128), bit_size);
+        let is_negative = self.allocate_register();
+        self.less_than_instruction(value, zero, is_negative, bit_size, true);
+        let negated = self.allocate_register();
+        self.negate_instruction(value, negated, bit_size);
+        self.select_instruction(is_negative, negated, value, result, bit_size);
+        self.deallocate_register(is_negative);
+        self.deallocate_register(negated);
+    }
+
+    /// Normalizes `value` of `bit_size` bits into a boolean, storing 0 in `result` if it is zero and 1 otherwise,
+    /// so that it can be used in boolean arithmetic such as [`Self::select_instruction`].
+    #[allow(dead_code)]
+    pub(crate) fn to_bool(&mut self, value: MemoryAddress, result: MemoryAddress, bit_size: u32) {
+        let zero = self.cached_constant(Value::from(0_u128), bit_size);
+        let result = SingleAddrVariable { address: result, bit_size: 1 };
+        self.equal_instruction(value, zero, result.address, bit_size);
+        self.not_instruction(result, result);
+    }
+
+    /// Counts the zero bits above the highest set bit of the `bit_size`-bit integer `value`.
+    /// The count for a zero `value` is `bit_size`.
+    pub(crate) fn leading_zeros(
+        &mut self,
+        value: MemoryAddress,
+        result: MemoryAddress,
+        bit_size: u32,
+    ) {
+        self.count_zeros_instruction(value, result, bit_size, true);
+    }
+
+    /// Counts the zero bits below the lowest set bit of the `bit_size`-bit integer `value`.
+    /// The count for a zero `value` is `bit_size`.
+    #[allow(dead_code)]
+    pub(crate) fn trailing_zeros(
+        &mut self,
+        value: MemoryAddress,
+        result: MemoryAddress,
+        bit_size: u32,
+    ) {
+        self.count_zeros_instruction(value, result, bit_size, false);
+    }
+
+    /// Walks over the bits of `value`, starting from either its highest or its lowest bit,
+    /// and counts the zero bits seen until the first set bit.
+    fn count_zeros_instruction(
+        &mut self,
+        value: MemoryAddress,
+        result: MemoryAddress,
+        bit_size: u32,
+        from_highest_bit: bool,
+    ) {
+        self.const_instruction(result, Value::from(0_u128), bit_size);
+
+        let one = self.make_constant(Value::from(1_u128), bit_size);
+        let still_counting = self.make_constant(Value::from(true), 1);
+        let shift = self.allocate_register();
+        let bit = self.allocate_register();
+        let iteration_count = self.make_usize_constant((bit_size as usize).into());
+
+        self.loop_instruction(iteration_count, |ctx, iterator| {
+            if from_highest_bit {
+                ctx.usize_const(shift, (bit_size as usize - 1).into());
+                ctx.memory_op(shift, iterator, shift, BinaryIntOp::Sub);
+            } else {
+                ctx.mov_instruction(shift, iterator);
+            }
+            ctx.binary_instruction(
+                value,
+                shift,
+                bit,
+                BrilligBinaryOp::Integer { op: BinaryIntOp::Shr, bit_size },
+            );
+            ctx.binary_instruction(
+                bit,
+                one,
+                bit,
+                BrilligBinaryOp::Integer { op: BinaryIntOp::And, bit_size },
+            );
+
+            // The count stops at the first set bit.
+            ctx.binary_instruction(
+                bit,
+                one,
+                bit,
+                BrilligBinaryOp::Integer { op: BinaryIntOp::Xor, bit_size: 1 },
+            );
+            ctx.binary_instruction(
+                still_counting,
+                bit,
+                still_counting,
+                BrilligBinaryOp::Integer { op: BinaryIntOp::And, bit_size: 1 },
+            );
+            ctx.binary_instruction(
+                result,
+                still_counting,
+                result,
+                BrilligBinaryOp::Integer { op: BinaryIntOp::Add, bit_size },
+            );
+        });
+
+        self.deallocate_register(one);
+        self.deallocate_register(still_counting);
+        self.deallocate_register(shift);
+        self.deallocate_register(bit);
+        self.deallocate_register(iteration_count);
+    }
+
+    /// Decomposes `value` into its `num_bits` lowest bits, writing them in little-endian order
+    /// into the array pointed by `result_array_ptr`, which must have room for `num_bits` elements.
+    ///
+    /// Bits above the bit size of `value` are written as zero, so `num_bits` may be as large as
+    /// the bit size of the field modulus.
+    #[allow(dead_code)]
+    pub(crate) fn to_le_bits(
+        &mut self,
+        value: MemoryAddress,
+        result_array_ptr: MemoryAddress,
+        num_bits: u32,
+    ) {
+        let max_bit_size = FieldElement::max_num_bits();
+        assert!(
+            num_bits <= max_bit_size,
+            "Cannot decompose into {num_bits} bits, fields have at most {max_bit_size}"
+        );
+
+        // The VM only shifts integers of up to 128 bits, so the bits are shifted out by halving instead.
+        let one = self.make_constant(Value::from(1_u128), max_bit_size);
+        let two = self.make_constant(Value::from(2_u128), max_bit_size);
+        let remaining = self.allocate_register();
+        self.mov_instruction(remaining, value);
+        let bit = self.allocate_register();
+        let iteration_count = self.make_usize_constant((num_bits as usize).into());
+
+        self.loop_instruction(iteration_count, |ctx, iterator| {
+            ctx.binary_instruction(
+                remaining,
+                one,
+                bit,
+                BrilligBinaryOp::Integer { op: BinaryIntOp::And, bit_size: max_bit_size },
+            );
+            ctx.array_set(result_array_ptr, iterator, bit);
+            ctx.binary_instruction(
+                remaining,
+                two,
+                remaining,
+                BrilligBinaryOp::Integer { op: BinaryIntOp::UnsignedDiv, bit_size: max_bit_size },
+            );
+        });
+
+        self.deallocate_register(one);
+        self.deallocate_register(two);
+        self.deallocate_register(remaining);
+        self.deallocate_register(bit);
+        self.deallocate_register(iteration_count);
+    }
+
+    /// Packs the `num_bits` booleans of the array pointed by `bools_ptr` into `result`,
+    /// element `i` of the array becoming bit `i` of the result.
+    ///
+    /// The packing is unrolled if `num_bits` is within the unroll threshold of this context.
+    #[allow(dead_code)]
+    pub(crate) fn pack_bits(
+        &mut self,
+        bools_ptr: MemoryAddress,
+        num_bits: usize,
+        result: SingleAddrVariable,
+    ) {
+        assert!(
+            num_bits <= result.bit_size as usize,
+            "Cannot pack {num_bits} bits into a {}-bit integer",
+            result.bit_size
+        );
+        assert!(result.bit_size <= 128, "Brillig can only shift integers of up to 128 bits");
+
+        self.const_instruction(result.address, Value::from(0_u128), result.bit_size);
+        let bit = self.allocate_register();
+        self.fixed_length_loop_instruction(num_bits, |ctx, iterator| {
+            ctx.array_get(bools_ptr, iterator, bit);
+            ctx.binary_instruction(
+                bit,
+                iterator,
+                bit,
+                BrilligBinaryOp::Integer { op: BinaryIntOp::Shl, bit_size: result.bit_size },
+            );
+            ctx.binary_instruction(
+                result.address,
+                bit,
+                result.address,
+                BrilligBinaryOp::Integer { op: BinaryIntOp::Or, bit_size: result.bit_size },
+            );
+        });
+        self.deallocate_register(bit);
+    }
+
+    /// Unpacks the `num_bits` lowest bits of `value` into the array pointed by `bools_ptr`,
+    /// bit `i` becoming element `i` of the array. This is the inverse of [`Self::pack_bits`].
+    ///
+    /// The unpacking is unrolled if `num_bits` is within the unroll threshold of this context.
+    #[allow(dead_code)]
+    pub(crate) fn unpack_bits(
+        &mut self,
+        value: SingleAddrVariable,
+        bools_ptr: MemoryAddress,
+        num_bits: usize,
+    ) {
+        assert!(
+            num_bits <= value.bit_size as usize,
+            "Cannot unpack {num_bits} bits from a {}-bit integer",
+            value.bit_size
+        );
+        assert!(value.bit_size <= 128, "Brillig can only shift integers of up to 128 bits");
+
+        let one = self.make_constant(Value::from(1_u128), value.bit_size);
+        let bit = self.allocate_register();
+        self.fixed_length_loop_instruction(num_bits, |ctx, iterator| {
+            ctx.binary_instruction(
+                value.address,
+                iterator,
+                bit,
+                BrilligBinaryOp::Integer { op: BinaryIntOp::Shr, bit_size: value.bit_size },
+            );
+            ctx.binary_instruction(
+                bit,
+                one,
+                bit,
+                BrilligBinaryOp::Integer { op: BinaryIntOp::And, bit_size: value.bit_size },
+            );
+            ctx.array_set(bools_ptr, iterator, bit);
+        });
+        self.deallocate_register(one);
+        self.deallocate_register(bit);
+    }
+
+    /// Decomposes `value` into `num_limbs` limbs in base `radix`, writing them in little-endian order
+    /// into the array pointed by `result_array_ptr`, which must have room for `num_limbs` elements.
+    ///
+    /// Traps if `radix` is zero or one. Any part of `value` which does not fit in the limbs is dropped.
+    #[allow(dead_code)]
+    pub(crate) fn to_radix(
+        &mut self,
+        value: MemoryAddress,
+        radix: MemoryAddress,
+        result_array_ptr: MemoryAddress,
+        num_limbs: u32,
+    ) {
+        let bit_size = FieldElement::max_num_bits();
+
+        let two = self.make_constant(Value::from(2_u128), bit_size);
+        let is_valid_radix = self.allocate_register();
+        self.less_than_equal_instruction(two, radix, is_valid_radix, bit_size, false);
+        self.constrain_instruction(is_valid_radix, Some("Radix must be at least 2".to_string()));
+        self.deallocate_register(is_valid_radix);
+        self.deallocate_register(two);
+
+        let remaining = self.allocate_register();
+        self.mov_instruction(remaining, value);
+        let limb = self.allocate_register();
+        let iteration_count = self.make_usize_constant((num_limbs as usize).into());
+
+        self.loop_instruction(iteration_count, |ctx, iterator| {
+            ctx.modulo_instruction(limb, remaining, radix, bit_size, false);
+            ctx.array_set(result_array_ptr, iterator, limb);
+            ctx.divide_instruction(remaining, remaining, radix, bit_size, false);
+        });
+
+        self.deallocate_register(remaining);
+        self.deallocate_register(limb);
+        self.deallocate_register(iteration_count);
+    }
+
+    /// Stores `base ^ exponent mod modulus` in `result`, where `exponent` is an integer of `bit_size` bits.
+    ///
+    /// The exponentiation squares and multiplies once per bit of `exponent`, reducing modulo `modulus`
+    /// after each step. The products are computed as integers of [`FieldElement::max_num_bits`] bits,
+    /// so `modulus` must be below `2^126` for them not to overflow.
+    #[allow(dead_code)]
+    pub(crate) fn modpow_instruction(
+        &mut self,
+        base: MemoryAddress,
+        exponent: MemoryAddress,
+        modulus: MemoryAddress,
+        result: MemoryAddress,
+        bit_size: u32,
+    ) {
+        let max_bit_size = FieldElement::max_num_bits();
+        let mul = BrilligBinaryOp::Integer { op: BinaryIntOp::Mul, bit_size: max_bit_size };
+
+        let one = self.make_constant(Value::from(1_u128), max_bit_size);
+        // Reduce the initial value too, so that `1 mod 1` is zero.
+        self.modulo_instruction(result, one, modulus, max_bit_size, false);
+        let power = self.allocate_register();
+        self.modulo_instruction(power, base, modulus, max_bit_size, false);
+        let remaining_exponent = self.allocate_register();
+        self.mov_instruction(remaining_exponent, exponent);
+
+        let bit = self.allocate_register();
+        let product = self.allocate_register();
+        let iteration_count = self.make_usize_constant((bit_size as usize).into());
+
+        self.loop_instruction(iteration_count, |ctx, _| {
+            ctx.binary_instruction(
+                remaining_exponent,
+                one,
+                bit,
+                BrilligBinaryOp::Integer { op: BinaryIntOp::And, bit_size },
+            );
+            ctx.binary_instruction(result, power, product, mul);
+            ctx.modulo_instruction(product, product, modulus, max_bit_size, false);
+            ctx.conditional_select(bit, product, result, result);
+
+            ctx.binary_instruction(power, power, power, mul);
+            ctx.modulo_instruction(power, power, modulus, max_bit_size, false);
+            ctx.binary_instruction(
+                remaining_exponent,
+                one,
+                remaining_exponent,
+                BrilligBinaryOp::Integer { op: BinaryIntOp::Shr, bit_size },
+            );
+        });
+
+        self.deallocate_register(one);
+        self.deallocate_register(power);
+        self.deallocate_register(remaining_exponent);
+        self.deallocate_register(bit);
+        self.deallocate_register(product);
+        self.deallocate_register(iteration_count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::{
+        brillig_vm::{
+            brillig::{BinaryFieldOp, BinaryIntOp, Opcode as BrilligOpcode, Value},
+            VMStatus, VM,
+        },
+        FieldElement,
+    };
+
+    use crate::brillig::brillig_ir::{
+        artifact::BrilligParameter,
+        brillig_variable::{BrilligArray, SingleAddrVariable},
+        tests::{
+            create_and_run_vm, create_context, create_entry_point_bytecode, execute, memory_of,
+            DummyBlackBoxSolver,
+        },
+    };
+
+    /// Encodes `value` as a `bit_size`-bit two's complement integer.
+    fn to_twos_complement(value: i128, bit_size: u32) -> Value {
+        let modulus = 1_i128 << bit_size;
+        Value::from(((value + modulus) % modulus) as u128)
+    }
+
+    fn clamp(value: Value, min: Value, max: Value, bit_size: u32, signed: bool) -> Value {
+        let calldata = vec![value, min, max];
+        let arguments = vec![BrilligParameter::SingleAddr(bit_size); 3];
+        let returns = vec![BrilligParameter::SingleAddr(bit_size)];
+
+        let mut context = create_context();
+        let value = context.allocate_register();
+        let min = context.allocate_register();
+        let max = context.allocate_register();
+        let result = context.allocate_register();
+
+        context.clamp_instruction(value, min, max, result, bit_size, signed);
+        context.return_instruction(&[result]);
+
+        let bytecode = create_entry_point_bytecode(context, arguments, returns).byte_code;
+        let (vm, return_data_offset, return_data_size) = create_and_run_vm(calldata, &bytecode);
+        assert_eq!(return_data_size, 1, "Return data size is incorrect");
+        vm.get_memory()[return_data_offset]
+    }
+
+    fn count_zeros(value: u128, bit_size: u32, leading: bool) -> Value {
+        let calldata = vec![Value::from(value)];
+        let arguments = vec![BrilligParameter::SingleAddr(bit_size)];
+        let returns = vec![BrilligParameter::SingleAddr(bit_size)];
+
+        let mut context = create_context();
+        let value = context.allocate_register();
+        let result = context.allocate_register();
+
+        if leading {
+            context.leading_zeros(value, result, bit_size);
+        } else {
+            context.trailing_zeros(value, result, bit_size);
+        }
+        context.return_instruction(&[result]);
+
+        let bytecode = create_entry_point_bytecode(context, arguments, returns).byte_code;
+        let (vm, return_data_offset, return_data_size) = create_and_run_vm(calldata, &bytecode);
+        assert_eq!(return_data_size, 1, "Return data size is incorrect");
+        vm.get_memory()[return_data_offset]
+    }
+
+    fn negate(value: Value, bit_size: u32) -> Value {
+        let calldata = vec![value];
+        let arguments = vec![BrilligParameter::SingleAddr(bit_size)];
+        let returns = vec![BrilligParameter::SingleAddr(bit_size)];
+
+        let mut context = create_context();
+        let value = context.allocate_register();
+        let result = context.allocate_register();
+        context.negate_instruction(value, result, bit_size);
+        context.return_instruction(&[result]);
+
+        let bytecode = create_entry_point_bytecode(context, arguments, returns).byte_code;
+        let (vm, return_data_offset, _) = create_and_run_vm(calldata, &bytecode);
+        vm.get_memory()[return_data_offset]
+    }
+
+    #[test]
+    fn negates_fields_and_signed_integers() {
+        let field_bit_size = FieldElement::max_num_bits();
+        assert_eq!(
+            negate(Value::from(5_u128), field_bit_size),
+            Value::from(-FieldElement::from(5_u128))
+        );
+        assert_eq!(negate(Value::from(7_u128), 32), to_twos_complement(-7, 32));
+        assert_eq!(negate(to_twos_complement(-7, 32), 32), Value::from(7_u128));
+    }
+
+    #[test]
+    fn shares_the_zero_between_negations() {
+        for bit_size in [FieldElement::max_num_bits(), 32] {
+            let mut context = create_context();
+            let value = context.allocate_register();
+            let result = context.allocate_register();
+            context.negate_instruction(value, result, bit_size);
+            context.negate_instruction(result, result, bit_size);
+
+            let bytecode = context.artifact().finish().byte_code;
+            let consts =
+                bytecode.iter().filter(|opcode| matches!(opcode, BrilligOpcode::Const { .. }));
+            assert_eq!(consts.count(), 1);
+            assert_eq!(bytecode.len(), 3);
+        }
+    }
+
+    #[test]
+    fn inverts_non_zero_field_elements() {
+        let mut context = create_context();
+        let value = context.allocate_register();
+        let result = context.allocate_register();
+        context.field_inverse_instruction(value, result);
+        let artifact = context.artifact();
+
+        // The zero check traps before the division.
+        let ops: Vec<_> = artifact
+            .byte_code
+            .iter()
+            .map(|opcode| match opcode {
+                BrilligOpcode::Const { .. } => "const",
+                BrilligOpcode::BinaryIntOp { op: BinaryIntOp::Equals, .. } => "eq",
+                BrilligOpcode::BinaryIntOp { op: BinaryIntOp::Sub, bit_size: 1, .. } => "not",
+                BrilligOpcode::JumpIf { .. } => "jump_if",
+                BrilligOpcode::Trap => "trap",
+                BrilligOpcode::BinaryFieldOp { op: BinaryFieldOp::Div, .. } => "div",
+                _ => panic!("unexpected opcode {opcode:?}"),
+            })
+            .collect();
+        assert_eq!(ops, ["const", "eq", "const", "not", "jump_if", "trap", "const", "div"]);
+        assert_eq!(
+            artifact.assert_messages.get(&5).map(String::as_str),
+            Some("Attempted to divide by zero")
+        );
+
+        let memory = execute(artifact.clone(), memory_of(&[0, 0, 4], 4));
+        let inverse = memory[result.to_usize()].to_field();
+        assert_eq!(inverse * FieldElement::from(4_u128), FieldElement::one());
+
+        // Memory starts out zeroed, so the value register holds zero.
+        let bytecode = artifact.finish().byte_code;
+        let mut vm = VM::new(vec![], &bytecode, vec![], &DummyBlackBoxSolver);
+        assert!(matches!(vm.process_opcodes(), VMStatus::Failure { .. }));
+    }
+
+    fn shift(value: u128, shift_amount: u128, left: bool) -> Value {
+        let calldata = vec![Value::from(value), Value::from(shift_amount)];
+        let arguments = vec![BrilligParameter::SingleAddr(8); 2];
+        let returns = vec![BrilligParameter::SingleAddr(8)];
+
+        let mut context = create_context();
+        let value = context.allocate_register();
+        let shift_amount = context.allocate_register();
+        let result = context.allocate_register();
+        if left {
+            context.shift_left_instruction(value, shift_amount, result, 8);
+        } else {
+            context.shift_right_instruction(value, shift_amount, result, 8);
+        }
+        context.return_instruction(&[result]);
+
+        let bytecode = create_entry_point_bytecode(context, arguments, returns).byte_code;
+        let (vm, return_data_offset, _) = create_and_run_vm(calldata, &bytecode);
+        vm.get_memory()[return_data_offset]
+    }
+
+    #[test]
+    fn shifts_within_bit_size() {
+        assert_eq!(shift(0b1011, 2, true), Value::from(0b10_1100_u128));
+        assert_eq!(shift(0b1011, 7, true), Value::from(0b1000_0000_u128));
+        assert_eq!(shift(0b1011, 2, false), Value::from(0b10_u128));
+        assert_eq!(shift(0b1000_0000, 7, false), Value::from(1_u128));
+    }
+
+    #[test]
+    fn shifts_by_at_least_bit_size_to_zero() {
+        for shift_amount in [8, 9, 255] {
+            assert_eq!(shift(0b1011, shift_amount, true), Value::from(0_u128));
+            assert_eq!(shift(0b1000_0000, shift_amount, false), Value::from(0_u128));
+        }
+    }
+
+    fn rotate(value: u128, rotation: u128, left: bool) -> Value {
+        let calldata = vec![Value::from(value), Value::from(rotation)];
+        let arguments = vec![BrilligParameter::SingleAddr(32); 2];
+        let returns = vec![BrilligParameter::SingleAddr(32)];
+
+        let mut context = create_context();
+        let value = context.allocate_register();
+        let rotation = context.allocate_register();
+        let result = context.allocate_register();
+        if left {
+            context.rotate_left_instruction(value, rotation, result, 32);
+        } else {
+            context.rotate_right_instruction(value, rotation, result, 32);
+        }
+        context.return_instruction(&[result]);
+
+        let bytecode = create_entry_point_bytecode(context, arguments, returns).byte_code;
+        let (vm, return_data_offset, _) = create_and_run_vm(calldata, &bytecode);
+        vm.get_memory()[return_data_offset]
+    }
+
+    #[test]
+    fn rotates_left_with_mask_shifts_and_or() {
+        let mut context = create_context();
+        let value = context.allocate_register();
+        let rotation = context.allocate_register();
+        let result = context.allocate_register();
+        context.rotate_left_instruction(value, rotation, result, 32);
+
+        let bytecode = context.artifact().finish().byte_code;
+        let opcodes: Vec<_> = bytecode
+            .iter()
+            .map(|opcode| match opcode {
+                BrilligOpcode::Const { value, bit_size: 32, .. } => Ok(*value),
+                BrilligOpcode::BinaryIntOp { op, bit_size: 32, .. } => Err(*op),
+                _ => panic!("unexpected opcode {opcode:?}"),
+            })
+            .collect();
+        assert_eq!(
+            opcodes,
+            vec![
+                Ok(Value::from(u32::MAX as u128)),
+                Err(BinaryIntOp::And),
+                Ok(Value::from(32_u128)),
+                Err(BinaryIntOp::Sub),
+                Err(BinaryIntOp::Shl),
+                Err(BinaryIntOp::Shr),
+                Err(BinaryIntOp::Or),
+            ]
+        );
+        assert!(matches!(
+            bytecode.last(),
+            Some(BrilligOpcode::BinaryIntOp { destination, .. }) if *destination == result
+        ));
+    }
+
+    #[test]
+    fn rotates_32_bit_integers() {
+        assert_eq!(rotate(0x8000_0001, 7, true), Value::from(0xc0_u128));
+        assert_eq!(rotate(0xc0, 7, false), Value::from(0x8000_0001_u128));
+        assert_eq!(rotate(0x1234_5678, 0, true), Value::from(0x1234_5678_u128));
+        assert_eq!(rotate(0x1234_5678, 32, false), Value::from(0x1234_5678_u128));
+        // Bits above the bit size do not leak into the result.
+        assert_eq!(rotate((1 << 40) | 1, 7, true), Value::from(0x80_u128));
+    }
+
+    #[test]
+    fn emits_unsigned_comparisons_directly() {
+        let mut context = create_context();
+        let lhs = context.allocate_register();
+        let rhs = context.allocate_register();
+        let result = context.allocate_register();
+        context.less_than_equal_instruction(lhs, rhs, result, 8, false);
+        context.equal_instruction(lhs, rhs, result, 8);
+
+        let bytecode = context.artifact().finish().byte_code;
+        assert_eq!(
+            bytecode,
+            vec![
+                BrilligOpcode::BinaryIntOp {
+                    destination: result,
+                    op: BinaryIntOp::LessThanEquals,
+                    bit_size: 8,
+                    lhs,
+                    rhs,
+                },
+                BrilligOpcode::BinaryIntOp {
+                    destination: result,
+                    op: BinaryIntOp::Equals,
+                    bit_size: 8,
+                    lhs,
+                    rhs,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn flips_sign_bits_before_signed_comparisons() {
+        let mut context = create_context();
+        let lhs = context.allocate_register();
+        let rhs = context.allocate_register();
+        let result = context.allocate_register();
+        context.less_than_instruction(lhs, rhs, result, 32, true);
+
+        let bytecode = context.artifact().finish().byte_code;
+        assert_eq!(bytecode.len(), 4);
+        let BrilligOpcode::Const { destination: sign_bit, bit_size: 32, value } = bytecode[0]
+        else {
+            panic!("expected the sign bit to be loaded first, got {:?}", bytecode[0]);
+        };
+        assert_eq!(value, Value::from(1_u128 << 31));
+
+        // Each operand is XORed with the sign bit into a temporary register
+        let flipped_operand = |opcode: &BrilligOpcode| match *opcode {
+            BrilligOpcode::BinaryIntOp {
+                destination,
+                op: BinaryIntOp::Xor,
+                bit_size: 32,
+                lhs: operand,
+                rhs,
+            } if rhs == sign_bit => (operand, destination),
+            _ => panic!("expected a sign bit flip, got {opcode:?}"),
+        };
+        let (flipped_lhs, biased_lhs) = flipped_operand(&bytecode[1]);
+        let (flipped_rhs, biased_rhs) = flipped_operand(&bytecode[2]);
+        assert_eq!((flipped_lhs, flipped_rhs), (lhs, rhs));
+        assert_eq!(
+            bytecode[3],
+            BrilligOpcode::BinaryIntOp {
+                destination: result,
+                op: BinaryIntOp::LessThan,
+                bit_size: 32,
+                lhs: biased_lhs,
+                rhs: biased_rhs,
+            }
+        );
+    }
+
+    #[test]
+    fn orders_negative_values_before_positive_ones() {
+        let less_than = |lhs: u128, rhs: u128| {
+            let mut context = create_context();
+            let lhs_register = context.allocate_register();
+            let rhs_register = context.allocate_register();
+            let result = context.allocate_register();
+            context.less_than_instruction(lhs_register, rhs_register, result, 8, true);
+
+            let memory = execute(context.artifact(), memory_of(&[0, 0, lhs, rhs], 5));
+            memory[result.to_usize()]
+        };
+
+        // -1 < 1
+        assert_eq!(less_than(0xFF, 1), Value::from(true));
+        assert_eq!(less_than(1, 0xFF), Value::from(false));
+        // -128 < 127
+        assert_eq!(less_than(0x80, 0x7F), Value::from(true));
+        assert_eq!(less_than(0x7F, 0x80), Value::from(false));
+        assert_eq!(less_than(0x80, 0x80), Value::from(false));
+    }
+
+    fn modulo_bytecode(checked: bool) -> Vec<BrilligOpcode> {
+        let arguments = vec![BrilligParameter::SingleAddr(32); 2];
+        let returns = vec![BrilligParameter::SingleAddr(32)];
+
+        let mut context = create_context();
+        let left = context.allocate_register();
+        let right = context.allocate_register();
+        let result = context.allocate_register();
+        if checked {
+            context.checked_modulo_instruction(result, left, right, 32, false);
+        } else {
+            context.modulo_instruction(result, left, right, 32, false);
+        }
+        context.return_instruction(&[result]);
+
+        create_entry_point_bytecode(context, arguments, returns).byte_code
+    }
+
+    fn is_trap(opcode: &BrilligOpcode) -> bool {
+        matches!(opcode, BrilligOpcode::Trap)
+    }
+
+    fn is_division(opcode: &BrilligOpcode) -> bool {
+        matches!(opcode, BrilligOpcode::BinaryIntOp { op: BinaryIntOp::UnsignedDiv, .. })
+    }
+
+    #[test]
+    fn guards_checked_modulo_against_zero() {
+        let bytecode = modulo_bytecode(true);
+        let trap = bytecode.iter().position(is_trap).expect("checked modulo should trap");
+        let division = bytecode.iter().position(is_division).expect("modulo should divide");
+        assert!(trap < division);
+
+        let (vm, return_data_offset, _) =
+            create_and_run_vm(vec![Value::from(17_u128), Value::from(5_u128)], &bytecode);
+        assert_eq!(vm.get_memory()[return_data_offset], Value::from(2_u128));
+
+        let mut vm = VM::new(
+            vec![Value::from(17_u128), Value::from(0_u128)],
+            &bytecode,
+            vec![],
+            &DummyBlackBoxSolver,
+        );
+        assert!(matches!(vm.process_opcodes(), VMStatus::Failure { .. }));
+    }
+
+    #[test]
+    fn does_not_guard_unchecked_modulo() {
+        let bytecode = modulo_bytecode(false);
+        assert_eq!(bytecode.iter().position(is_trap), None);
+        assert!(bytecode.iter().position(is_division).is_some());
+    }
+
+    fn assert_valid_tag(tag: u128, num_variants: usize) -> VMStatus {
+        let calldata = vec![Value::from(tag)];
+        let arguments = vec![BrilligParameter::SingleAddr(8)];
+
+        let mut context = create_context();
+        let tag = SingleAddrVariable { address: context.allocate_register(), bit_size: 8 };
+        context.assert_valid_tag(tag, num_variants);
+        context.return_instruction(&[]);
+
+        let bytecode = create_entry_point_bytecode(context, arguments, vec![]).byte_code;
+        let mut vm = VM::new(calldata, &bytecode, vec![], &DummyBlackBoxSolver);
+        vm.process_opcodes()
+    }
+
+    #[test]
+    fn accepts_valid_tags() {
+        assert!(matches!(assert_valid_tag(0, 3), VMStatus::Finished { .. }));
+        assert!(matches!(assert_valid_tag(2, 3), VMStatus::Finished { .. }));
+    }
+
+    #[test]
+    fn traps_on_out_of_range_tags() {
+        assert!(matches!(assert_valid_tag(3, 3), VMStatus::Failure { .. }));
+        assert!(matches!(assert_valid_tag(200, 3), VMStatus::Failure { .. }));
+    }
+
+    #[test]
+    fn counts_trailing_zeros() {
+        assert_eq!(count_zeros(0b1000, 8, false), Value::from(3_u128));
+        assert_eq!(count_zeros(0b1011_0100, 8, false), Value::from(2_u128));
+        assert_eq!(count_zeros(1, 8, false), Value::from(0_u128));
+        assert_eq!(count_zeros(0, 8, false), Value::from(8_u128));
+    }
+
+    #[test]
+    fn counts_leading_zeros() {
+        assert_eq!(count_zeros(0b1000, 8, true), Value::from(4_u128));
+        assert_eq!(count_zeros(0b1000_0000, 8, true), Value::from(0_u128));
+        assert_eq!(count_zeros(0, 8, true), Value::from(8_u128));
+    }
+
+    #[test]
+    fn clamps_unsigned_values() {
+        let (min, max) = (Value::from(10_u128), Value::from(20_u128));
+
+        assert_eq!(clamp(Value::from(3_u128), min, max, 8, false), min);
+        assert_eq!(clamp(Value::from(15_u128), min, max, 8, false), Value::from(15_u128));
+        assert_eq!(clamp(Value::from(200_u128), min, max, 8, false), max);
+    }
+
+    #[test]
+    fn clamps_signed_values() {
+        let (min, max) = (to_twos_complement(-10, 8), to_twos_complement(5, 8));
+
+        assert_eq!(clamp(to_twos_complement(-100, 8), min, max, 8, true), min);
+        assert_eq!(clamp(to_twos_complement(-3, 8), min, max, 8, true), to_twos_complement(-3, 8));
+        assert_eq!(clamp(to_twos_complement(0, 8), min, max, 8, true), to_twos_complement(0, 8));
+        assert_eq!(clamp(to_twos_complement(100, 8), min, max, 8, true), max);
+    }
+
+    fn integer_cast(value: u128, from_bits: u32, to_bits: u32, signed: bool) -> Value {
+        let mut context = create_context();
+        let source =
+            SingleAddrVariable { address: context.allocate_register(), bit_size: from_bits };
+        let destination =
+            SingleAddrVariable { address: context.allocate_register(), bit_size: to_bits };
+        context.integer_cast_instruction(destination, source, signed);
+
+        let memory = execute(context.artifact(), memory_of(&[0, 0, value], 4));
+        memory[destination.address.to_usize()]
+    }
+
+    #[test]
+    fn sign_extends_widened_signed_integers() {
+        assert_eq!(integer_cast(0xFF, 8, 32, true), to_twos_complement(-1, 32));
+        assert_eq!(integer_cast(0x80, 8, 32, true), to_twos_complement(-128, 32));
+        assert_eq!(integer_cast(0x7F, 8, 32, true), Value::from(0x7F_u128));
+        // Unsigned values are zero extended.
+        assert_eq!(integer_cast(0xFF, 8, 32, false), Value::from(0xFF_u128));
+    }
+
+    #[test]
+    fn truncates_narrowed_integers() {
+        assert_eq!(integer_cast(0x1234_5678, 32, 8, false), Value::from(0x78_u128));
+        assert_eq!(integer_cast(0xFFFF_FF80, 32, 8, true), to_twos_complement(-128, 8));
+    }
+
+    fn increment_saturating(counter: u128, max: u128) -> Value {
+        let mut context = create_context();
+        let counter_register = context.allocate_register();
+        let max_register = context.allocate_register();
+        context.increment_saturating(counter_register, max_register, 8);
+
+        let memory = execute(context.artifact(), memory_of(&[0, 0, counter, max], 4));
+        memory[counter_register.to_usize()]
+    }
+
+    #[test]
+    fn increments_counters_below_their_cap() {
+        assert_eq!(increment_saturating(0, 3), Value::from(1_u128));
+        assert_eq!(increment_saturating(2, 3), Value::from(3_u128));
+    }
+
+    #[test]
+    fn leaves_counters_at_their_cap_unchanged() {
+        assert_eq!(increment_saturating(3, 3), Value::from(3_u128));
+        // The discarded increment of the largest 8-bit counter wraps without trapping.
+        assert_eq!(increment_saturating(255, 255), Value::from(255_u128));
+    }
+
+    fn to_le_bits_bytecode(num_bits: u32) -> Vec<BrilligOpcode> {
+        let mut context = create_context();
+        let value = context.allocate_register();
+        let result_array_ptr = context.allocate_register();
+        context.to_le_bits(value, result_array_ptr, num_bits);
+        context.artifact().finish().byte_code
+    }
+
+    #[test]
+    fn emits_a_single_loop_for_bit_decomposition() {
+        let bytecode = to_le_bits_bytecode(8);
+        assert_eq!(bytecode.len(), 16);
+        assert_eq!(to_le_bits_bytecode(254).len(), bytecode.len());
+
+        let stores =
+            bytecode.iter().filter(|opcode| matches!(opcode, BrilligOpcode::Store { .. })).count();
+        assert_eq!(stores, 1);
+    }
+
+    #[test]
+    fn decomposes_into_little_endian_bits() {
+        let num_bits = 8;
+        let calldata = vec![Value::from(0b1000_0110_u128)];
+        let arguments = vec![BrilligParameter::SingleAddr(8)];
+        let returns =
+            vec![BrilligParameter::Array(vec![BrilligParameter::SingleAddr(1)], num_bits)];
+
+        let mut context = create_context();
+        let value = context.allocate_register();
+        let bits = BrilligArray {
+            pointer: context.allocate_register(),
+            size: num_bits,
+            rc: context.make_usize_constant(1_usize.into()),
+        };
+        context.allocate_fixed_length_array(bits.pointer, num_bits);
+        context.to_le_bits(value, bits.pointer, num_bits as u32);
+        context.return_instruction(&bits.extract_registers());
+
+        let bytecode = create_entry_point_bytecode(context, arguments, returns).byte_code;
+        let (vm, return_data_offset, return_data_size) = create_and_run_vm(calldata, &bytecode);
+        assert_eq!(return_data_size, num_bits);
+
+        let expected: Vec<Value> =
+            [0_u128, 1, 1, 0, 0, 0, 0, 1].into_iter().map(Value::from).collect();
+        // The highest bit is the last one written, at index `num_bits - 1`.
+        assert_eq!(vm.get_memory()[return_data_offset..(return_data_offset + num_bits)], expected);
+    }
+
+    fn pack_bits(bools: &[u128], unroll_threshold: usize) -> Value {
+        let calldata = bools.iter().copied().map(Value::from).collect();
+        let arguments =
+            vec![BrilligParameter::Array(vec![BrilligParameter::SingleAddr(1)], bools.len())];
+        let returns = vec![BrilligParameter::SingleAddr(8)];
+
+        let mut context = create_context();
+        context.set_unroll_threshold(unroll_threshold);
+        let bools_ptr = context.allocate_register();
+        let _rc = context.allocate_register();
+        let result = SingleAddrVariable { address: context.allocate_register(), bit_size: 8 };
+        context.pack_bits(bools_ptr, bools.len(), result);
+        context.return_instruction(&[result.address]);
+
+        let bytecode = create_entry_point_bytecode(context, arguments, returns).byte_code;
+        let (vm, return_data_offset, _) = create_and_run_vm(calldata, &bytecode);
+        vm.get_memory()[return_data_offset]
+    }
+
+    #[test]
+    fn packs_booleans_into_bits() {
+        let bools = [1, 0, 1, 1];
+        assert_eq!(pack_bits(&bools, 0), Value::from(0b1101_u128));
+        assert_eq!(pack_bits(&bools, bools.len()), Value::from(0b1101_u128));
+    }
+
+    #[test]
+    #[should_panic(expected = "Brillig can only shift integers of up to 128 bits")]
+    fn refuses_to_pack_bits_into_fields() {
+        let mut context = create_context();
+        let bools_ptr = context.allocate_register();
+        let result = SingleAddrVariable {
+            address: context.allocate_register(),
+            bit_size: FieldElement::max_num_bits(),
+        };
+        context.pack_bits(bools_ptr, 4, result);
+    }
+
+    fn unpack_bits(value: u128, num_bits: usize, unroll_threshold: usize) -> Vec<Value> {
+        let calldata = vec![Value::from(value)];
+        let arguments = vec![BrilligParameter::SingleAddr(8)];
+        let returns =
+            vec![BrilligParameter::Array(vec![BrilligParameter::SingleAddr(1)], num_bits)];
+
+        let mut context = create_context();
+        context.set_unroll_threshold(unroll_threshold);
+        let value = SingleAddrVariable { address: context.allocate_register(), bit_size: 8 };
+        let bools = BrilligArray {
+            pointer: context.allocate_register(),
+            size: num_bits,
+            rc: context.make_usize_constant(1_usize.into()),
+        };
+        context.allocate_fixed_length_array(bools.pointer, num_bits);
+        context.unpack_bits(value, bools.pointer, num_bits);
+        context.return_instruction(&bools.extract_registers());
+
+        let bytecode = create_entry_point_bytecode(context, arguments, returns).byte_code;
+        let (vm, return_data_offset, return_data_size) = create_and_run_vm(calldata, &bytecode);
+        assert_eq!(return_data_size, num_bits);
+        vm.get_memory()[return_data_offset..(return_data_offset + num_bits)].to_vec()
+    }
+
+    #[test]
+    fn unpacks_bits_into_booleans() {
+        let expected: Vec<Value> = [1_u128, 0, 1, 1].into_iter().map(Value::from).collect();
+        assert_eq!(unpack_bits(0b1101, 4, 0), expected);
+        assert_eq!(unpack_bits(0b1101, 4, 4), expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "Brillig can only shift integers of up to 128 bits")]
+    fn refuses_to_unpack_bits_from_fields() {
+        let mut context = create_context();
+        let value = SingleAddrVariable {
+            address: context.allocate_register(),
+            bit_size: FieldElement::max_num_bits(),
+        };
+        let bools_ptr = context.allocate_register();
+        context.unpack_bits(value, bools_ptr, 4);
+    }
+
+    fn to_radix(value: u128, radix: u128, num_limbs: usize) -> Result<Vec<Value>, VMStatus> {
+        let calldata = vec![Value::from(value), Value::from(radix)];
+        let arguments = vec![BrilligParameter::SingleAddr(32); 2];
+        let returns =
+            vec![BrilligParameter::Array(vec![BrilligParameter::SingleAddr(32)], num_limbs)];
+
+        let mut context = create_context();
+        let value = context.allocate_register();
+        let radix = context.allocate_register();
+        let limbs = BrilligArray {
+            pointer: context.allocate_register(),
+            size: num_limbs,
+            rc: context.make_usize_constant(1_usize.into()),
+        };
+        context.allocate_fixed_length_array(limbs.pointer, num_limbs);
+        context.to_radix(value, radix, limbs.pointer, num_limbs as u32);
+        context.return_instruction(&limbs.extract_registers());
+
+        let bytecode = create_entry_point_bytecode(context, arguments, returns).byte_code;
+        let mut vm = VM::new(calldata, &bytecode, vec![], &DummyBlackBoxSolver);
+        match vm.process_opcodes() {
+            VMStatus::Finished { return_data_offset, return_data_size } => {
+                assert_eq!(return_data_size, num_limbs);
+                Ok(vm.get_memory()[return_data_offset..(return_data_offset + num_limbs)].to_vec())
+            }
+            status => Err(status),
+        }
+    }
+
+    #[test]
+    fn emits_modulo_and_division_for_each_limb() {
+        let mut context = create_context();
+        let value = context.allocate_register();
+        let radix = context.make_constant(Value::from(256_u128), FieldElement::max_num_bits());
+        let result_array_ptr = context.allocate_register();
+        context.to_radix(value, radix, result_array_ptr, 4);
+
+        let bytecode = context.artifact().finish().byte_code;
+        let field_ops: Vec<BinaryIntOp> = bytecode
+            .iter()
+            .filter_map(|opcode| match opcode {
+                BrilligOpcode::BinaryIntOp { op, bit_size, .. }
+                    if *bit_size == FieldElement::max_num_bits() =>
+                {
+                    Some(*op)
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            field_ops,
+            vec![
+                // radix >= 2
+                BinaryIntOp::LessThanEquals,
+                // limb = remaining % radix
+                BinaryIntOp::UnsignedDiv,
+                BinaryIntOp::Mul,
+                BinaryIntOp::Sub,
+                // remaining /= radix
+                BinaryIntOp::UnsignedDiv,
+            ]
+        );
+        let stores =
+            bytecode.iter().filter(|opcode| matches!(opcode, BrilligOpcode::Store { .. })).count();
+        assert_eq!(stores, 1);
+    }
+
+    #[test]
+    fn decomposes_into_limbs() {
+        let limbs = to_radix(0x01020304, 256, 4).unwrap();
+        let expected: Vec<Value> = [4_u128, 3, 2, 1].into_iter().map(Value::from).collect();
+        assert_eq!(limbs, expected);
+
+        let limbs = to_radix(100, 10, 3).unwrap();
+        let expected: Vec<Value> = [0_u128, 0, 1].into_iter().map(Value::from).collect();
+        assert_eq!(limbs, expected);
+    }
+
+    #[test]
+    fn traps_on_radix_below_two() {
+        for radix in [0, 1] {
+            let status = to_radix(100, radix, 3).unwrap_err();
+            assert!(matches!(status, VMStatus::Failure { .. }));
+        }
+    }
+
+    fn modpow(base: u128, exponent: u128, modulus: u128) -> Value {
+        let calldata = vec![Value::from(base), Value::from(exponent), Value::from(modulus)];
+        let arguments = vec![BrilligParameter::SingleAddr(32); 3];
+        let returns = vec![BrilligParameter::SingleAddr(32)];
+
+        let mut context = create_context();
+        let base = context.allocate_register();
+        let exponent = context.allocate_register();
+        let modulus = context.allocate_register();
+        let result = context.allocate_register();
+        context.modpow_instruction(base, exponent, modulus, result, 32);
+        context.return_instruction(&[result]);
+
+        let bytecode = create_entry_point_bytecode(context, arguments, returns).byte_code;
+        let (vm, return_data_offset, return_data_size) = create_and_run_vm(calldata, &bytecode);
+        assert_eq!(return_data_size, 1);
+        vm.get_memory()[return_data_offset]
+    }
+
+    #[test]
+    fn computes_modular_exponentiation() {
+        assert_eq!(modpow(3, 4, 5), Value::from(1_u128));
+        assert_eq!(modpow(2, 10, 1000), Value::from(24_u128));
+        assert_eq!(modpow(7, 0, 13), Value::from(1_u128));
+        assert_eq!(modpow(4_000_000_000, 3, 4_294_967_291), {
+            let base = 4_000_000_000_u128 % 4_294_967_291;
+            Value::from(base * base % 4_294_967_291 * base % 4_294_967_291)
+        });
+    }
+
+    #[test]
+    fn computes_min_and_max_without_branching() {
+        let ops = |max: bool| {
+            let mut context = create_context();
+            let lhs = context.allocate_register();
+            let rhs = context.allocate_register();
+            let result = context.allocate_register();
+            if max {
+                context.max_instruction(lhs, rhs, result, 8, false);
+            } else {
+                context.min_instruction(lhs, rhs, result, 8, false);
+            }
+
+            let bytecode = context.artifact().finish().byte_code;
+            bytecode
+                .iter()
+                .map(|opcode| match opcode {
+                    BrilligOpcode::BinaryIntOp { op, bit_size: 8, .. } => *op,
+                    _ => panic!("expected an 8-bit integer operation, found {opcode:?}"),
+                })
+                .collect::<Vec<_>>()
+        };
+        let expected =
+            vec![BinaryIntOp::LessThan, BinaryIntOp::Sub, BinaryIntOp::Mul, BinaryIntOp::Add];
+        assert_eq!(ops(false), expected);
+        assert_eq!(ops(true), expected);
+    }
+
+    #[test]
+    fn computes_signed_and_unsigned_min_and_max() {
+        let min_max = |lhs: u128, rhs: u128, signed: bool| {
+            let mut context = create_context();
+            let lhs_register = context.allocate_register();
+            let rhs_register = context.allocate_register();
+            let min = context.allocate_register();
+            let max = context.allocate_register();
+            context.min_instruction(lhs_register, rhs_register, min, 8, signed);
+            context.max_instruction(lhs_register, rhs_register, max, 8, signed);
+
+            let memory = execute(context.artifact(), memory_of(&[0, 0, lhs, rhs], 6));
+            (memory[min.to_usize()], memory[max.to_usize()])
+        };
+
+        assert_eq!(min_max(3, 7, false), (Value::from(3_u128), Value::from(7_u128)));
+        assert_eq!(min_max(7, 3, false), (Value::from(3_u128), Value::from(7_u128)));
+        // -1 is the smaller of -1 and 7 as signed integers, but the greater as unsigned ones.
+        assert_eq!(min_max(0xFF, 7, true), (Value::from(0xFF_u128), Value::from(7_u128)));
+        assert_eq!(min_max(0xFF, 7, false), (Value::from(7_u128), Value::from(0xFF_u128)));
+    }
+
+    #[test]
+    fn computes_absolute_values_without_branching() {
+        let abs = |value: u128| {
+            let mut context = create_context();
+            let value_register = context.allocate_register();
+            let result = context.allocate_register();
+            context.abs_instruction(value_register, result, 8);
+
+            let artifact = context.artifact();
+            assert!(!artifact.clone().finish().byte_code.iter().any(|opcode| matches!(
+                opcode,
+                BrilligOpcode::Jump { .. }
+                    | BrilligOpcode::JumpIf { .. }
+                    | BrilligOpcode::JumpIfNot { .. }
+            )));
+            execute(artifact, memory_of(&[0, 0, value], 4))[result.to_usize()]
+        };
+
+        assert_eq!(abs(5), Value::from(5_u128));
+        // -5 as an 8-bit two's complement integer.
+        assert_eq!(abs(0xFB), Value::from(5_u128));
+        assert_eq!(abs(0), Value::from(0_u128));
+        // -128 has no positive counterpart in 8 bits, so its absolute value overflows back to -128.
+        assert_eq!(abs(0x80), Value::from(0x80_u128));
+    }
+
+    #[test]
+    fn normalizes_values_into_booleans() {
+        for bit_size in [FieldElement::max_num_bits(), 32] {
+            let to_bool = |value: u128| {
+                let mut context = create_context();
+                let value_register = context.allocate_register();
+                let result = context.allocate_register();
+                context.to_bool(value_register, result, bit_size);
+                execute(context.artifact(), memory_of(&[0, 0, value], 4))[result.to_usize()]
+            };
+
+            assert_eq!(to_bool(0), Value::from(0_u128));
+            assert_eq!(to_bool(1), Value::from(1_u128));
+            assert_eq!(to_bool(42), Value::from(1_u128));
+        }
+    }
+
+    #[test]
+    fn selects_with_three_integer_opcodes() {
+        let mut context = create_context();
+        let condition = context.allocate_register();
+        let if_true = context.allocate_register();
+        let if_false = context.allocate_register();
+        let result = context.allocate_register();
+        context.select_instruction(condition, if_true, if_false, result, 8);
+
+        let bytecode = context.artifact().finish().byte_code;
+        let ops: Vec<_> = bytecode
+            .iter()
+            .map(|opcode| match opcode {
+                BrilligOpcode::BinaryIntOp { op, bit_size: 8, .. } => *op,
+                _ => panic!("expected an 8-bit integer operation, found {opcode:?}"),
+            })
+            .collect();
+        assert_eq!(ops, vec![BinaryIntOp::Sub, BinaryIntOp::Mul, BinaryIntOp::Add]);
+        assert!(matches!(
+            bytecode[2],
+            BrilligOpcode::BinaryIntOp { lhs, destination, .. } if lhs == if_false && destination == result
+        ));
+    }
+
+    #[test]
+    fn selects_integers_smaller_than_the_alternative() {
+        let select = |condition: bool| {
+            let calldata = vec![Value::from(condition), Value::from(3_u128), Value::from(250_u128)];
+            let arguments = vec![
+                BrilligParameter::SingleAddr(1),
+                BrilligParameter::SingleAddr(8),
+                BrilligParameter::SingleAddr(8),
+            ];
+            let returns = vec![BrilligParameter::SingleAddr(8)];
+
+            let mut context = create_context();
+            let condition = context.allocate_register();
+            let if_true = context.allocate_register();
+            let if_false = context.allocate_register();
+            let result = context.allocate_register();
+            context.select_instruction(condition, if_true, if_false, result, 8);
+            context.return_instruction(&[result]);
+
+            let bytecode = create_entry_point_bytecode(context, arguments, returns).byte_code;
+            let (vm, return_data_offset, _) = create_and_run_vm(calldata, &bytecode);
+            vm.get_memory()[return_data_offset]
+        };
+        assert_eq!(select(true), Value::from(3_u128));
+        assert_eq!(select(false), Value::from(250_u128));
+    }
+}
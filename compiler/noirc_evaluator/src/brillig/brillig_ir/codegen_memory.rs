@@ -0,0 +1,1667 @@
+use std::collections::BTreeMap;
+
+use acvm::{
+    acir::brillig::{BinaryFieldOp, BinaryIntOp, MemoryAddress, Value},
+    FieldElement,
+};
+
+use super::{
+    brillig_variable::SingleAddrVariable, BrilligBinaryOp, BrilligContext,
+    BRILLIG_MEMORY_ADDRESSING_BIT_SIZE,
+};
+
+/// The operations which [`BrilligContext::array_map`] can apply to each element of an array.
+#[derive(Clone, Copy, Debug)]
+#[allow(dead_code)]
+pub(crate) enum UnaryOp {
+    /// Negation, see [`BrilligContext::negate_instruction`].
+    Negate { bit_size: u32 },
+    /// Bitwise NOT of integers.
+    Not { bit_size: u32 },
+    /// Absolute value of signed integers.
+    Abs { bit_size: u32 },
+}
+
+/// The direction in which [`BrilligContext::array_rotate`] moves the elements of an array.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(dead_code)]
+pub(crate) enum RotationDirection {
+    /// The element at index `k` is moved to index 0.
+    Left,
+    /// The element at index 0 is moved to index `k`.
+    Right,
+}
+
+impl BrilligContext {
+    /// Same as [`BrilligContext::array_get`], but traps if `index` is not less than the array length held in `length`.
+    #[allow(dead_code)]
+    pub(crate) fn array_get_checked(
+        &mut self,
+        array_ptr: MemoryAddress,
+        index: MemoryAddress,
+        length: MemoryAddress,
+        result: MemoryAddress,
+    ) {
+        self.assert_index_in_bounds(index, length);
+        self.array_get(array_ptr, index, result);
+    }
+
+    /// Same as [`BrilligContext::array_set`], but traps if `index` is not less than the array length held in `length`.
+    #[allow(dead_code)]
+    pub(crate) fn array_set_checked(
+        &mut self,
+        array_ptr: MemoryAddress,
+        index: MemoryAddress,
+        length: MemoryAddress,
+        value: MemoryAddress,
+    ) {
+        self.assert_index_in_bounds(index, length);
+        self.array_set(array_ptr, index, value);
+    }
+
+    fn assert_index_in_bounds(&mut self, index: MemoryAddress, length: MemoryAddress) {
+        let index_in_bounds = self.allocate_register();
+        self.memory_op(index, length, index_in_bounds, BinaryIntOp::LessThan);
+        self.constrain_instruction(index_in_bounds, Some("Index out of bounds".to_string()));
+        self.deallocate_register(index_in_bounds);
+    }
+
+    /// Compares the first `num_elements` values of the arrays pointed by `lhs_pointer` and `rhs_pointer`.
+    /// Stores in `result` whether all of these values are equal.
+    pub(crate) fn arrays_equal(
+        &mut self,
+        lhs_pointer: MemoryAddress,
+        rhs_pointer: MemoryAddress,
+        num_elements: MemoryAddress,
+        result: MemoryAddress,
+    ) {
+        self.const_instruction(result, Value::from(true), 1);
+
+        let lhs_value = self.allocate_register();
+        let rhs_value = self.allocate_register();
+        let values_are_equal = self.allocate_register();
+
+        self.loop_instruction(num_elements, |ctx, iterator| {
+            ctx.array_get(lhs_pointer, iterator, lhs_value);
+            ctx.array_get(rhs_pointer, iterator, rhs_value);
+            ctx.binary_instruction(
+                lhs_value,
+                rhs_value,
+                values_are_equal,
+                BrilligBinaryOp::Field { op: BinaryFieldOp::Equals },
+            );
+            ctx.binary_instruction(
+                result,
+                values_are_equal,
+                result,
+                BrilligBinaryOp::Integer { op: BinaryIntOp::And, bit_size: 1 },
+            );
+        });
+
+        self.deallocate_register(lhs_value);
+        self.deallocate_register(rhs_value);
+        self.deallocate_register(values_are_equal);
+    }
+
+    /// Applies `operation` to every pair of elements of the arrays pointed by `lhs_pointer` and `rhs_pointer`,
+    /// writing the outcomes into the array pointed by `result_pointer`.
+    pub(crate) fn arrays_binary_instruction(
+        &mut self,
+        lhs_pointer: MemoryAddress,
+        rhs_pointer: MemoryAddress,
+        result_pointer: MemoryAddress,
+        num_elements: MemoryAddress,
+        operation: BrilligBinaryOp,
+    ) {
+        let lhs_value = self.allocate_register();
+        let rhs_value = self.allocate_register();
+        let result_value = self.allocate_register();
+
+        self.loop_instruction(num_elements, |ctx, iterator| {
+            ctx.array_get(lhs_pointer, iterator, lhs_value);
+            ctx.array_get(rhs_pointer, iterator, rhs_value);
+            ctx.binary_instruction(lhs_value, rhs_value, result_value, operation);
+            ctx.array_set(result_pointer, iterator, result_value);
+        });
+
+        self.deallocate_register(lhs_value);
+        self.deallocate_register(rhs_value);
+        self.deallocate_register(result_value);
+    }
+
+    /// Applies `operation` to each of the first `num_elements` values of the array pointed by `source_pointer`,
+    /// writing the outcomes into the array pointed by `destination_pointer`.
+    #[allow(dead_code)]
+    pub(crate) fn array_map(
+        &mut self,
+        source_pointer: MemoryAddress,
+        destination_pointer: MemoryAddress,
+        num_elements: usize,
+        operation: UnaryOp,
+    ) {
+        let value = self.allocate_register();
+        let result = self.allocate_register();
+
+        self.fixed_length_loop_instruction(num_elements, |ctx, iterator| {
+            ctx.array_get(source_pointer, iterator, value);
+            match operation {
+                UnaryOp::Negate { bit_size } => ctx.negate_instruction(value, result, bit_size),
+                UnaryOp::Not { bit_size } => ctx.not_instruction(
+                    SingleAddrVariable { address: value, bit_size },
+                    SingleAddrVariable { address: result, bit_size },
+                ),
+                UnaryOp::Abs { bit_size } => ctx.abs_instruction(value, result, bit_size),
+            }
+            ctx.array_set(destination_pointer, iterator, result);
+        });
+
+        self.deallocate_register(value);
+        self.deallocate_register(result);
+    }
+
+    /// Writes into the array pointed by `result_pointer` whether each element of the array pointed by `lhs_pointer`
+    /// is equal to the element at the same index of the array pointed by `rhs_pointer`.
+    #[allow(dead_code)]
+    pub(crate) fn arrays_eq_mask(
+        &mut self,
+        lhs_pointer: MemoryAddress,
+        rhs_pointer: MemoryAddress,
+        result_pointer: MemoryAddress,
+        num_elements: MemoryAddress,
+    ) {
+        self.arrays_binary_instruction(
+            lhs_pointer,
+            rhs_pointer,
+            result_pointer,
+            num_elements,
+            BrilligBinaryOp::Field { op: BinaryFieldOp::Equals },
+        );
+    }
+
+    /// Writes into the array pointed by `result_pointer` the running reduction with `operation`
+    /// of the first `num_elements` values of the array pointed by `array_pointer`.
+    #[allow(dead_code)]
+    pub(crate) fn array_prefix_sum(
+        &mut self,
+        array_pointer: MemoryAddress,
+        result_pointer: MemoryAddress,
+        num_elements: MemoryAddress,
+        operation: BrilligBinaryOp,
+    ) {
+        let accumulator = self.allocate_register();
+        let value = self.allocate_register();
+        let is_first_element = self.allocate_register();
+        let zero = self.make_usize_constant(0_usize.into());
+
+        self.loop_instruction(num_elements, |ctx, iterator| {
+            ctx.array_get(array_pointer, iterator, value);
+            ctx.memory_op(iterator, zero, is_first_element, BinaryIntOp::Equals);
+            ctx.branch_instruction(is_first_element, |ctx, is_first_element| {
+                if is_first_element {
+                    ctx.mov_instruction(accumulator, value);
+                } else {
+                    ctx.binary_instruction(accumulator, value, accumulator, operation);
+                }
+            });
+            ctx.array_set(result_pointer, iterator, accumulator);
+        });
+
+        self.deallocate_register(accumulator);
+        self.deallocate_register(value);
+        self.deallocate_register(is_first_element);
+        self.deallocate_register(zero);
+    }
+
+    /// Folds the first `num_elements` values of the array pointed by `array_pointer` into `accumulator`
+    /// with `operation`, starting from the value in `initial`.
+    ///
+    /// Elements are folded from first to last with the accumulator as the left operand, i.e. the result is
+    /// `(((initial op a[0]) op a[1]) ... op a[n - 1])`, which matters for non-commutative operations.
+    /// `initial` may be the same register as `accumulator`.
+    pub(crate) fn array_reduce(
+        &mut self,
+        array_pointer: MemoryAddress,
+        num_elements: MemoryAddress,
+        accumulator: MemoryAddress,
+        initial: MemoryAddress,
+        operation: BrilligBinaryOp,
+    ) {
+        self.array_fold_with_index(
+            array_pointer,
+            num_elements,
+            accumulator,
+            initial,
+            |ctx, accumulator, _, value| {
+                ctx.binary_instruction(accumulator, value, accumulator, operation);
+            },
+        );
+    }
+
+    /// Stores in `result` the dot product `sum(lhs[i] * rhs[i])` of the first `num_elements` values
+    /// of the arrays pointed by `lhs_pointer` and `rhs_pointer`.
+    ///
+    /// Values of `bit_size` [`FieldElement::max_num_bits`] are multiplied as field elements, others as
+    /// integers of `bit_size` bits, whose arithmetic wraps.
+    #[allow(dead_code)]
+    pub(crate) fn array_dot(
+        &mut self,
+        lhs_pointer: MemoryAddress,
+        rhs_pointer: MemoryAddress,
+        num_elements: usize,
+        result: MemoryAddress,
+        bit_size: u32,
+    ) {
+        let (multiply, add) = if bit_size == FieldElement::max_num_bits() {
+            (
+                BrilligBinaryOp::Field { op: BinaryFieldOp::Mul },
+                BrilligBinaryOp::Field { op: BinaryFieldOp::Add },
+            )
+        } else {
+            (
+                BrilligBinaryOp::Integer { op: BinaryIntOp::Mul, bit_size },
+                BrilligBinaryOp::Integer { op: BinaryIntOp::Add, bit_size },
+            )
+        };
+
+        let zero = self.cached_constant(Value::from(0_u128), bit_size);
+        self.mov_instruction(result, zero);
+
+        let lhs_value = self.allocate_register();
+        let rhs_value = self.allocate_register();
+        self.fixed_length_loop_instruction(num_elements, |ctx, iterator| {
+            ctx.array_get(lhs_pointer, iterator, lhs_value);
+            ctx.array_get(rhs_pointer, iterator, rhs_value);
+            ctx.binary_instruction(lhs_value, rhs_value, lhs_value, multiply);
+            ctx.binary_instruction(result, lhs_value, result, add);
+        });
+
+        self.deallocate_register(lhs_value);
+        self.deallocate_register(rhs_value);
+    }
+
+    /// Folds the first `num_elements` values of the array pointed by `array_pointer` into `accumulator`,
+    /// starting from the value in `initial`.
+    ///
+    /// `on_element` receives the accumulator, the index of the current element and its value,
+    /// and must leave the updated accumulator in place.
+    pub(crate) fn array_fold_with_index<F>(
+        &mut self,
+        array_pointer: MemoryAddress,
+        num_elements: MemoryAddress,
+        accumulator: MemoryAddress,
+        initial: MemoryAddress,
+        on_element: F,
+    ) where
+        F: FnOnce(&mut BrilligContext, MemoryAddress, MemoryAddress, MemoryAddress),
+    {
+        if accumulator != initial {
+            self.mov_instruction(accumulator, initial);
+        }
+        let value = self.allocate_register();
+
+        self.loop_instruction(num_elements, |ctx, iterator| {
+            ctx.array_get(array_pointer, iterator, value);
+            on_element(ctx, accumulator, iterator, value);
+        });
+
+        self.deallocate_register(value);
+    }
+
+    /// Stores in `result` the XOR of the first `num_elements` integers of the array pointed by `array_pointer`.
+    #[allow(dead_code)]
+    pub(crate) fn array_xor_checksum(
+        &mut self,
+        array_pointer: MemoryAddress,
+        num_elements: MemoryAddress,
+        result: MemoryAddress,
+        bit_size: u32,
+    ) {
+        let zero = self.cached_constant(Value::from(0_usize), bit_size);
+        self.array_reduce(
+            array_pointer,
+            num_elements,
+            result,
+            zero,
+            BrilligBinaryOp::Integer { op: BinaryIntOp::Xor, bit_size },
+        );
+    }
+
+    /// Stores in `result` how many of the first `num_elements` values of the array pointed by `array_ptr`
+    /// satisfy the comparison `needle op element`, e.g. equal `needle` for an `Equals` operation.
+    ///
+    /// `op` must be a comparison, producing a boolean.
+    #[allow(dead_code)]
+    pub(crate) fn array_count(
+        &mut self,
+        array_ptr: MemoryAddress,
+        num_elements: MemoryAddress,
+        needle: MemoryAddress,
+        op: BrilligBinaryOp,
+        result: MemoryAddress,
+    ) {
+        let zero = self.make_usize_constant(0_usize.into());
+        let matches = self.allocate_register();
+
+        self.array_fold_with_index(
+            array_ptr,
+            num_elements,
+            result,
+            zero,
+            |ctx, count, _, value| {
+                ctx.binary_instruction(needle, value, matches, op);
+                ctx.memory_op(count, matches, count, BinaryIntOp::Add);
+            },
+        );
+
+        self.deallocate_register(zero);
+        self.deallocate_register(matches);
+    }
+
+    /// Stores in `result_index` the index of the largest of the first `num_elements` integers of the array
+    /// pointed by `array_pointer`, or of the first of them if several are equally large.
+    /// `result_index` is zero for empty arrays.
+    #[allow(dead_code)]
+    pub(crate) fn array_argmax(
+        &mut self,
+        array_pointer: MemoryAddress,
+        num_elements: MemoryAddress,
+        result_index: MemoryAddress,
+        signed: bool,
+        bit_size: u32,
+    ) {
+        self.usize_const(result_index, 0_usize.into());
+        let max = self.allocate_register();
+        let value = self.allocate_register();
+        let is_new_max = self.allocate_register();
+        let is_first_element = self.allocate_register();
+        let zero = self.make_usize_constant(0_usize.into());
+
+        self.loop_instruction(num_elements, |ctx, iterator| {
+            ctx.array_get(array_pointer, iterator, value);
+
+            // Only strictly greater values replace the maximum, so ties keep the first index.
+            ctx.less_than_instruction(max, value, is_new_max, bit_size, signed);
+            ctx.memory_op(iterator, zero, is_first_element, BinaryIntOp::Equals);
+            ctx.binary_instruction(
+                is_new_max,
+                is_first_element,
+                is_new_max,
+                BrilligBinaryOp::Integer { op: BinaryIntOp::Or, bit_size: 1 },
+            );
+
+            ctx.conditional_select(is_new_max, value, max, max);
+            ctx.conditional_select(is_new_max, iterator, result_index, result_index);
+        });
+
+        self.deallocate_register(max);
+        self.deallocate_register(value);
+        self.deallocate_register(is_new_max);
+        self.deallocate_register(is_first_element);
+        self.deallocate_register(zero);
+    }
+
+    /// Searches the first `num_elements` values of the array pointed by `array_pointer` for `needle`,
+    /// storing in `found` whether it is present and in `result_index` its index.
+    /// If `needle` is absent, `result_index` is the index at which it could be inserted keeping the array sorted.
+    ///
+    /// The array must be sorted in ascending order as unsigned integers of the bit size of `needle`.
+    #[allow(dead_code)]
+    pub(crate) fn array_binary_search(
+        &mut self,
+        array_pointer: MemoryAddress,
+        num_elements: MemoryAddress,
+        needle: SingleAddrVariable,
+        result_index: MemoryAddress,
+        found: MemoryAddress,
+    ) {
+        let low = self.make_usize_constant(0_usize.into());
+        let high = self.allocate_register();
+        self.mov_instruction(high, num_elements);
+        let middle = self.allocate_register();
+        let value = self.allocate_register();
+        let condition = SingleAddrVariable { address: self.allocate_register(), bit_size: 1 };
+
+        let (loop_section, loop_label) = self.reserve_next_section_label();
+        let (absent_section, absent_label) = self.reserve_next_section_label();
+        let (present_section, present_label) = self.reserve_next_section_label();
+        let (end_section, end_label) = self.reserve_next_section_label();
+
+        // Search while the range [low, high) is not empty
+        self.enter_section(loop_section);
+        self.memory_op(low, high, condition.address, BinaryIntOp::LessThan);
+        self.not_instruction(condition, condition);
+        self.jump_if_instruction(condition.address, absent_label);
+
+        self.memory_op(low, high, middle, BinaryIntOp::Add);
+        self.usize_op_in_place(middle, BinaryIntOp::UnsignedDiv, 2);
+        self.array_get(array_pointer, middle, value);
+
+        self.binary_instruction(
+            value,
+            needle.address,
+            condition.address,
+            BrilligBinaryOp::Integer { op: BinaryIntOp::Equals, bit_size: needle.bit_size },
+        );
+        self.jump_if_instruction(condition.address, present_label);
+
+        self.binary_instruction(
+            value,
+            needle.address,
+            condition.address,
+            BrilligBinaryOp::Integer { op: BinaryIntOp::LessThan, bit_size: needle.bit_size },
+        );
+        self.branch_instruction(condition.address, |ctx, value_is_less| {
+            if value_is_less {
+                ctx.usize_op(middle, low, BinaryIntOp::Add, 1);
+            } else {
+                ctx.mov_instruction(high, middle);
+            }
+        });
+        self.jump_instruction(loop_label);
+
+        self.enter_section(absent_section);
+        self.const_instruction(found, Value::from(false), 1);
+        self.mov_instruction(result_index, low);
+        self.jump_instruction(end_label);
+
+        self.enter_section(present_section);
+        self.const_instruction(found, Value::from(true), 1);
+        self.mov_instruction(result_index, middle);
+
+        self.enter_section(end_section);
+
+        self.deallocate_register(low);
+        self.deallocate_register(high);
+        self.deallocate_register(middle);
+        self.deallocate_register(value);
+        self.deallocate_register(condition.address);
+    }
+
+    /// Copies `num_elements` values of the array pointed by `array_pointer` starting at `source_index`
+    /// to the same array starting at `destination_index`. The two ranges may overlap.
+    #[allow(dead_code)]
+    pub(crate) fn copy_within(
+        &mut self,
+        array_pointer: MemoryAddress,
+        source_index: MemoryAddress,
+        destination_index: MemoryAddress,
+        num_elements: MemoryAddress,
+    ) {
+        let source_pointer = self.allocate_register();
+        let destination_pointer = self.allocate_register();
+        self.memory_op(array_pointer, source_index, source_pointer, BinaryIntOp::Add);
+        self.memory_op(array_pointer, destination_index, destination_pointer, BinaryIntOp::Add);
+
+        self.copy_possibly_overlapping(source_pointer, destination_pointer, num_elements);
+
+        self.deallocate_register(source_pointer);
+        self.deallocate_register(destination_pointer);
+    }
+
+    /// Copies `num_elements` values from the memory pointed by `source_pointer` to the memory pointed by
+    /// `destination_pointer`. Unlike [`BrilligContext::copy_array_instruction`], the two ranges may overlap.
+    #[allow(dead_code)]
+    pub(crate) fn array_copy(
+        &mut self,
+        source_pointer: MemoryAddress,
+        destination_pointer: MemoryAddress,
+        num_elements: usize,
+    ) {
+        let num_elements = self.make_usize_constant(num_elements.into());
+        self.copy_possibly_overlapping(source_pointer, destination_pointer, num_elements);
+        self.deallocate_register(num_elements);
+    }
+
+    fn copy_possibly_overlapping(
+        &mut self,
+        source_pointer: MemoryAddress,
+        destination_pointer: MemoryAddress,
+        num_elements: MemoryAddress,
+    ) {
+        let copies_forward = self.allocate_register();
+        self.memory_op(destination_pointer, source_pointer, copies_forward, BinaryIntOp::LessThan);
+
+        let value = self.allocate_register();
+        let index = self.allocate_register();
+
+        // Copying towards the start of the array must go front to back, and towards the end back to front,
+        // so that each value is read before the copy overwrites it.
+        self.branch_instruction(copies_forward, |ctx, copies_forward| {
+            if copies_forward {
+                ctx.copy_array_instruction(source_pointer, destination_pointer, num_elements);
+            } else {
+                ctx.loop_instruction(num_elements, |ctx, iterator| {
+                    // The index from the end of the range is num_elements - 1 - iterator
+                    ctx.memory_op(num_elements, iterator, index, BinaryIntOp::Sub);
+                    ctx.usize_op_in_place(index, BinaryIntOp::Sub, 1);
+                    ctx.array_get(source_pointer, index, value);
+                    ctx.array_set(destination_pointer, index, value);
+                });
+            }
+        });
+
+        self.deallocate_register(copies_forward);
+        self.deallocate_register(value);
+        self.deallocate_register(index);
+    }
+
+    /// Reverses in place the order of the first `num_elements` values of the array pointed by `array_pointer`.
+    pub(crate) fn array_reverse(
+        &mut self,
+        array_pointer: MemoryAddress,
+        num_elements: MemoryAddress,
+    ) {
+        let iteration_count = self.allocate_register();
+        self.usize_op(num_elements, iteration_count, BinaryIntOp::UnsignedDiv, 2);
+
+        let start_value_register = self.allocate_register();
+        let index_at_end_of_array = self.allocate_register();
+        let end_value_register = self.allocate_register();
+
+        self.loop_instruction(iteration_count, |ctx, iterator_register| {
+            // Load both values
+            ctx.array_get(array_pointer, iterator_register, start_value_register);
+
+            // The index at the end of array is size - 1 - iterator
+            ctx.mov_instruction(index_at_end_of_array, num_elements);
+            ctx.usize_op_in_place(index_at_end_of_array, BinaryIntOp::Sub, 1);
+            ctx.memory_op(
+                index_at_end_of_array,
+                iterator_register,
+                index_at_end_of_array,
+                BinaryIntOp::Sub,
+            );
+
+            ctx.array_get(array_pointer, index_at_end_of_array, end_value_register);
+
+            // Write both values
+            ctx.array_set(array_pointer, iterator_register, end_value_register);
+            ctx.array_set(array_pointer, index_at_end_of_array, start_value_register);
+        });
+
+        self.deallocate_register(iteration_count);
+        self.deallocate_register(start_value_register);
+        self.deallocate_register(end_value_register);
+        self.deallocate_register(index_at_end_of_array);
+    }
+
+    /// Rotates in place the first `num_elements` values of the array pointed by `array_pointer`
+    /// by `k` positions in the given `direction`. `k` may be larger than `num_elements`.
+    #[allow(dead_code)]
+    pub(crate) fn array_rotate(
+        &mut self,
+        array_pointer: MemoryAddress,
+        num_elements: MemoryAddress,
+        k: MemoryAddress,
+        direction: RotationDirection,
+    ) {
+        let zero = self.make_usize_constant(0_usize.into());
+        let is_empty = self.allocate_register();
+        self.memory_op(num_elements, zero, is_empty, BinaryIntOp::Equals);
+
+        let shift = self.allocate_register();
+        let tail_pointer = self.allocate_register();
+        let tail_length = self.allocate_register();
+
+        // Rotating an empty array is a no-op, and would otherwise divide by zero below.
+        self.branch_instruction(is_empty, |ctx, is_empty| {
+            if is_empty {
+                return;
+            }
+            ctx.modulo_instruction(
+                shift,
+                k,
+                num_elements,
+                BRILLIG_MEMORY_ADDRESSING_BIT_SIZE,
+                false,
+            );
+            // A right rotation by `k` is a left rotation by `num_elements - k`.
+            if direction == RotationDirection::Right {
+                ctx.memory_op(num_elements, shift, shift, BinaryIntOp::Sub);
+            }
+
+            // Rotating left by `shift` is reversing the first `shift` elements, the remaining ones,
+            // and then the whole array.
+            ctx.memory_op(array_pointer, shift, tail_pointer, BinaryIntOp::Add);
+            ctx.memory_op(num_elements, shift, tail_length, BinaryIntOp::Sub);
+            ctx.array_reverse(array_pointer, shift);
+            ctx.array_reverse(tail_pointer, tail_length);
+            ctx.array_reverse(array_pointer, num_elements);
+        });
+
+        self.deallocate_register(zero);
+        self.deallocate_register(is_empty);
+        self.deallocate_register(shift);
+        self.deallocate_register(tail_pointer);
+        self.deallocate_register(tail_length);
+    }
+
+    /// Stores `value` at the address in `destination_pointer` if the boolean `condition` is true.
+    ///
+    /// The store is always emitted, writing back the value already in memory when `condition` is false,
+    /// so that no branch is needed.
+    pub(crate) fn store_if(
+        &mut self,
+        condition: MemoryAddress,
+        destination_pointer: MemoryAddress,
+        value: MemoryAddress,
+    ) {
+        let stored_value = self.allocate_register();
+        self.load_instruction(stored_value, destination_pointer);
+        self.conditional_select(condition, value, stored_value, stored_value);
+        self.store_instruction(destination_pointer, stored_value);
+        self.deallocate_register(stored_value);
+    }
+
+    /// Copies each of the first `num_elements` values of the array pointed by `source_pointer` into the same
+    /// index of the array pointed by `destination_pointer` if the boolean at that index of the array pointed
+    /// by `mask_pointer` is true, leaving the other elements of the destination unchanged.
+    #[allow(dead_code)]
+    pub(crate) fn masked_copy(
+        &mut self,
+        source_pointer: MemoryAddress,
+        destination_pointer: MemoryAddress,
+        mask_pointer: MemoryAddress,
+        num_elements: usize,
+    ) {
+        let condition = self.allocate_register();
+        let value = self.allocate_register();
+        let element_pointer = self.allocate_register();
+        self.fixed_length_loop_instruction(num_elements, |ctx, iterator| {
+            ctx.array_get(mask_pointer, iterator, condition);
+            ctx.array_get(source_pointer, iterator, value);
+            ctx.memory_op(destination_pointer, iterator, element_pointer, BinaryIntOp::Add);
+            ctx.store_if(condition, element_pointer, value);
+        });
+        self.deallocate_register(condition);
+        self.deallocate_register(value);
+        self.deallocate_register(element_pointer);
+    }
+
+    /// Stores `value` into the first `num_elements` elements of the array pointed by `array_pointer`.
+    pub(crate) fn array_fill(
+        &mut self,
+        array_pointer: MemoryAddress,
+        value: MemoryAddress,
+        num_elements: usize,
+    ) {
+        self.fixed_length_loop_instruction(num_elements, |ctx, iterator| {
+            ctx.array_set(array_pointer, iterator, value);
+        });
+    }
+
+    /// Zeroes the first `num_elements` elements of the array pointed by `array_pointer`,
+    /// e.g. to reuse an array which has already been written to.
+    #[allow(dead_code)]
+    pub(crate) fn array_zero(&mut self, array_pointer: MemoryAddress, num_elements: usize) {
+        let zero = self.make_constant(Value::from(0_u128), FieldElement::max_num_bits());
+        self.array_fill(array_pointer, zero, num_elements);
+        self.deallocate_register(zero);
+    }
+
+    /// Allocates an array holding `values` as constants of `bit_size` bits and returns the register
+    /// holding its pointer.
+    ///
+    /// Each distinct value is loaded into a register once, and runs of repeated values are stored
+    /// in a loop, subject to the unroll threshold.
+    #[allow(dead_code)]
+    pub(crate) fn const_array(&mut self, values: &[Value], bit_size: u32) -> MemoryAddress {
+        let array_pointer = self.allocate_register();
+        self.allocate_fixed_length_array(array_pointer, values.len());
+
+        let mut constants: BTreeMap<Value, MemoryAddress> = BTreeMap::new();
+        let mut start = 0;
+        while start < values.len() {
+            let value = values[start];
+            let run_length =
+                values[start..].iter().take_while(|element| **element == value).count();
+            let value_register =
+                *constants.entry(value).or_insert_with(|| self.make_constant(value, bit_size));
+
+            if run_length == 1 {
+                let index = self.make_usize_constant(start.into());
+                self.array_set(array_pointer, index, value_register);
+                self.deallocate_register(index);
+            } else {
+                self.fixed_length_loop_instruction(run_length, |ctx, iterator| {
+                    let index = ctx.allocate_register();
+                    ctx.usize_op(iterator, index, BinaryIntOp::Add, start);
+                    ctx.array_set(array_pointer, index, value_register);
+                    ctx.deallocate_register(index);
+                });
+            }
+            start += run_length;
+        }
+
+        for register in constants.into_values() {
+            self.deallocate_register(register);
+        }
+        array_pointer
+    }
+
+    /// Traps unless the first `num_elements` values of the arrays pointed by `lhs_pointer`
+    /// and `rhs_pointer` are equal.
+    #[allow(dead_code)]
+    pub(crate) fn assert_array_eq(
+        &mut self,
+        lhs_pointer: MemoryAddress,
+        rhs_pointer: MemoryAddress,
+        num_elements: MemoryAddress,
+    ) {
+        let arrays_are_equal = self.allocate_register();
+        self.arrays_equal(lhs_pointer, rhs_pointer, num_elements, arrays_are_equal);
+        self.constrain_instruction(arrays_are_equal, None);
+        self.deallocate_register(arrays_are_equal);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::{
+        brillig_vm::{
+            brillig::{BinaryIntOp, Value},
+            VMStatus, VM,
+        },
+        FieldElement,
+    };
+
+    use crate::brillig::brillig_ir::{
+        artifact::BrilligParameter,
+        brillig_variable::{BrilligArray, SingleAddrVariable},
+        tests::{
+            create_and_run_vm, create_context, create_entry_point_bytecode, execute, memory_of,
+            DummyBlackBoxSolver,
+        },
+        BrilligBinaryOp, BrilligOpcode,
+    };
+
+    use super::{RotationDirection, UnaryOp};
+
+    #[test]
+    fn checks_bounds_before_accessing_array() {
+        let mut context = create_context();
+        let array_ptr = context.allocate_register();
+        let index = context.allocate_register();
+        let length = context.allocate_register();
+        let value = context.allocate_register();
+        context.array_get_checked(array_ptr, index, length, value);
+        context.array_set_checked(array_ptr, index, length, value);
+
+        let bytecode = context.artifact().finish().byte_code;
+        assert_eq!(bytecode.len(), 10);
+        for (access, opcodes) in bytecode.chunks(5).enumerate() {
+            let BrilligOpcode::BinaryIntOp {
+                op: BinaryIntOp::LessThan,
+                lhs,
+                rhs,
+                destination: index_in_bounds,
+                ..
+            } = opcodes[0]
+            else {
+                panic!("expected a bounds comparison, found {:?}", opcodes[0]);
+            };
+            assert_eq!((lhs, rhs), (index, length));
+            assert_eq!(
+                opcodes[1],
+                BrilligOpcode::JumpIf { condition: index_in_bounds, location: access * 5 + 3 }
+            );
+            assert_eq!(opcodes[2], BrilligOpcode::Trap);
+            assert!(matches!(opcodes[3], BrilligOpcode::BinaryFieldOp { .. }));
+        }
+        assert!(matches!(bytecode[4], BrilligOpcode::Load { .. }));
+        assert!(matches!(bytecode[9], BrilligOpcode::Store { .. }));
+    }
+
+    fn assert_array_eq(lhs: Vec<Value>, rhs: Vec<Value>) -> VMStatus {
+        let num_elements = lhs.len();
+        let calldata = [lhs, rhs].concat();
+        let arguments =
+            vec![BrilligParameter::Array(vec![BrilligParameter::SingleAddr(32)], num_elements); 2];
+
+        let mut context = create_context();
+        let lhs_pointer = context.allocate_register();
+        let _lhs_rc = context.allocate_register();
+        let rhs_pointer = context.allocate_register();
+        let _rhs_rc = context.allocate_register();
+
+        let num_elements = context.make_usize_constant(num_elements.into());
+        context.assert_array_eq(lhs_pointer, rhs_pointer, num_elements);
+        context.return_instruction(&[]);
+
+        let bytecode = create_entry_point_bytecode(context, arguments, vec![]).byte_code;
+        let mut vm = VM::new(calldata, &bytecode, vec![], &DummyBlackBoxSolver);
+        vm.process_opcodes()
+    }
+
+    #[test]
+    fn does_not_trap_on_equal_arrays() {
+        let array: Vec<Value> = (1_u128..=3).map(Value::from).collect();
+
+        let status = assert_array_eq(array.clone(), array);
+        assert!(matches!(status, VMStatus::Finished { .. }));
+    }
+
+    #[test]
+    fn traps_on_differing_arrays() {
+        let lhs: Vec<Value> = (1_u128..=3).map(Value::from).collect();
+        let rhs = vec![Value::from(1_u128), Value::from(5_u128), Value::from(3_u128)];
+
+        let status = assert_array_eq(lhs, rhs);
+        assert!(matches!(status, VMStatus::Failure { .. }));
+    }
+
+    #[test]
+    fn writes_equality_mask() {
+        let lhs: Vec<Value> = [1_u128, 2, 3, 4].into_iter().map(Value::from).collect();
+        let rhs: Vec<Value> = [1_u128, 5, 3, 0].into_iter().map(Value::from).collect();
+        let num_elements = lhs.len();
+
+        let calldata = [lhs, rhs].concat();
+        let arguments =
+            vec![BrilligParameter::Array(vec![BrilligParameter::SingleAddr(32)], num_elements); 2];
+        let returns =
+            vec![BrilligParameter::Array(vec![BrilligParameter::SingleAddr(1)], num_elements)];
+
+        let mut context = create_context();
+        let lhs_pointer = context.allocate_register();
+        let _lhs_rc = context.allocate_register();
+        let rhs_pointer = context.allocate_register();
+        let _rhs_rc = context.allocate_register();
+
+        let mask = BrilligArray {
+            pointer: context.allocate_register(),
+            size: num_elements,
+            rc: context.make_usize_constant(1_usize.into()),
+        };
+        let num_elements_register = context.make_usize_constant(num_elements.into());
+        context.allocate_array_instruction(mask.pointer, num_elements_register);
+        context.arrays_eq_mask(lhs_pointer, rhs_pointer, mask.pointer, num_elements_register);
+        context.return_instruction(&mask.extract_registers());
+
+        let bytecode = create_entry_point_bytecode(context, arguments, returns).byte_code;
+        let (vm, return_data_offset, return_data_size) = create_and_run_vm(calldata, &bytecode);
+        assert_eq!(return_data_size, num_elements);
+
+        let expected_mask: Vec<Value> =
+            [true, false, true, false].into_iter().map(Value::from).collect();
+        assert_eq!(
+            vm.get_memory()[return_data_offset..(return_data_offset + num_elements)],
+            expected_mask
+        );
+    }
+
+    #[test]
+    fn computes_prefix_sums() {
+        let array: Vec<Value> = [1_u128, 2, 3, 4].into_iter().map(Value::from).collect();
+        let num_elements = array.len();
+
+        let arguments =
+            vec![BrilligParameter::Array(vec![BrilligParameter::SingleAddr(32)], num_elements)];
+        let returns =
+            vec![BrilligParameter::Array(vec![BrilligParameter::SingleAddr(32)], num_elements)];
+
+        let mut context = create_context();
+        let array_pointer = context.allocate_register();
+        let _array_rc = context.allocate_register();
+
+        let prefix_sums = BrilligArray {
+            pointer: context.allocate_register(),
+            size: num_elements,
+            rc: context.make_usize_constant(1_usize.into()),
+        };
+        let num_elements_register = context.make_usize_constant(num_elements.into());
+        context.allocate_array_instruction(prefix_sums.pointer, num_elements_register);
+        context.array_prefix_sum(
+            array_pointer,
+            prefix_sums.pointer,
+            num_elements_register,
+            BrilligBinaryOp::Integer { op: BinaryIntOp::Add, bit_size: 32 },
+        );
+        context.return_instruction(&prefix_sums.extract_registers());
+
+        let bytecode = create_entry_point_bytecode(context, arguments, returns).byte_code;
+        let (vm, return_data_offset, return_data_size) = create_and_run_vm(array, &bytecode);
+        assert_eq!(return_data_size, num_elements);
+
+        let expected: Vec<Value> = [1_u128, 3, 6, 10].into_iter().map(Value::from).collect();
+        assert_eq!(
+            vm.get_memory()[return_data_offset..(return_data_offset + num_elements)],
+            expected
+        );
+    }
+
+    #[test]
+    fn computes_xor_checksum() {
+        let array: Vec<Value> = [0x0F_u128, 0xF0].into_iter().map(Value::from).collect();
+        let num_elements = array.len();
+
+        let arguments =
+            vec![BrilligParameter::Array(vec![BrilligParameter::SingleAddr(8)], num_elements)];
+        let returns = vec![BrilligParameter::SingleAddr(8)];
+
+        let mut context = create_context();
+        let array_pointer = context.allocate_register();
+        let _array_rc = context.allocate_register();
+
+        let checksum = context.allocate_register();
+        let num_elements_register = context.make_usize_constant(num_elements.into());
+        context.array_xor_checksum(array_pointer, num_elements_register, checksum, 8);
+        context.return_instruction(&[checksum]);
+
+        let bytecode = create_entry_point_bytecode(context, arguments, returns).byte_code;
+        let (vm, return_data_offset, return_data_size) = create_and_run_vm(array, &bytecode);
+        assert_eq!(return_data_size, 1);
+        assert_eq!(vm.get_memory()[return_data_offset], Value::from(0xFF_u128));
+    }
+
+    #[test]
+    fn folds_with_element_indices() {
+        let array: Vec<Value> = [3_u128, 5, 7, 11].into_iter().map(Value::from).collect();
+        let num_elements = array.len();
+
+        let arguments =
+            vec![BrilligParameter::Array(vec![BrilligParameter::SingleAddr(32)], num_elements)];
+        let returns = vec![BrilligParameter::SingleAddr(32)];
+
+        let mut context = create_context();
+        let array_pointer = context.allocate_register();
+        let _array_rc = context.allocate_register();
+
+        let dot_product = context.allocate_register();
+        let initial = context.make_constant(0_u128.into(), 32);
+        let product = context.allocate_register();
+        let num_elements_register = context.make_usize_constant(num_elements.into());
+        context.array_fold_with_index(
+            array_pointer,
+            num_elements_register,
+            dot_product,
+            initial,
+            |ctx, accumulator, index, value| {
+                let mul = BrilligBinaryOp::Integer { op: BinaryIntOp::Mul, bit_size: 32 };
+                let add = BrilligBinaryOp::Integer { op: BinaryIntOp::Add, bit_size: 32 };
+                ctx.binary_instruction(index, value, product, mul);
+                ctx.binary_instruction(accumulator, product, accumulator, add);
+            },
+        );
+        context.return_instruction(&[dot_product]);
+
+        let bytecode = create_entry_point_bytecode(context, arguments, returns).byte_code;
+        let (vm, return_data_offset, return_data_size) = create_and_run_vm(array, &bytecode);
+        assert_eq!(return_data_size, 1);
+        // 0 * 3 + 1 * 5 + 2 * 7 + 3 * 11
+        assert_eq!(vm.get_memory()[return_data_offset], Value::from(52_u128));
+    }
+
+    fn binary_search(array: Vec<u128>, needle: u128) -> (Value, Value) {
+        let num_elements = array.len();
+        let calldata: Vec<Value> = array.into_iter().chain([needle]).map(Value::from).collect();
+
+        let arguments = vec![
+            BrilligParameter::Array(vec![BrilligParameter::SingleAddr(32)], num_elements),
+            BrilligParameter::SingleAddr(32),
+        ];
+        let returns = vec![BrilligParameter::SingleAddr(32), BrilligParameter::SingleAddr(1)];
+
+        let mut context = create_context();
+        let array_pointer = context.allocate_register();
+        let _array_rc = context.allocate_register();
+        let needle = SingleAddrVariable { address: context.allocate_register(), bit_size: 32 };
+
+        let result_index = context.allocate_register();
+        let found = context.allocate_register();
+        let num_elements_register = context.make_usize_constant(num_elements.into());
+        context.array_binary_search(
+            array_pointer,
+            num_elements_register,
+            needle,
+            result_index,
+            found,
+        );
+        context.return_instruction(&[result_index, found]);
+
+        let bytecode = create_entry_point_bytecode(context, arguments, returns).byte_code;
+        let (vm, return_data_offset, return_data_size) = create_and_run_vm(calldata, &bytecode);
+        assert_eq!(return_data_size, 2);
+        let memory = vm.get_memory();
+        (memory[return_data_offset], memory[return_data_offset + 1])
+    }
+
+    #[test]
+    fn binary_searches_present_values() {
+        let array = vec![1, 3, 5, 7, 9];
+        for (index, needle) in array.iter().enumerate() {
+            assert_eq!(
+                binary_search(array.clone(), *needle),
+                (Value::from(index), Value::from(true))
+            );
+        }
+    }
+
+    #[test]
+    fn binary_searches_absent_values() {
+        let array = vec![1, 3, 5, 7, 9];
+        assert_eq!(binary_search(array.clone(), 0), (Value::from(0_usize), Value::from(false)));
+        assert_eq!(binary_search(array.clone(), 4), (Value::from(2_usize), Value::from(false)));
+        assert_eq!(binary_search(array.clone(), 10), (Value::from(5_usize), Value::from(false)));
+        assert_eq!(binary_search(vec![], 4), (Value::from(0_usize), Value::from(false)));
+    }
+
+    #[test]
+    fn fills_every_element_of_arrays() {
+        const ARRAY_START: usize = 100;
+
+        for unroll_threshold in [0, 8] {
+            let mut context = create_context();
+            context.set_unroll_threshold(unroll_threshold);
+            let array_pointer = context.allocate_register();
+            let value = context.allocate_register();
+            context.array_fill(array_pointer, value, 3);
+
+            let mut initial_memory = memory_of(&[0, 0, ARRAY_START as u128, 42], ARRAY_START);
+            initial_memory.extend([1_u128, 2, 3, 4].map(Value::from));
+            let memory = execute(context.artifact(), initial_memory);
+
+            // The element past `num_elements` is left untouched.
+            assert_eq!(memory[ARRAY_START..ARRAY_START + 4], [42_u128, 42, 42, 4].map(Value::from));
+        }
+    }
+
+    #[test]
+    fn copies_the_elements_selected_by_a_mask() {
+        const SOURCE_START: usize = 100;
+        const DESTINATION_START: usize = 104;
+        const MASK_START: usize = 108;
+
+        for unroll_threshold in [0, 8] {
+            let mut context = create_context();
+            context.set_unroll_threshold(unroll_threshold);
+            let source_pointer = context.allocate_register();
+            let destination_pointer = context.allocate_register();
+            let mask_pointer = context.allocate_register();
+            context.masked_copy(source_pointer, destination_pointer, mask_pointer, 3);
+
+            let pointers =
+                [0, 0, SOURCE_START as u128, DESTINATION_START as u128, MASK_START as u128];
+            let mut initial_memory = memory_of(&pointers, SOURCE_START);
+            initial_memory.extend([1_u128, 2, 3, 4].map(Value::from));
+            initial_memory.extend([10_u128, 20, 30, 40].map(Value::from));
+            initial_memory.extend([1_u128, 0, 1, 1].map(Value::from));
+            let memory = execute(context.artifact(), initial_memory);
+
+            // Only the masked elements among the first `num_elements` are copied.
+            assert_eq!(
+                memory[DESTINATION_START..DESTINATION_START + 4],
+                [1_u128, 20, 3, 40].map(Value::from)
+            );
+            assert_eq!(memory[SOURCE_START..SOURCE_START + 4], [1_u128, 2, 3, 4].map(Value::from));
+        }
+    }
+
+    #[test]
+    fn zeroes_arrays() {
+        const ARRAY_START: usize = 100;
+
+        let mut context = create_context();
+        let array_pointer = context.allocate_register();
+        context.array_zero(array_pointer, 3);
+
+        let mut initial_memory = memory_of(&[0, 0, ARRAY_START as u128], ARRAY_START);
+        initial_memory.extend([1_u128, 2, 3, 4].map(Value::from));
+        let memory = execute(context.artifact(), initial_memory);
+
+        assert_eq!(memory[ARRAY_START..ARRAY_START + 4], [0_u128, 0, 0, 4].map(Value::from));
+    }
+
+    #[test]
+    fn zeroes_filled_arrays() {
+        const ARRAY_START: usize = 100;
+
+        for unroll_threshold in [0, 8] {
+            let mut context = create_context();
+            context.set_unroll_threshold(unroll_threshold);
+            let array_pointer = context.allocate_register();
+            let value = context.allocate_register();
+            context.array_fill(array_pointer, value, 3);
+            context.array_zero(array_pointer, 3);
+
+            let mut initial_memory = memory_of(&[0, 0, ARRAY_START as u128, 42], ARRAY_START);
+            initial_memory.extend([1_u128, 2, 3, 4].map(Value::from));
+            let memory = execute(context.artifact(), initial_memory);
+
+            assert_eq!(memory[ARRAY_START..ARRAY_START + 4], [0_u128, 0, 0, 4].map(Value::from));
+        }
+    }
+
+    #[test]
+    fn reverses_arrays_in_place() {
+        const ARRAY_START: usize = 100;
+
+        let reverse = |array: &[u128]| {
+            let mut context = create_context();
+            let array_pointer = context.allocate_register();
+            let num_elements = context.allocate_register();
+            context.array_reverse(array_pointer, num_elements);
+
+            let mut initial_memory =
+                memory_of(&[0, 0, ARRAY_START as u128, array.len() as u128], ARRAY_START);
+            initial_memory.extend(array.iter().copied().map(Value::from));
+            initial_memory.push(Value::from(42_u128));
+            let memory = execute(context.artifact(), initial_memory);
+
+            // The element past `num_elements` is left untouched.
+            assert_eq!(memory[ARRAY_START + array.len()], Value::from(42_u128));
+            memory[ARRAY_START..ARRAY_START + array.len()].to_vec()
+        };
+
+        assert_eq!(reverse(&[1, 2, 3, 4]), [4_u128, 3, 2, 1].map(Value::from));
+        // The middle element of odd length arrays stays in place.
+        assert_eq!(reverse(&[1, 2, 3, 4, 5]), [5_u128, 4, 3, 2, 1].map(Value::from));
+        assert_eq!(reverse(&[7]), [Value::from(7_u128)]);
+        assert!(reverse(&[]).is_empty());
+    }
+
+    #[test]
+    fn computes_dot_products() {
+        const LHS_START: usize = 100;
+        const RHS_START: usize = 103;
+
+        for bit_size in [FieldElement::max_num_bits(), 32] {
+            for unroll_threshold in [0, 8] {
+                let mut context = create_context();
+                context.set_unroll_threshold(unroll_threshold);
+                let lhs_pointer = context.allocate_register();
+                let rhs_pointer = context.allocate_register();
+                let result = context.allocate_register();
+                context.array_dot(lhs_pointer, rhs_pointer, 3, result, bit_size);
+
+                // The result register starts with a value which must not leak into the sum.
+                let mut initial_memory =
+                    memory_of(&[0, 0, LHS_START as u128, RHS_START as u128, 99], LHS_START);
+                initial_memory.extend([1_u128, 2, 3, 4, 5, 6].map(Value::from));
+                let memory = execute(context.artifact(), initial_memory);
+
+                assert_eq!(memory[result.to_usize()], Value::from(32_u128));
+            }
+        }
+    }
+
+    #[test]
+    fn reduces_arrays_in_memory() {
+        const ARRAY_START: usize = 100;
+
+        let mut context = create_context();
+        let array_pointer = context.allocate_register();
+        let num_elements = context.allocate_register();
+        let accumulator = context.allocate_register();
+        context.array_reduce(
+            array_pointer,
+            num_elements,
+            accumulator,
+            accumulator,
+            BrilligBinaryOp::Integer { op: BinaryIntOp::Add, bit_size: 32 },
+        );
+
+        let mut initial_memory = memory_of(&[0, 0, ARRAY_START as u128, 3, 10], ARRAY_START);
+        initial_memory.extend([5_u128, 6, 7, 100].map(Value::from));
+        let memory = execute(context.artifact(), initial_memory);
+
+        // Only the first `num_elements` elements are added to the initial value of the accumulator.
+        assert_eq!(memory[accumulator.to_usize()], Value::from(10_u128 + 5 + 6 + 7));
+    }
+
+    #[test]
+    fn reduces_arrays_from_an_initial_value() {
+        const ARRAY_START: usize = 100;
+
+        let reduce = |operation| {
+            let mut context = create_context();
+            let array_pointer = context.allocate_register();
+            let num_elements = context.allocate_register();
+            let accumulator = context.allocate_register();
+            let initial = context.allocate_register();
+            context.array_reduce(array_pointer, num_elements, accumulator, initial, operation);
+
+            // The accumulator starts with a value which must be overwritten by the initial one.
+            let mut initial_memory =
+                memory_of(&[0, 0, ARRAY_START as u128, 3, 99, 10], ARRAY_START);
+            initial_memory.extend([1_u128, 2, 3].map(Value::from));
+            execute(context.artifact(), initial_memory)[accumulator.to_usize()]
+        };
+
+        assert_eq!(
+            reduce(BrilligBinaryOp::Integer { op: BinaryIntOp::Add, bit_size: 32 }),
+            Value::from(16_u128)
+        );
+        // Elements are folded from first to last, on the right of the accumulator.
+        assert_eq!(
+            reduce(BrilligBinaryOp::Integer { op: BinaryIntOp::Sub, bit_size: 32 }),
+            Value::from(((10_u128 - 1) - 2) - 3)
+        );
+    }
+
+    #[test]
+    fn applies_binary_operations_to_arrays_in_memory() {
+        const LHS_START: usize = 100;
+        const RHS_START: usize = 110;
+        const RESULT_START: usize = 120;
+
+        let mut context = create_context();
+        let lhs_pointer = context.allocate_register();
+        let rhs_pointer = context.allocate_register();
+        let result_pointer = context.allocate_register();
+        let num_elements = context.allocate_register();
+        context.arrays_binary_instruction(
+            lhs_pointer,
+            rhs_pointer,
+            result_pointer,
+            num_elements,
+            BrilligBinaryOp::Integer { op: BinaryIntOp::Sub, bit_size: 8 },
+        );
+
+        let registers = [0, 0, LHS_START, RHS_START, RESULT_START, 4].map(|value| value as u128);
+        let mut initial_memory = memory_of(&registers, LHS_START);
+        initial_memory.extend(memory_of(&[9, 8, 7, 6], RHS_START - LHS_START));
+        initial_memory.extend(memory_of(&[1, 2, 3, 7], RESULT_START - RHS_START));
+        let memory = execute(context.artifact(), initial_memory);
+
+        let expected = [8_u128, 6, 4, 255].map(Value::from);
+        assert_eq!(memory[RESULT_START..(RESULT_START + 4)], expected);
+        // The operands are left untouched.
+        assert_eq!(memory[LHS_START..(LHS_START + 4)], [9_u128, 8, 7, 6].map(Value::from));
+    }
+
+    fn count(array: &[u128], needle: u128, op: BinaryIntOp) -> Value {
+        let num_elements = array.len();
+        let calldata =
+            std::iter::once(needle).chain(array.iter().copied()).map(Value::from).collect();
+        let arguments = vec![
+            BrilligParameter::SingleAddr(8),
+            BrilligParameter::Array(vec![BrilligParameter::SingleAddr(8)], num_elements),
+        ];
+        let returns = vec![BrilligParameter::SingleAddr(32)];
+
+        let mut context = create_context();
+        let needle = context.allocate_register();
+        let array_pointer = context.allocate_register();
+        let _array_rc = context.allocate_register();
+
+        let result = context.allocate_register();
+        let num_elements_register = context.make_usize_constant(num_elements.into());
+        context.array_count(
+            array_pointer,
+            num_elements_register,
+            needle,
+            BrilligBinaryOp::Integer { op, bit_size: 8 },
+            result,
+        );
+        context.return_instruction(&[result]);
+
+        let bytecode = create_entry_point_bytecode(context, arguments, returns).byte_code;
+        let (vm, return_data_offset, return_data_size) = create_and_run_vm(calldata, &bytecode);
+        assert_eq!(return_data_size, 1);
+        vm.get_memory()[return_data_offset]
+    }
+
+    #[test]
+    fn counts_occurrences_of_a_value() {
+        let array = [4, 1, 4, 4, 2];
+        assert_eq!(count(&array, 4, BinaryIntOp::Equals), Value::from(3_usize));
+        assert_eq!(count(&array, 3, BinaryIntOp::Equals), Value::from(0_usize));
+        assert_eq!(count(&[], 4, BinaryIntOp::Equals), Value::from(0_usize));
+    }
+
+    #[test]
+    fn counts_elements_greater_than_a_threshold() {
+        // `threshold < element`
+        assert_eq!(count(&[4, 1, 9, 2, 5], 3, BinaryIntOp::LessThan), Value::from(3_usize));
+    }
+
+    fn argmax(array: Vec<Value>, signed: bool) -> Value {
+        let num_elements = array.len();
+        let arguments =
+            vec![BrilligParameter::Array(vec![BrilligParameter::SingleAddr(8)], num_elements)];
+        let returns = vec![BrilligParameter::SingleAddr(32)];
+
+        let mut context = create_context();
+        let array_pointer = context.allocate_register();
+        let _array_rc = context.allocate_register();
+
+        let result_index = context.allocate_register();
+        let num_elements_register = context.make_usize_constant(num_elements.into());
+        context.array_argmax(array_pointer, num_elements_register, result_index, signed, 8);
+        context.return_instruction(&[result_index]);
+
+        let bytecode = create_entry_point_bytecode(context, arguments, returns).byte_code;
+        let (vm, return_data_offset, return_data_size) = create_and_run_vm(array, &bytecode);
+        assert_eq!(return_data_size, 1);
+        vm.get_memory()[return_data_offset]
+    }
+
+    #[test]
+    fn finds_first_index_of_maximum() {
+        let array: Vec<Value> = [3_u128, 7, 2, 7].into_iter().map(Value::from).collect();
+        assert_eq!(argmax(array, false), Value::from(1_usize));
+    }
+
+    #[test]
+    fn finds_index_of_signed_maximum() {
+        // -1, 2, -5 as 8-bit two's complement integers.
+        let array: Vec<Value> = [0xFF_u128, 2, 0xFB].into_iter().map(Value::from).collect();
+        assert_eq!(argmax(array.clone(), true), Value::from(1_usize));
+        assert_eq!(argmax(array, false), Value::from(0_usize));
+    }
+
+    fn copy_within(
+        array: Vec<u128>,
+        source_index: usize,
+        destination_index: usize,
+        count: usize,
+    ) -> Vec<Value> {
+        let num_elements = array.len();
+        let array_values: Vec<Value> = array.into_iter().map(Value::from).collect();
+
+        let arguments =
+            vec![BrilligParameter::Array(vec![BrilligParameter::SingleAddr(32)], num_elements)];
+        let returns =
+            vec![BrilligParameter::Array(vec![BrilligParameter::SingleAddr(32)], num_elements)];
+
+        let mut context = create_context();
+        let array = BrilligArray {
+            pointer: context.allocate_register(),
+            size: num_elements,
+            rc: context.allocate_register(),
+        };
+
+        let source_index = context.make_usize_constant(source_index.into());
+        let destination_index = context.make_usize_constant(destination_index.into());
+        let count = context.make_usize_constant(count.into());
+        context.copy_within(array.pointer, source_index, destination_index, count);
+        context.return_instruction(&array.extract_registers());
+
+        let bytecode = create_entry_point_bytecode(context, arguments, returns).byte_code;
+        let (vm, return_data_offset, return_data_size) = create_and_run_vm(array_values, &bytecode);
+        assert_eq!(return_data_size, num_elements);
+        vm.get_memory()[return_data_offset..(return_data_offset + num_elements)].to_vec()
+    }
+
+    #[test]
+    fn copies_within_array_towards_the_start() {
+        // Removing the element at index 1 by shifting the following elements left by one
+        let expected: Vec<Value> = [1_u128, 3, 4, 5, 5].into_iter().map(Value::from).collect();
+        assert_eq!(copy_within(vec![1, 2, 3, 4, 5], 2, 1, 3), expected);
+    }
+
+    /// Copies `count` values of `array` from `source_index` to `destination_index` through `array_copy`,
+    /// returning the array along with a copy of its first `count` values made beforehand.
+    fn array_copy(
+        array: Vec<u128>,
+        source_index: usize,
+        destination_index: usize,
+        count: usize,
+    ) -> (Vec<Value>, Vec<Value>) {
+        let num_elements = array.len();
+        let array_values: Vec<Value> = array.into_iter().map(Value::from).collect();
+
+        let arguments =
+            vec![BrilligParameter::Array(vec![BrilligParameter::SingleAddr(32)], num_elements)];
+        let returns = vec![
+            BrilligParameter::Array(vec![BrilligParameter::SingleAddr(32)], num_elements),
+            BrilligParameter::Array(vec![BrilligParameter::SingleAddr(32)], count),
+        ];
+
+        let mut context = create_context();
+        let array = BrilligArray {
+            pointer: context.allocate_register(),
+            size: num_elements,
+            rc: context.allocate_register(),
+        };
+        let prefix = BrilligArray {
+            pointer: context.allocate_register(),
+            size: count,
+            rc: context.make_usize_constant(1_usize.into()),
+        };
+        context.allocate_fixed_length_array(prefix.pointer, count);
+        context.array_copy(array.pointer, prefix.pointer, count);
+
+        let source_pointer = context.allocate_register();
+        let destination_pointer = context.allocate_register();
+        context.usize_op(array.pointer, source_pointer, BinaryIntOp::Add, source_index);
+        context.usize_op(array.pointer, destination_pointer, BinaryIntOp::Add, destination_index);
+        context.array_copy(source_pointer, destination_pointer, count);
+        context
+            .return_instruction(&[array.extract_registers(), prefix.extract_registers()].concat());
+
+        let bytecode = create_entry_point_bytecode(context, arguments, returns).byte_code;
+        let (vm, return_data_offset, return_data_size) = create_and_run_vm(array_values, &bytecode);
+        assert_eq!(return_data_size, num_elements + count);
+        let memory = &vm.get_memory()[return_data_offset..(return_data_offset + return_data_size)];
+        (memory[..num_elements].to_vec(), memory[num_elements..].to_vec())
+    }
+
+    #[test]
+    fn copies_between_separate_arrays() {
+        let (array, prefix) = array_copy(vec![1, 2, 3, 4, 5], 0, 0, 3);
+        let expected: Vec<Value> = [1_u128, 2, 3].into_iter().map(Value::from).collect();
+        assert_eq!(prefix, expected);
+        let unchanged: Vec<Value> = (1_u128..=5).map(Value::from).collect();
+        assert_eq!(array, unchanged);
+    }
+
+    #[test]
+    fn copies_overlapping_ranges() {
+        let (forward, _) = array_copy(vec![1, 2, 3, 4, 5, 6], 2, 0, 4);
+        let expected: Vec<Value> = [3_u128, 4, 5, 6, 5, 6].into_iter().map(Value::from).collect();
+        assert_eq!(forward, expected);
+
+        let (backward, _) = array_copy(vec![1, 2, 3, 4, 5, 6], 0, 2, 4);
+        let expected: Vec<Value> = [1_u128, 2, 1, 2, 3, 4].into_iter().map(Value::from).collect();
+        assert_eq!(backward, expected);
+    }
+
+    #[test]
+    fn copies_within_array_towards_the_end() {
+        let expected: Vec<Value> = [1_u128, 1, 2, 3, 5].into_iter().map(Value::from).collect();
+        assert_eq!(copy_within(vec![1, 2, 3, 4, 5], 0, 1, 3), expected);
+    }
+
+    fn store_if(condition: bool) -> Value {
+        let calldata = vec![Value::from(7_u128), Value::from(condition)];
+        let arguments = vec![
+            BrilligParameter::Array(vec![BrilligParameter::SingleAddr(32)], 1),
+            BrilligParameter::SingleAddr(1),
+        ];
+        let returns = vec![BrilligParameter::Array(vec![BrilligParameter::SingleAddr(32)], 1)];
+
+        let mut context = create_context();
+        let cell = BrilligArray {
+            pointer: context.allocate_register(),
+            size: 1,
+            rc: context.allocate_register(),
+        };
+        let condition = context.allocate_register();
+
+        let value = context.make_usize_constant(42_usize.into());
+        context.store_if(condition, cell.pointer, value);
+        context.return_instruction(&cell.extract_registers());
+
+        let bytecode = create_entry_point_bytecode(context, arguments, returns).byte_code;
+        let (vm, return_data_offset, return_data_size) = create_and_run_vm(calldata, &bytecode);
+        assert_eq!(return_data_size, 1);
+        vm.get_memory()[return_data_offset]
+    }
+
+    #[test]
+    fn stores_value_when_condition_is_true() {
+        assert_eq!(store_if(true), Value::from(42_usize));
+    }
+
+    #[test]
+    fn keeps_stored_value_when_condition_is_false() {
+        assert_eq!(store_if(false), Value::from(7_usize));
+    }
+
+    #[test]
+    fn materializes_constant_arrays() {
+        let returns = vec![BrilligParameter::SingleAddr(32); 3];
+
+        let mut context = create_context();
+        let values: Vec<Value> = [1_u128, 2, 3].into_iter().map(Value::from).collect();
+        let array_pointer = context.const_array(&values, 32);
+
+        let elements: Vec<_> = (0..3_usize)
+            .map(|index| {
+                let index = context.make_usize_constant(index.into());
+                let element = context.allocate_register();
+                context.array_get(array_pointer, index, element);
+                element
+            })
+            .collect();
+        context.return_instruction(&elements);
+
+        let bytecode = create_entry_point_bytecode(context, vec![], returns).byte_code;
+        let (vm, return_data_offset, return_data_size) = create_and_run_vm(vec![], &bytecode);
+        assert_eq!(return_data_size, 3);
+        assert_eq!(vm.get_memory()[return_data_offset..(return_data_offset + 3)], values);
+    }
+
+    #[test]
+    fn materializes_repeated_constants_in_a_loop() {
+        let values: Vec<Value> = [7_u128, 7, 7, 7, 9].into_iter().map(Value::from).collect();
+        let num_elements = values.len();
+        let returns =
+            vec![BrilligParameter::Array(vec![BrilligParameter::SingleAddr(32)], num_elements)];
+
+        let mut context = create_context();
+        let array = BrilligArray {
+            pointer: context.const_array(&values, 32),
+            size: num_elements,
+            rc: context.make_usize_constant(1_usize.into()),
+        };
+        context.return_instruction(&array.extract_registers());
+
+        let bytecode = create_entry_point_bytecode(context, vec![], returns).byte_code;
+        assert!(bytecode.iter().any(|opcode| matches!(opcode, BrilligOpcode::JumpIf { .. })));
+
+        let (vm, return_data_offset, return_data_size) = create_and_run_vm(vec![], &bytecode);
+        assert_eq!(return_data_size, num_elements);
+        assert_eq!(
+            vm.get_memory()[return_data_offset..(return_data_offset + num_elements)],
+            values
+        );
+    }
+
+    fn rotate(array: Vec<u128>, k: usize, direction: RotationDirection) -> Vec<Value> {
+        let num_elements = array.len();
+        let array_values: Vec<Value> = array.into_iter().map(Value::from).collect();
+
+        let arguments =
+            vec![BrilligParameter::Array(vec![BrilligParameter::SingleAddr(32)], num_elements)];
+        let returns =
+            vec![BrilligParameter::Array(vec![BrilligParameter::SingleAddr(32)], num_elements)];
+
+        let mut context = create_context();
+        let array = BrilligArray {
+            pointer: context.allocate_register(),
+            size: num_elements,
+            rc: context.allocate_register(),
+        };
+
+        let num_elements_register = context.make_usize_constant(num_elements.into());
+        let k = context.make_usize_constant(k.into());
+        context.array_rotate(array.pointer, num_elements_register, k, direction);
+        context.return_instruction(&array.extract_registers());
+
+        let bytecode = create_entry_point_bytecode(context, arguments, returns).byte_code;
+        let (vm, return_data_offset, return_data_size) = create_and_run_vm(array_values, &bytecode);
+        assert_eq!(return_data_size, num_elements);
+
+        vm.get_memory()[return_data_offset..(return_data_offset + num_elements)].to_vec()
+    }
+
+    #[test]
+    fn rotates_arrays() {
+        let to_values =
+            |array: [u128; 5]| -> Vec<Value> { array.into_iter().map(Value::from).collect() };
+
+        assert_eq!(
+            rotate(vec![1, 2, 3, 4, 5], 2, RotationDirection::Left),
+            to_values([3, 4, 5, 1, 2])
+        );
+        assert_eq!(
+            rotate(vec![1, 2, 3, 4, 5], 2, RotationDirection::Right),
+            to_values([4, 5, 1, 2, 3])
+        );
+        assert_eq!(
+            rotate(vec![1, 2, 3, 4, 5], 0, RotationDirection::Left),
+            to_values([1, 2, 3, 4, 5])
+        );
+        assert_eq!(
+            rotate(vec![1, 2, 3, 4, 5], 5, RotationDirection::Right),
+            to_values([1, 2, 3, 4, 5])
+        );
+    }
+
+    #[test]
+    fn rotates_by_more_than_the_array_length() {
+        let expected: Vec<Value> = [3_u128, 4, 5, 1, 2].into_iter().map(Value::from).collect();
+
+        assert_eq!(rotate(vec![1, 2, 3, 4, 5], 7, RotationDirection::Left), expected);
+        assert_eq!(rotate(vec![1, 2, 3, 4, 5], 8, RotationDirection::Right), expected);
+    }
+
+    fn map(array: Vec<Value>, operation: UnaryOp, unroll_threshold: usize) -> Vec<Value> {
+        let num_elements = array.len();
+
+        let arguments =
+            vec![BrilligParameter::Array(vec![BrilligParameter::SingleAddr(32)], num_elements)];
+        let returns =
+            vec![BrilligParameter::Array(vec![BrilligParameter::SingleAddr(32)], num_elements)];
+
+        let mut context = create_context();
+        context.set_unroll_threshold(unroll_threshold);
+        let source_pointer = context.allocate_register();
+        let _source_rc = context.allocate_register();
+
+        let mapped = BrilligArray {
+            pointer: context.allocate_register(),
+            size: num_elements,
+            rc: context.make_usize_constant(1_usize.into()),
+        };
+        context.allocate_fixed_length_array(mapped.pointer, num_elements);
+        context.array_map(source_pointer, mapped.pointer, num_elements, operation);
+        context.return_instruction(&mapped.extract_registers());
+
+        let bytecode = create_entry_point_bytecode(context, arguments, returns).byte_code;
+        let (vm, return_data_offset, return_data_size) = create_and_run_vm(array, &bytecode);
+        assert_eq!(return_data_size, num_elements);
+
+        vm.get_memory()[return_data_offset..(return_data_offset + num_elements)].to_vec()
+    }
+
+    #[test]
+    fn maps_negation_over_field_elements() {
+        let array: Vec<Value> = (1_u128..=3).map(Value::from).collect();
+        let expected: Vec<Value> =
+            (1_u128..=3).map(|value| Value::from(-FieldElement::from(value))).collect();
+        let negate = UnaryOp::Negate { bit_size: FieldElement::max_num_bits() };
+
+        // Both as a loop and unrolled
+        assert_eq!(map(array.clone(), negate, 0), expected);
+        assert_eq!(map(array, negate, 3), expected);
+    }
+
+    #[test]
+    fn maps_integer_operations() {
+        let to_values =
+            |array: [u128; 3]| -> Vec<Value> { array.into_iter().map(Value::from).collect() };
+
+        // -1, 2 and -128 as 8 bit signed integers
+        let array = to_values([255, 2, 128]);
+        assert_eq!(
+            map(array.clone(), UnaryOp::Negate { bit_size: 8 }, 0),
+            to_values([1, 254, 128])
+        );
+        assert_eq!(map(array.clone(), UnaryOp::Not { bit_size: 8 }, 0), to_values([0, 253, 127]));
+        assert_eq!(map(array, UnaryOp::Abs { bit_size: 8 }, 0), to_values([1, 2, 128]));
+    }
+}
@@ -326,6 +326,16 @@ impl DebugShow {
         debug_println!(self.enable_debug_trace, "  JUMP_TO {}", target_label);
     }
 
+    /// Debug function for call_instruction
+    pub(crate) fn call_instruction(&self, target_label: String) {
+        debug_println!(self.enable_debug_trace, "  CALL_TO {}", target_label);
+    }
+
+    /// Debug function for return_from_call_instruction
+    pub(crate) fn return_from_call_instruction(&self) {
+        debug_println!(self.enable_debug_trace, "  RETURN_FROM_CALL");
+    }
+
     /// Debug function for jump_if_instruction
     pub(crate) fn jump_if_instruction<T: ToString>(
         &self,
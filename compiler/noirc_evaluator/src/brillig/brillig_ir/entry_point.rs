@@ -9,6 +9,7 @@ use acvm::{
     acir::brillig::{MemoryAddress, Opcode as BrilligOpcode},
     FieldElement,
 };
+use std::collections::{BTreeMap, HashSet};
 
 pub(crate) const MAX_STACK_SIZE: usize = 1024;
 
@@ -26,6 +27,12 @@ impl BrilligContext {
             section_label: 0,
             next_section: 1,
             debug_show: DebugShow::new(false),
+            unroll_threshold: 0,
+            checked_truncation: false,
+            cached_constants: BTreeMap::new(),
+            assigned_registers: HashSet::new(),
+            terminated: false,
+            drop_unreachable_opcodes: false,
         };
 
         context.entry_point_instruction(&arguments, &return_parameters);
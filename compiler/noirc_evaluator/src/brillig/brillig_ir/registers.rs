@@ -81,3 +81,22 @@ impl BrilligRegistersContext {
         self.deallocated_registers.push(register_index);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::BrilligRegistersContext;
+
+    #[test]
+    fn reuses_deallocated_registers() {
+        let mut registers = BrilligRegistersContext::new();
+        let first = registers.allocate_register();
+        let second = registers.allocate_register();
+
+        registers.deallocate_register(first);
+        assert_eq!(registers.allocate_register(), first);
+
+        // With the free-list empty again, allocation continues past the latest register.
+        let third = registers.allocate_register();
+        assert_eq!(third.to_usize(), second.to_usize() + 1);
+    }
+}
@@ -2,12 +2,21 @@ pub(crate) mod brillig_gen;
 pub(crate) mod brillig_ir;
 
 use self::{
-    brillig_gen::{brillig_fn::FunctionContext, convert_ssa_function},
+    brillig_gen::{
+        brillig_fn::FunctionContext, convert_ssa_function, memory_profile::allocation_report,
+    },
     brillig_ir::artifact::{BrilligArtifact, Label},
 };
-use crate::ssa::{
-    ir::function::{Function, FunctionId, RuntimeType},
-    ssa_gen::Ssa,
+use crate::{
+    errors::RuntimeError,
+    ssa::{
+        ir::{
+            dfg::CallStack,
+            function::{Function, FunctionId, RuntimeType},
+            instruction::TerminatorInstruction,
+        },
+        ssa_gen::Ssa,
+    },
 };
 use std::collections::{BTreeSet, HashMap};
 
@@ -21,8 +30,15 @@ pub struct Brillig {
 
 impl Brillig {
     /// Compiles a function into brillig and store the compilation artifacts
-    pub(crate) fn compile(&mut self, func: &Function, enable_debug_trace: bool) {
-        let obj = convert_ssa_function(func, enable_debug_trace);
+    pub(crate) fn compile(
+        &mut self,
+        func: &Function,
+        enable_debug_trace: bool,
+        unroll_threshold: usize,
+        checked_truncation: bool,
+    ) {
+        let obj =
+            convert_ssa_function(func, enable_debug_trace, unroll_threshold, checked_truncation);
         self.ssa_function_to_brillig.insert(func.id(), obj);
     }
 
@@ -47,7 +63,19 @@ impl std::ops::Index<FunctionId> for Brillig {
 
 impl Ssa {
     /// Compile to brillig brillig functions and ACIR functions reachable from them
-    pub(crate) fn to_brillig(&self, enable_debug_trace: bool) -> Brillig {
+    ///
+    /// If `fail_on_unassigned_return` is set, an error is returned when a function
+    /// returns a register which it never assigns a value to.
+    /// If `max_opcodes` is set, an error is returned when a function compiles to more than that many opcodes.
+    pub(crate) fn to_brillig(
+        &self,
+        enable_debug_trace: bool,
+        unroll_threshold: usize,
+        checked_truncation: bool,
+        profile_memory: bool,
+        fail_on_unassigned_return: bool,
+        max_opcodes: Option<usize>,
+    ) -> Result<Brillig, RuntimeError> {
         // Collect all the function ids that are reachable from brillig
         // That means all the functions marked as brillig and ACIR functions called by them
         let brillig_reachable_function_ids = self
@@ -59,9 +87,114 @@ impl Ssa {
         let mut brillig = Brillig::default();
         for brillig_function_id in brillig_reachable_function_ids {
             let func = &self.functions[&brillig_function_id];
-            brillig.compile(func, enable_debug_trace);
+            brillig.compile(func, enable_debug_trace, unroll_threshold, checked_truncation);
+            if profile_memory {
+                println!("{}", allocation_report(func, &brillig[brillig_function_id]));
+            }
+            if fail_on_unassigned_return {
+                if let Some(call_stack) = &brillig[brillig_function_id].unassigned_return {
+                    return Err(RuntimeError::UnassignedReturnValue {
+                        name: func.name().to_owned(),
+                        call_stack: call_stack.clone(),
+                    });
+                }
+            }
+            if let Some(max_opcodes) = max_opcodes {
+                let num_opcodes = brillig[brillig_function_id].byte_code.len();
+                if num_opcodes > max_opcodes {
+                    return Err(RuntimeError::TooManyBrilligOpcodes {
+                        name: func.name().to_owned(),
+                        num_opcodes,
+                        max_opcodes,
+                        call_stack: return_call_stack(func),
+                    });
+                }
+            }
+        }
+
+        Ok(brillig)
+    }
+}
+
+/// Returns the call stack of the return of `func`, so that errors about the whole function can point into it.
+fn return_call_stack(func: &Function) -> CallStack {
+    func.reachable_blocks()
+        .into_iter()
+        .find_map(|block| match func.dfg[block].terminator() {
+            Some(TerminatorInstruction::Return { call_stack, .. }) => Some(call_stack.clone()),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::acir::brillig::{BinaryIntOp, Opcode as BrilligOpcode};
+
+    use crate::{
+        errors::RuntimeError,
+        ssa::{
+            function_builder::FunctionBuilder,
+            ir::{function::RuntimeType, instruction::BinaryOp, map::Id, types::Type},
+        },
+    };
+
+    #[test]
+    fn fails_when_a_function_exceeds_the_opcode_budget() {
+        // brillig fn main f0 {
+        //   b0(v0: Field, v1: Field):
+        //     v2 = add v0, v1
+        //     v3 = mul v2, v1
+        //     return v3
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Brillig);
+        let v0 = builder.add_parameter(Type::field());
+        let v1 = builder.add_parameter(Type::field());
+        let v2 = builder.insert_binary(v0, BinaryOp::Add, v1);
+        let v3 = builder.insert_binary(v2, BinaryOp::Mul, v1);
+        builder.terminate_with_return(vec![v3]);
+
+        let ssa = builder.finish();
+        assert!(ssa.to_brillig(false, 0, false, false, false, Some(1000)).is_ok());
+
+        match ssa.to_brillig(false, 0, false, false, false, Some(1)) {
+            Err(error @ RuntimeError::TooManyBrilligOpcodes { .. }) => {
+                assert!(error.to_string().contains("function main"));
+            }
+            _ => panic!("expected the opcode budget to be exceeded"),
         }
+    }
+
+    #[test]
+    fn inserts_range_checks_before_truncations_in_checked_mode() {
+        // brillig fn main f0 {
+        //   b0(v0: u32):
+        //     v1 = truncate v0 to 8 bits, max_bit_size: 32
+        //     return v1
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Brillig);
+        let v0 = builder.add_parameter(Type::unsigned(32));
+        let v1 = builder.insert_truncate(v0, 8, 32);
+        builder.terminate_with_return(vec![v1]);
+        let ssa = builder.finish();
 
-        brillig
+        let range_check_opcodes = |checked_truncation| {
+            let brillig = ssa.to_brillig(false, 0, checked_truncation, false, false, None).unwrap();
+            brillig[main_id]
+                .byte_code
+                .iter()
+                .filter(|opcode| {
+                    matches!(
+                        opcode,
+                        BrilligOpcode::BinaryIntOp { op: BinaryIntOp::LessThanEquals, .. }
+                            | BrilligOpcode::Trap
+                    )
+                })
+                .count()
+        };
+        assert_eq!(range_check_opcodes(false), 0);
+        assert_eq!(range_check_opcodes(true), 2);
     }
 }
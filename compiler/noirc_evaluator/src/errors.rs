@@ -48,6 +48,15 @@ pub enum RuntimeError {
     BigIntModulus { call_stack: CallStack },
     #[error("Slices cannot be returned from an unconstrained runtime to a constrained runtime")]
     UnconstrainedSliceReturnToConstrained { call_stack: CallStack },
+    #[error("Unconstrained function {name} returns a value which is never assigned")]
+    UnassignedReturnValue { name: String, call_stack: CallStack },
+    #[error("Unconstrained function {name} compiles to {num_opcodes} opcodes, more than the maximum of {max_opcodes}")]
+    TooManyBrilligOpcodes {
+        name: String,
+        num_opcodes: usize,
+        max_opcodes: usize,
+        call_stack: CallStack,
+    },
 }
 
 // We avoid showing the actual lhs and rhs since most of the time they are just 0
@@ -113,6 +122,13 @@ pub enum InternalError {
     UndeclaredAcirVar { call_stack: CallStack },
     #[error("ICE: Expected {expected:?}, found {found:?}")]
     Unexpected { expected: String, found: String, call_stack: CallStack },
+    #[error("ICE: {operator} applied to operands of {lhs_bit_size} and {rhs_bit_size} bits")]
+    MismatchedBitSizes {
+        operator: String,
+        lhs_bit_size: u32,
+        rhs_bit_size: u32,
+        call_stack: CallStack,
+    },
 }
 
 impl RuntimeError {
@@ -125,7 +141,8 @@ impl RuntimeError {
                 | InternalError::MissingArg { call_stack, .. }
                 | InternalError::NotAConstant { call_stack, .. }
                 | InternalError::UndeclaredAcirVar { call_stack }
-                | InternalError::Unexpected { call_stack, .. },
+                | InternalError::Unexpected { call_stack, .. }
+                | InternalError::MismatchedBitSizes { call_stack, .. },
             )
             | RuntimeError::FailedConstraint { call_stack, .. }
             | RuntimeError::IndexOutOfBounds { call_stack, .. }
@@ -138,7 +155,9 @@ impl RuntimeError {
             | RuntimeError::UnsupportedIntegerSize { call_stack, .. }
             | RuntimeError::NestedSlice { call_stack, .. }
             | RuntimeError::BigIntModulus { call_stack, .. }
-            | RuntimeError::UnconstrainedSliceReturnToConstrained { call_stack } => call_stack,
+            | RuntimeError::UnconstrainedSliceReturnToConstrained { call_stack }
+            | RuntimeError::UnassignedReturnValue { call_stack, .. }
+            | RuntimeError::TooManyBrilligOpcodes { call_stack, .. } => call_stack,
         }
     }
 }
@@ -174,6 +193,18 @@ impl RuntimeError {
                     location.span,
                 )
             }
+            RuntimeError::TooManyBrilligOpcodes { .. } => {
+                let span = self
+                    .call_stack()
+                    .back()
+                    .map_or(noirc_errors::Span::inclusive(0, 0), |location| location.span);
+
+                Diagnostic::simple_error(
+                    self.to_string(),
+                    "Consider lowering `--brillig-unroll-threshold` or raising `--max-opcodes`".to_string(),
+                    span,
+                )
+            }
             _ => {
                 let message = self.to_string();
                 let location =
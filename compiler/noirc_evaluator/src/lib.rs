@@ -12,3 +12,4 @@ pub mod ssa;
 pub mod brillig;
 
 pub use ssa::create_circuit;
+pub use ssa::ir::loops::LoopReport;
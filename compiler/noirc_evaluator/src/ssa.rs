@@ -25,7 +25,7 @@ use noirc_frontend::{
 };
 use tracing::{span, Level};
 
-use self::{acir_gen::GeneratedAcir, ssa_gen::Ssa};
+use self::{acir_gen::GeneratedAcir, ir::loops::LoopReport, ssa_gen::Ssa};
 
 mod acir_gen;
 pub(super) mod function_builder;
@@ -33,22 +33,43 @@ pub mod ir;
 mod opt;
 pub mod ssa_gen;
 
+/// The SSA passes which only optimize the program and so can be skipped.
+pub const OPTIONAL_PASSES: &[&str] = &[
+    "intern_constants",
+    "fold_constants",
+    "fold_constants_using_constraints",
+    "remove_enable_side_effects",
+    "remove_redundant_truncates",
+    "dead_instruction_elimination",
+    "schedule_instructions",
+];
+
 /// Optimize the given program by converting it into SSA
 /// form and performing optimizations there. When finished,
-/// convert the final SSA into ACIR and return it.
+/// convert the final SSA into ACIR and return it, along with
+/// a summary of the loops left in the final SSA.
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn optimize_into_acir(
     program: Program,
     print_ssa_passes: bool,
     print_brillig_trace: bool,
     force_brillig_output: bool,
-) -> Result<GeneratedAcir, RuntimeError> {
+    brillig_unroll_threshold: usize,
+    brillig_checked_truncation: bool,
+    profile_brillig_memory: bool,
+    print_loop_reports: bool,
+    fail_on_unassigned_return: bool,
+    disabled_passes: &[String],
+    max_brillig_opcodes: Option<usize>,
+) -> Result<(GeneratedAcir, LoopReport), RuntimeError> {
     let abi_distinctness = program.return_distinctness;
 
     let ssa_gen_span = span!(Level::TRACE, "ssa_generation");
     let ssa_gen_span_guard = ssa_gen_span.enter();
-    let ssa = SsaBuilder::new(program, print_ssa_passes, force_brillig_output)?
+    let ssa = SsaBuilder::new(program, print_ssa_passes, force_brillig_output, disabled_passes)?
         .run_pass(Ssa::defunctionalize, "After Defunctionalization:")
         .run_pass(Ssa::inline_functions, "After Inlining:")
+        .run_pass(Ssa::convert_tail_calls, "After Tail Call Conversion:")
         // Run mem2reg with the CFG separated into blocks
         .run_pass(Ssa::mem2reg, "After Mem2Reg:")
         .try_run_pass(Ssa::evaluate_assert_constant, "After Assert Constant:")?
@@ -58,44 +79,112 @@ pub(crate) fn optimize_into_acir(
         .run_pass(Ssa::remove_bit_shifts, "After Removing Bit Shifts:")
         // Run mem2reg once more with the flattened CFG to catch any remaining loads/stores
         .run_pass(Ssa::mem2reg, "After Mem2Reg:")
-        .run_pass(Ssa::fold_constants, "After Constant Folding:")
-        .run_pass(
+        .run_optional_pass("intern_constants", Ssa::intern_constants, "After Constant Interning:")
+        .run_optional_pass("fold_constants", Ssa::fold_constants, "After Constant Folding:")
+        .run_optional_pass(
+            "remove_enable_side_effects",
+            Ssa::remove_enable_side_effects,
+            "After EnableSideEffects removal:",
+        )
+        .run_optional_pass(
+            "fold_constants_using_constraints",
             Ssa::fold_constants_using_constraints,
             "After Constant Folding With Constraint Info:",
         )
-        .run_pass(Ssa::dead_instruction_elimination, "After Dead Instruction Elimination:")
+        // Folding with constraint info may resolve conditions into ones which are already enabled
+        .run_optional_pass(
+            "remove_enable_side_effects",
+            Ssa::remove_enable_side_effects,
+            "After EnableSideEffects removal:",
+        )
+        .run_optional_pass(
+            "remove_redundant_truncates",
+            Ssa::remove_redundant_truncates,
+            "After Redundant Truncate removal:",
+        )
+        .run_optional_pass(
+            "dead_instruction_elimination",
+            Ssa::dead_instruction_elimination,
+            "After Dead Instruction Elimination:",
+        )
+        .run_optional_pass(
+            "schedule_instructions",
+            Ssa::schedule_instructions,
+            "After Instruction Scheduling:",
+        )
         .finish();
 
-    let brillig = ssa.to_brillig(print_brillig_trace);
+    ssa.validate_bit_sizes()?;
+
+    let loops = LoopReport::with_ssa(&ssa);
+    if print_loop_reports {
+        for function in ssa.functions.values() {
+            let report = LoopReport::with_function(function);
+            println!(
+                "{} {}: {} loops, max nesting depth {}",
+                function.name(),
+                function.id(),
+                report.count,
+                report.max_depth
+            );
+        }
+    }
+
+    let brillig = ssa.to_brillig(
+        print_brillig_trace,
+        brillig_unroll_threshold,
+        brillig_checked_truncation,
+        profile_brillig_memory,
+        fail_on_unassigned_return,
+        max_brillig_opcodes,
+    )?;
 
     drop(ssa_gen_span_guard);
 
     let last_array_uses = ssa.find_last_array_uses();
 
-    ssa.into_acir(brillig, abi_distinctness, &last_array_uses)
+    let generated_acir = ssa.into_acir(brillig, abi_distinctness, &last_array_uses)?;
+    Ok((generated_acir, loops))
 }
 
 /// Compiles the [`Program`] into [`ACIR`][acvm::acir::circuit::Circuit].
 ///
 /// The output ACIR is is backend-agnostic and so must go through a transformation pass before usage in proof generation.
-#[allow(clippy::type_complexity)]
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 #[tracing::instrument(level = "trace", skip_all)]
 pub fn create_circuit(
     program: Program,
     enable_ssa_logging: bool,
     enable_brillig_logging: bool,
     force_brillig_output: bool,
-) -> Result<(Circuit, DebugInfo, Vec<Witness>, Vec<Witness>, Vec<SsaReport>), RuntimeError> {
+    brillig_unroll_threshold: usize,
+    brillig_checked_truncation: bool,
+    profile_brillig_memory: bool,
+    print_loop_reports: bool,
+    fail_on_unassigned_return: bool,
+    disabled_passes: &[String],
+    max_brillig_opcodes: Option<usize>,
+) -> Result<
+    (Circuit, DebugInfo, Vec<Witness>, Vec<Witness>, Vec<SsaReport>, LoopReport),
+    RuntimeError,
+> {
     let debug_variables = program.debug_variables.clone();
     let debug_types = program.debug_types.clone();
     let debug_functions = program.debug_functions.clone();
     let func_sig = program.main_function_signature.clone();
     let recursive = program.recursive;
-    let mut generated_acir = optimize_into_acir(
+    let (mut generated_acir, loops) = optimize_into_acir(
         program,
         enable_ssa_logging,
         enable_brillig_logging,
         force_brillig_output,
+        brillig_unroll_threshold,
+        brillig_checked_truncation,
+        profile_brillig_memory,
+        print_loop_reports,
+        fail_on_unassigned_return,
+        disabled_passes,
+        max_brillig_opcodes,
     )?;
     let opcodes = generated_acir.take_opcodes();
     let current_witness_index = generated_acir.current_witness_index().0;
@@ -137,7 +226,7 @@ pub fn create_circuit(
     let (optimized_circuit, transformation_map) = acvm::compiler::optimize(circuit);
     debug_info.update_acir(transformation_map);
 
-    Ok((optimized_circuit, debug_info, input_witnesses, return_witnesses, warnings))
+    Ok((optimized_circuit, debug_info, input_witnesses, return_witnesses, warnings, loops))
 }
 
 // Takes each function argument and partitions the circuit's inputs witnesses according to its visibility.
@@ -178,6 +267,8 @@ fn split_public_and_private_inputs(
 struct SsaBuilder {
     ssa: Ssa,
     print_ssa_passes: bool,
+    /// The names of the [OPTIONAL_PASSES] which should be skipped.
+    disabled_passes: Vec<String>,
 }
 
 impl SsaBuilder {
@@ -185,9 +276,11 @@ impl SsaBuilder {
         program: Program,
         print_ssa_passes: bool,
         force_brillig_runtime: bool,
+        disabled_passes: &[String],
     ) -> Result<SsaBuilder, RuntimeError> {
         let ssa = ssa_gen::generate_ssa(program, force_brillig_runtime)?;
-        Ok(SsaBuilder { print_ssa_passes, ssa }.print("Initial SSA:"))
+        let disabled_passes = disabled_passes.to_vec();
+        Ok(SsaBuilder { print_ssa_passes, ssa, disabled_passes }.print("Initial SSA:"))
     }
 
     fn finish(self) -> Ssa {
@@ -200,6 +293,16 @@ impl SsaBuilder {
         self.print(msg)
     }
 
+    /// The same as `run_pass` but skips the pass if `name` is one of the disabled passes
+    fn run_optional_pass(self, name: &str, pass: fn(Ssa) -> Ssa, msg: &str) -> Self {
+        debug_assert!(OPTIONAL_PASSES.contains(&name), "{name} is not an optional pass");
+        if self.disabled_passes.iter().any(|disabled| disabled == name) {
+            self
+        } else {
+            self.run_pass(pass, msg)
+        }
+    }
+
     /// The same as `run_pass` but for passes that may fail
     fn try_run_pass(
         mut self,
@@ -210,8 +313,23 @@ impl SsaBuilder {
         Ok(self.print(msg))
     }
 
-    fn to_brillig(&self, print_brillig_trace: bool) -> Brillig {
-        self.ssa.to_brillig(print_brillig_trace)
+    fn to_brillig(
+        &self,
+        print_brillig_trace: bool,
+        unroll_threshold: usize,
+        checked_truncation: bool,
+        profile_memory: bool,
+        fail_on_unassigned_return: bool,
+        max_opcodes: Option<usize>,
+    ) -> Result<Brillig, RuntimeError> {
+        self.ssa.to_brillig(
+            print_brillig_trace,
+            unroll_threshold,
+            checked_truncation,
+            profile_memory,
+            fail_on_unassigned_return,
+            max_opcodes,
+        )
     }
 
     fn print(self, msg: &str) -> Self {
@@ -5,6 +5,7 @@ pub(crate) mod dom;
 pub(crate) mod function;
 pub(crate) mod function_inserter;
 pub(crate) mod instruction;
+pub(crate) mod loops;
 pub(crate) mod map;
 pub(crate) mod post_order;
 pub(crate) mod printer;
@@ -105,6 +105,9 @@ impl Binary {
         let lhs_is_one = lhs.map_or(false, |lhs| lhs.is_one());
         let rhs_is_one = rhs.map_or(false, |rhs| rhs.is_one());
 
+        let lhs_is_all_ones = lhs.map_or(false, |lhs| is_all_ones(lhs, &operand_type));
+        let rhs_is_all_ones = rhs.map_or(false, |rhs| is_all_ones(rhs, &operand_type));
+
         match self.operator {
             BinaryOp::Add => {
                 if lhs_is_zero {
@@ -135,6 +138,18 @@ impl Binary {
                 if rhs_is_one {
                     return SimplifyResult::SimplifiedTo(self.lhs);
                 }
+                if operand_type == Type::field() {
+                    // Dividing a field element by a nonzero constant is a multiplication by its inverse,
+                    // which we can compute now rather than at runtime.
+                    if let Some(rhs) = rhs.filter(|rhs| !rhs.is_zero()) {
+                        let inverse = dfg.make_constant(rhs.inverse(), operand_type);
+                        return SimplifyResult::SimplifiedToInstruction(Instruction::binary(
+                            BinaryOp::Mul,
+                            self.lhs,
+                            inverse,
+                        ));
+                    }
+                }
             }
             BinaryOp::Mod => {
                 if rhs_is_one {
@@ -248,6 +263,13 @@ impl Binary {
                 if rhs_is_zero {
                     return SimplifyResult::SimplifiedTo(self.lhs);
                 }
+                // Every bit of the result is set if every bit of either operand is set.
+                if lhs_is_all_ones {
+                    return SimplifyResult::SimplifiedTo(self.lhs);
+                }
+                if rhs_is_all_ones {
+                    return SimplifyResult::SimplifiedTo(self.rhs);
+                }
                 if dfg.resolve(self.lhs) == dfg.resolve(self.rhs) {
                     return SimplifyResult::SimplifiedTo(self.lhs);
                 }
@@ -364,6 +386,20 @@ fn eval_constant_binary_op(
     Some((value, operand_type))
 }
 
+/// Returns whether `constant` has all of the bits of the integer type `typ` set.
+///
+/// Always returns false for fields, as bitwise operations are not defined for them.
+fn is_all_ones(constant: FieldElement, typ: &Type) -> bool {
+    match typ {
+        Type::Numeric(NumericType::Unsigned { bit_size } | NumericType::Signed { bit_size })
+            if *bit_size <= 128 =>
+        {
+            constant.try_into_u128() == Some(u128::MAX >> (128 - bit_size))
+        }
+        _ => false,
+    }
+}
+
 fn truncate(int: u128, bit_size: u32) -> u128 {
     let max = 2u128.pow(bit_size);
     int % max
@@ -422,3 +458,71 @@ impl BinaryOp {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use acvm::FieldElement;
+
+    use crate::ssa::{
+        function_builder::FunctionBuilder,
+        ir::{function::RuntimeType, instruction::BinaryOp, map::Id, types::Type},
+    };
+
+    #[test]
+    fn or_with_zero_is_identity() {
+        let mut builder = FunctionBuilder::new("main".into(), Id::test_new(0), RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::unsigned(8));
+        let zero = builder.numeric_constant(0u128, Type::unsigned(8));
+
+        assert_eq!(builder.insert_binary(v0, BinaryOp::Or, zero), v0);
+        assert_eq!(builder.insert_binary(zero, BinaryOp::Or, v0), v0);
+    }
+
+    #[test]
+    fn or_with_all_ones_is_all_ones() {
+        let mut builder = FunctionBuilder::new("main".into(), Id::test_new(0), RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::unsigned(8));
+        let v1 = builder.add_parameter(Type::signed(16));
+        let u8_all_ones = builder.numeric_constant(u8::MAX as u128, Type::unsigned(8));
+        let i16_all_ones = builder.numeric_constant(u16::MAX as u128, Type::signed(16));
+
+        assert_eq!(builder.insert_binary(v0, BinaryOp::Or, u8_all_ones), u8_all_ones);
+        assert_eq!(builder.insert_binary(u8_all_ones, BinaryOp::Or, v0), u8_all_ones);
+        assert_eq!(builder.insert_binary(v1, BinaryOp::Or, i16_all_ones), i16_all_ones);
+    }
+
+    #[test]
+    fn or_with_all_ones_of_a_narrower_type_is_not_simplified() {
+        let mut builder = FunctionBuilder::new("main".into(), Id::test_new(0), RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::unsigned(16));
+        let u8_all_ones = builder.numeric_constant(u8::MAX as u128, Type::unsigned(16));
+
+        let v1 = builder.insert_binary(v0, BinaryOp::Or, u8_all_ones);
+        assert_ne!(v1, u8_all_ones);
+        assert_ne!(v1, v0);
+    }
+
+    #[test]
+    fn and_with_zero_is_zero() {
+        let mut builder = FunctionBuilder::new("main".into(), Id::test_new(0), RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::unsigned(8));
+        let zero = builder.numeric_constant(0u128, Type::unsigned(8));
+
+        let v1 = builder.insert_binary(v0, BinaryOp::And, zero);
+        let v2 = builder.insert_binary(zero, BinaryOp::And, v0);
+
+        let dfg = &builder.current_function.dfg;
+        assert_eq!(dfg.get_numeric_constant(v1), Some(FieldElement::zero()));
+        assert_eq!(dfg.get_numeric_constant(v2), Some(FieldElement::zero()));
+    }
+
+    #[test]
+    fn xor_with_zero_is_identity() {
+        let mut builder = FunctionBuilder::new("main".into(), Id::test_new(0), RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::unsigned(8));
+        let zero = builder.numeric_constant(0u128, Type::unsigned(8));
+
+        assert_eq!(builder.insert_binary(v0, BinaryOp::Xor, zero), v0);
+        assert_eq!(builder.insert_binary(zero, BinaryOp::Xor, v0), v0);
+    }
+}
@@ -0,0 +1,213 @@
+//! Detection of the natural loops of a function.
+//!
+//! A natural loop is identified by a back-edge `n -> d` of the control flow graph, where the
+//! header block `d` dominates the block `n`. The loop is made of the header along with every
+//! block which can reach `n` without going through the header.
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ssa::ssa_gen::Ssa;
+
+use super::{
+    basic_block::BasicBlockId, cfg::ControlFlowGraph, dom::DominatorTree, function::Function,
+    post_order::PostOrder,
+};
+
+pub(crate) struct Loop {
+    /// The header block of a loop is the block which dominates all the
+    /// other blocks in the loop.
+    pub(crate) header: BasicBlockId,
+
+    /// The start of the back_edge n -> d is the block n at the end of
+    /// the loop that jumps back to the header block d which restarts the loop.
+    pub(crate) back_edge_start: BasicBlockId,
+
+    /// All the blocks contained within the loop, including `header` and `back_edge_start`.
+    pub(crate) blocks: HashSet<BasicBlockId>,
+}
+
+impl Loop {
+    /// Find the loops of a function by finding nodes that dominate any of their predecessors.
+    /// The edge where this happens will be the back-edge of the loop.
+    pub(crate) fn find_all(function: &Function, cfg: &ControlFlowGraph) -> Vec<Loop> {
+        let post_order = PostOrder::with_function(function);
+        let mut dom_tree = DominatorTree::with_cfg_and_post_order(cfg, &post_order);
+
+        let mut loops = vec![];
+
+        for (block, _) in function.dfg.basic_blocks_iter() {
+            // These reachable checks wouldn't be needed if we only iterated over reachable blocks
+            if dom_tree.is_reachable(block) {
+                for predecessor in cfg.predecessors(block) {
+                    if dom_tree.is_reachable(predecessor) && dom_tree.dominates(block, predecessor)
+                    {
+                        // predecessor -> block is the back-edge of a loop
+                        loops.push(Loop::from_back_edge(block, predecessor, cfg));
+                    }
+                }
+            }
+        }
+
+        loops
+    }
+
+    fn from_back_edge(
+        header: BasicBlockId,
+        back_edge_start: BasicBlockId,
+        cfg: &ControlFlowGraph,
+    ) -> Loop {
+        let mut blocks = HashSet::new();
+        blocks.insert(header);
+
+        let mut insert = |block, stack: &mut Vec<BasicBlockId>| {
+            if !blocks.contains(&block) {
+                blocks.insert(block);
+                stack.push(block);
+            }
+        };
+
+        // Starting from the back edge of the loop, each predecessor of this block until
+        // the header is within the loop.
+        let mut stack = vec![];
+        insert(back_edge_start, &mut stack);
+
+        while let Some(block) = stack.pop() {
+            for predecessor in cfg.predecessors(block) {
+                insert(predecessor, &mut stack);
+            }
+        }
+
+        Loop { header, back_edge_start, blocks }
+    }
+
+    /// Returns how many of `loops` this loop is nested in, counting itself.
+    /// A loop which is not contained in any other loop has a depth of 1.
+    pub(crate) fn nesting_depth(&self, loops: &[Loop]) -> usize {
+        // Natural loops with distinct headers are either disjoint or nested,
+        // so containing the header of this loop means containing all of it.
+        let outer_loops = loops
+            .iter()
+            .filter(|other| other.header != self.header && other.blocks.contains(&self.header))
+            .count();
+        outer_loops + 1
+    }
+}
+
+/// A summary of the loops of a function or program, for diagnostics.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoopReport {
+    /// The number of natural loops.
+    pub count: usize,
+    /// The depth of the most deeply nested loop, or 0 if there are no loops.
+    pub max_depth: usize,
+}
+
+impl LoopReport {
+    pub(crate) fn with_function(function: &Function) -> LoopReport {
+        let cfg = ControlFlowGraph::with_function(function);
+        let loops = Loop::find_all(function, &cfg);
+        let max_depth = loops.iter().map(|loop_| loop_.nesting_depth(&loops)).max().unwrap_or(0);
+        LoopReport { count: loops.len(), max_depth }
+    }
+
+    /// Summarizes the loops of every function of `ssa`.
+    pub(crate) fn with_ssa(ssa: &Ssa) -> LoopReport {
+        ssa.functions.values().map(LoopReport::with_function).fold(
+            LoopReport::default(),
+            |total, report| LoopReport {
+                count: total.count + report.count,
+                max_depth: total.max_depth.max(report.max_depth),
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ssa::{
+        function_builder::FunctionBuilder,
+        ir::{function::RuntimeType, instruction::BinaryOp, map::Id, types::Type},
+    };
+
+    use super::LoopReport;
+
+    #[test]
+    fn reports_nested_loops() {
+        // fn main f0 {
+        //   b0():
+        //     jmp b1(Field 0)
+        //   b1(v0: Field):  // header of outer loop
+        //     v1 = lt v0, Field 3
+        //     jmpif v1, then: b2, else: b3
+        //   b2():
+        //     jmp b4(Field 0)
+        //   b4(v2: Field):  // header of inner loop
+        //     v3 = lt v2, Field 4
+        //     jmpif v3, then: b5, else: b6
+        //   b5():
+        //     v4 = add v2, Field 1
+        //     jmp b4(v4)
+        //   b6(): // end of inner loop
+        //     v5 = add v0, Field 1
+        //     jmp b1(v5)
+        //   b3(): // end of outer loop
+        //     return
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Brillig);
+
+        let b1 = builder.insert_block();
+        let b2 = builder.insert_block();
+        let b3 = builder.insert_block();
+        let b4 = builder.insert_block();
+        let b5 = builder.insert_block();
+        let b6 = builder.insert_block();
+
+        let v0 = builder.add_block_parameter(b1, Type::field());
+        let v2 = builder.add_block_parameter(b4, Type::field());
+
+        let zero = builder.field_constant(0u128);
+        let one = builder.field_constant(1u128);
+        let three = builder.field_constant(3u128);
+        let four = builder.field_constant(4u128);
+
+        builder.terminate_with_jmp(b1, vec![zero]);
+
+        builder.switch_to_block(b1);
+        let v1 = builder.insert_binary(v0, BinaryOp::Lt, three);
+        builder.terminate_with_jmpif(v1, b2, b3);
+
+        builder.switch_to_block(b2);
+        builder.terminate_with_jmp(b4, vec![zero]);
+
+        builder.switch_to_block(b3);
+        builder.terminate_with_return(vec![]);
+
+        builder.switch_to_block(b4);
+        let v3 = builder.insert_binary(v2, BinaryOp::Lt, four);
+        builder.terminate_with_jmpif(v3, b5, b6);
+
+        builder.switch_to_block(b5);
+        let v4 = builder.insert_binary(v2, BinaryOp::Add, one);
+        builder.terminate_with_jmp(b4, vec![v4]);
+
+        builder.switch_to_block(b6);
+        let v5 = builder.insert_binary(v0, BinaryOp::Add, one);
+        builder.terminate_with_jmp(b1, vec![v5]);
+
+        let ssa = builder.finish();
+        assert_eq!(LoopReport::with_function(ssa.main()), LoopReport { count: 2, max_depth: 2 });
+        assert_eq!(LoopReport::with_ssa(&ssa), LoopReport { count: 2, max_depth: 2 });
+    }
+
+    #[test]
+    fn reports_no_loops_for_straight_line_code() {
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        builder.terminate_with_return(vec![]);
+
+        let ssa = builder.finish();
+        assert_eq!(LoopReport::with_function(ssa.main()), LoopReport { count: 0, max_depth: 0 });
+    }
+}
@@ -280,6 +280,8 @@ impl Context {
 mod test {
     use std::rc::Rc;
 
+    use acvm::FieldElement;
+
     use crate::ssa::{
         function_builder::FunctionBuilder,
         ir::{
@@ -401,6 +403,76 @@ mod test {
         );
     }
 
+    #[test]
+    fn field_division_by_constant_becomes_multiplication_by_inverse() {
+        // fn main f0 {
+        //   b0(v0: Field, v1: Field):
+        //     v2 = div v0, v1
+        //     return v2
+        // }
+        //
+        // After constructing this IR, we set the value of v1 to 3.
+        let main_id = Id::test_new(0);
+
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::field());
+        let v1 = builder.add_parameter(Type::field());
+        let three = builder.field_constant(3u128);
+
+        let v2 = builder.insert_binary(v0, BinaryOp::Div, v1);
+        builder.terminate_with_return(vec![v2]);
+
+        let mut ssa = builder.finish();
+        let main = ssa.main_mut();
+        main.dfg.set_value_from_id(v1, three);
+
+        // Expected output:
+        //
+        // fn main f0 {
+        //   b0(v0: Field, Field 3: Field):
+        //     v4 = mul v0, Field 1/3
+        //     return v4
+        // }
+        let ssa = ssa.fold_constants();
+        let main = ssa.main();
+
+        let instructions = main.dfg[main.entry_block()].instructions();
+        assert_eq!(instructions.len(), 1);
+        let Instruction::Binary(Binary { lhs, operator: BinaryOp::Mul, rhs }) =
+            main.dfg[instructions[0]]
+        else {
+            panic!("expected the division to become a multiplication");
+        };
+        assert_eq!(lhs, v0);
+        assert_eq!(main.dfg.get_numeric_constant(rhs), Some(FieldElement::from(3u128).inverse()));
+    }
+
+    #[test]
+    fn does_not_invert_division_by_zero() {
+        // fn main f0 {
+        //   b0(v0: Field):
+        //     v1 = div v0, Field 0
+        //     return v1
+        // }
+        let main_id = Id::test_new(0);
+
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::field());
+        let zero = builder.field_constant(0u128);
+        let v1 = builder.insert_binary(v0, BinaryOp::Div, zero);
+        builder.terminate_with_return(vec![v1]);
+
+        let ssa = builder.finish().fold_constants();
+        let main = ssa.main();
+
+        let instructions = main.dfg[main.entry_block()].instructions();
+        assert_eq!(instructions.len(), 1);
+        assert!(matches!(
+            main.dfg[instructions[0]],
+            Instruction::Binary(Binary { operator: BinaryOp::Div, .. })
+        ));
+    }
+
     #[test]
     fn non_redundant_truncation() {
         // fn main f0 {
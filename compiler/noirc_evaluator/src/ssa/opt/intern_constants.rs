@@ -0,0 +1,97 @@
+//! Numeric constants created through the `DataFlowGraph` are interned so each constant has a
+//! single `ValueId`, but a value can still come to hold a constant without going through it,
+//! e.g. when it is replaced in place by a pass. Two arguments holding the same constant then look
+//! different to anything comparing `ValueId`s, such as the deduplication of instructions
+//! in constant folding or Brillig's per-value registers.
+//!
+//! This pass replaces every argument holding a numeric constant with the interned value
+//! for that constant and type.
+use crate::ssa::{
+    ir::{basic_block::BasicBlockId, dfg::DataFlowGraph, function::Function, value::ValueId},
+    ssa_gen::Ssa,
+};
+
+impl Ssa {
+    /// See [`intern_constants`][self] module for more information.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub(crate) fn intern_constants(mut self) -> Ssa {
+        for function in self.functions.values_mut() {
+            intern_constants(function);
+        }
+        self
+    }
+}
+
+fn intern_constants(function: &mut Function) {
+    for block in function.reachable_blocks() {
+        intern_block_constants(&mut function.dfg, block);
+    }
+}
+
+fn intern_block_constants(dfg: &mut DataFlowGraph, block: BasicBlockId) {
+    for instruction_id in dfg[block].instructions().to_vec() {
+        let instruction = dfg[instruction_id].clone();
+        dfg[instruction_id] = instruction.map_values(|value| interned_value(dfg, value));
+    }
+
+    if let Some(terminator) = dfg[block].terminator().cloned() {
+        let terminator = terminator.map_values(|value| interned_value(dfg, value));
+        dfg[block].set_terminator(terminator);
+    }
+}
+
+/// Returns the interned constant for `value` if it holds a numeric constant, or `value` otherwise.
+fn interned_value(dfg: &mut DataFlowGraph, value: ValueId) -> ValueId {
+    match dfg.get_numeric_constant_with_type(value) {
+        Some((constant, typ)) => dfg.make_constant(constant, typ),
+        None => value,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use acvm::FieldElement;
+
+    use crate::ssa::{
+        function_builder::FunctionBuilder,
+        ir::{function::RuntimeType, instruction::Instruction, map::Id, types::Type, value::Value},
+    };
+
+    #[test]
+    fn merges_identical_constants() {
+        // fn main f0 {
+        //   b0(v0: Field, v1: Field):
+        //     constrain v0 == Field 2
+        //     constrain v1 == Field 2
+        //     return
+        // }
+        //
+        // After constructing this IR, we introduce a second `Field 2` value into the second
+        // constraint, as replacing a simplified instruction's result in place does.
+        let main_id = Id::test_new(0);
+
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::field());
+        let v1 = builder.add_parameter(Type::field());
+        let two = builder.field_constant(2u128);
+        builder.insert_constrain(v0, two, None);
+        builder.insert_constrain(v1, two, None);
+        builder.terminate_with_return(vec![]);
+
+        let mut ssa = builder.finish();
+        let main = ssa.main_mut();
+        let instructions = main.dfg[main.entry_block()].instructions().to_vec();
+        assert_eq!(instructions.len(), 2);
+        let duplicate = main.dfg.make_value(Value::NumericConstant {
+            constant: FieldElement::from(2u128),
+            typ: Type::field(),
+        });
+        main.dfg[instructions[1]] = Instruction::Constrain(v1, duplicate, None);
+        assert_ne!(duplicate, two);
+
+        let ssa = ssa.intern_constants();
+        let main = ssa.main();
+        assert_eq!(main.dfg[instructions[0]], Instruction::Constrain(v0, two, None));
+        assert_eq!(main.dfg[instructions[1]], Instruction::Constrain(v1, two, None));
+    }
+}
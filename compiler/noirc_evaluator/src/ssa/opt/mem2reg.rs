@@ -535,6 +535,75 @@ mod tests {
         assert_eq!(ret_val_id, allocate_id);
     }
 
+    #[test]
+    fn forwards_constant_store_past_store_to_distinct_address() {
+        // fn func {
+        //   b0():
+        //     v0 = allocate
+        //     v1 = allocate
+        //     store Field 1 at v0
+        //     store Field 2 at v1
+        //     v2 = load v0
+        //     return v2, v1
+        // }
+
+        let func_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("func".into(), func_id, RuntimeType::Acir);
+        let v0 = builder.insert_allocate(Type::field());
+        let v1 = builder.insert_allocate(Type::field());
+        let one = builder.field_constant(FieldElement::one());
+        let two = builder.field_constant(2u128);
+        builder.insert_store(v0, one);
+        builder.insert_store(v1, two);
+        let v2 = builder.insert_load(v0, Type::field());
+        builder.terminate_with_return(vec![v2, v1]);
+
+        let ssa = builder.finish().mem2reg();
+
+        let func = ssa.main();
+        let block_id = func.entry_block();
+
+        // v0 and v1 are distinct allocations so the store to v1 cannot alias v0.
+        assert_eq!(count_loads(block_id, &func.dfg), 0);
+
+        let ret_val_id = match func.dfg[block_id].terminator().unwrap() {
+            TerminatorInstruction::Return { return_values, .. } => return_values.first().unwrap(),
+            _ => unreachable!(),
+        };
+        assert_eq!(func.dfg[*ret_val_id], func.dfg[one]);
+    }
+
+    #[test]
+    fn does_not_forward_store_past_store_to_unknown_address() {
+        // fn func(v0: &mut Field) {
+        //   b0(v0: &mut Field):
+        //     v1 = allocate
+        //     store Field 1 at v1
+        //     store Field 2 at v0
+        //     v2 = load v1
+        //     return v2
+        // }
+
+        let func_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("func".into(), func_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::Reference(Rc::new(Type::field())));
+        let v1 = builder.insert_allocate(Type::field());
+        let one = builder.field_constant(FieldElement::one());
+        let two = builder.field_constant(2u128);
+        builder.insert_store(v1, one);
+        builder.insert_store(v0, two);
+        let v2 = builder.insert_load(v1, Type::field());
+        builder.terminate_with_return(vec![v2]);
+
+        let ssa = builder.finish().mem2reg();
+
+        let func = ssa.main();
+        let block_id = func.entry_block();
+
+        // v0 may alias v1, so the load cannot be proven to read Field 1.
+        assert_eq!(count_loads(block_id, &func.dfg), 1);
+    }
+
     fn count_stores(block: BasicBlockId, dfg: &DataFlowGraph) -> usize {
         dfg[block]
             .instructions()
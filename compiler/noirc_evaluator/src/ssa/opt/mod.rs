@@ -11,7 +11,13 @@ mod defunctionalize;
 mod die;
 pub(crate) mod flatten_cfg;
 mod inlining;
+mod intern_constants;
 mod mem2reg;
 mod remove_bit_shifts;
+mod remove_enable_side_effects;
+mod remove_redundant_truncates;
+mod schedule_instructions;
 mod simplify_cfg;
+mod tail_calls;
 mod unrolling;
+mod validate_bit_sizes;
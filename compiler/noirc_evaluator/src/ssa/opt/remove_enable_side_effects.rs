@@ -0,0 +1,1307 @@
+//! The goal of the "remove enable side effects" optimization pass is to delay any [Instruction::EnableSideEffects]
+//! instructions such that they cover the minimum number of instructions possible.
+//!
+//! The pass works as follows:
+//! - Insert instructions until an [Instruction::EnableSideEffects] is encountered, save this [InstructionId].
+//! - Continue inserting instructions until either
+//!     - Another [Instruction::EnableSideEffects] is encountered, if so then drop the previous [InstructionId] in favour
+//!       of this one.
+//!     - An [Instruction] with side-effects is encountered, if so then insert the currently saved [Instruction::EnableSideEffects]
+//!       before the [Instruction]. Continue inserting instructions until the next [Instruction::EnableSideEffects] is encountered.
+//!
+//! Pure instructions which do not depend on the results of any instruction after an [Instruction::EnableSideEffects]
+//! are then hoisted above it, as far up the block as their operands allow. This further shrinks the set of
+//! instructions which each [Instruction::EnableSideEffects] covers.
+//!
+//! An [Instruction::EnableSideEffects] which sets the same condition as the one already in effect is a no-op,
+//! so it is removed. Such instructions are commonly left behind when constant folding resolves a condition
+//! into a value which has been enabled already, e.g. `u1 1`.
+//!
+//! A condition which refines an earlier one, e.g. `v2 = and v0, v1` following `v0`, does not make the earlier
+//! [Instruction::EnableSideEffects] redundant: any instruction between the two still responds to exactly the
+//! earlier condition, and would fail or be skipped differently under the refined one. The earlier instruction is
+//! only dropped when nothing between the two responds to it.
+//!
+//! When both successors of a branch start with an [Instruction::EnableSideEffects] of the same condition,
+//! and the branching block is their only predecessor, the instruction is first hoisted to the end of the
+//! branching block so that it is only executed once.
+//!
+//! An [Instruction::EnableSideEffects] still pending at the end of a block which branches is moved into the
+//! start of the successor which needs it, when the other successor and everything it leads to are unaffected
+//! by the side effects condition.
+//!
+//! An [Instruction::EnableSideEffects] still pending at the end of a block from which no instruction responding to
+//! the side effects condition can be reached, e.g. one which returns, is removed.
+//!
+//! Likewise an `enable_side_effects u1 1`, which is inserted as soon as it is seen, is removed again if it is
+//! superseded by another [Instruction::EnableSideEffects] before any instruction responds to it, and an
+//! [Instruction::EnableSideEffects] of the condition which is already pending is dropped as a duplicate.
+//!
+//! The side effects condition in effect at the end of a block carries over into its successor when the block
+//! jumps unconditionally to a successor which has no other predecessor, so an [Instruction::EnableSideEffects]
+//! of that condition is a no-op there too and is removed.
+//!
+//! If no instruction of a function responds to the side effects condition then all of its
+//! [Instruction::EnableSideEffects] are dead, so they are all removed up front.
+//!
+//! Every [Instruction::EnableSideEffects] left by this pass sits directly in front of an instruction which responds
+//! to it, except for an `enable_side_effects u1 1` which restores the default condition and is kept where it was
+//! seen, e.g. before a `return`. Applying the pass a second time leaves the function unchanged.
+use acvm::FieldElement;
+use fxhash::{FxHashMap as HashMap, FxHashSet as HashSet};
+
+use crate::ssa::{
+    ir::{
+        basic_block::BasicBlockId,
+        cfg::ControlFlowGraph,
+        dfg::DataFlowGraph,
+        function::Function,
+        instruction::{BinaryOp, Instruction, InstructionId, Intrinsic, TerminatorInstruction},
+        post_order::PostOrder,
+        value::{Value, ValueId},
+    },
+    ssa_gen::Ssa,
+};
+
+impl Ssa {
+    /// See [`remove_enable_side_effects`][self] module for more information.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub(crate) fn remove_enable_side_effects(mut self) -> Ssa {
+        for function in self.functions.values_mut() {
+            remove_enable_side_effects(function);
+        }
+        self
+    }
+}
+
+fn remove_enable_side_effects(function: &mut Function) {
+    if !has_side_effects(function) {
+        for block in function.reachable_blocks() {
+            let dfg = &function.dfg;
+            let instructions = dfg[block]
+                .instructions()
+                .iter()
+                .copied()
+                .filter(|instruction| {
+                    !matches!(dfg[*instruction], Instruction::EnableSideEffects { .. })
+                })
+                .collect();
+            *function.dfg[block].instructions_mut() = instructions;
+        }
+        return;
+    }
+
+    hoist_shared_leading_enables(function);
+
+    // Blocks are visited in reverse post-order so that the condition in effect at the end of a block
+    // is known by the time its successor is visited.
+    let cfg = ControlFlowGraph::with_function(function);
+    let mut blocks = PostOrder::with_function(function).into_vec();
+    blocks.reverse();
+    let mut entry_conditions: HashMap<BasicBlockId, ValueId> = HashMap::default();
+    for block in blocks {
+        let entry_condition = entry_conditions.get(&block).copied();
+        let exit_condition = remove_enable_side_effects_in_block(function, block, entry_condition);
+        if let (Some(condition), Some(successor)) =
+            (exit_condition, linear_successor(function, &cfg, block))
+        {
+            entry_conditions.insert(successor, condition);
+        }
+    }
+}
+
+/// Returns the successor of `block` if it is the only one and `block` is its only predecessor,
+/// so that the side effects condition at the end of `block` is still in effect when entering it.
+fn linear_successor(
+    function: &Function,
+    cfg: &ControlFlowGraph,
+    block: BasicBlockId,
+) -> Option<BasicBlockId> {
+    match function.dfg[block].terminator() {
+        Some(TerminatorInstruction::Jmp { destination, .. })
+            if *destination != block && cfg.predecessors(*destination).len() == 1 =>
+        {
+            Some(*destination)
+        }
+        _ => None,
+    }
+}
+
+/// Hoists the [Instruction::EnableSideEffects] which starts both successors of a branch to the end of
+/// the branching block.
+///
+/// Each successor must have the branching block as its sole predecessor, so that no other path into it
+/// relied on the hoisted instruction, and the condition must not be one of the successors' parameters.
+fn hoist_shared_leading_enables(function: &mut Function) {
+    let cfg = ControlFlowGraph::with_function(function);
+    for block in function.reachable_blocks() {
+        let Some(TerminatorInstruction::JmpIf { then_destination, else_destination, .. }) =
+            function.dfg[block].terminator()
+        else {
+            continue;
+        };
+        let successors = [*then_destination, *else_destination];
+        if successors[0] == successors[1] {
+            continue;
+        }
+
+        let leading_condition = |successor: BasicBlockId| {
+            if cfg.predecessors(successor).len() != 1 {
+                return None;
+            }
+            let first_instruction = *function.dfg[successor].instructions().first()?;
+            match function.dfg[first_instruction] {
+                Instruction::EnableSideEffects { condition }
+                    if !function.dfg[successor]
+                        .parameters()
+                        .contains(&function.dfg.resolve(condition)) =>
+                {
+                    Some(condition)
+                }
+                _ => None,
+            }
+        };
+        let (Some(then_condition), Some(else_condition)) =
+            (leading_condition(successors[0]), leading_condition(successors[1]))
+        else {
+            continue;
+        };
+        if !is_same_condition(&function.dfg, then_condition, else_condition) {
+            continue;
+        }
+
+        let hoisted = function.dfg[successors[0]].instructions()[0];
+        for successor in successors {
+            function.dfg[successor].instructions_mut().remove(0);
+        }
+        function.dfg[block].instructions_mut().push(hoisted);
+    }
+}
+
+/// Removes the unnecessary [Instruction::EnableSideEffects] of `block`, in which `entry_condition`, if known,
+/// is the side effects condition in effect on entry.
+///
+/// Returns the side effects condition known to be in effect at the end of the block.
+fn remove_enable_side_effects_in_block(
+    function: &mut Function,
+    block: BasicBlockId,
+    entry_condition: Option<ValueId>,
+) -> Option<ValueId> {
+    let instructions = function.dfg[block].take_instructions();
+
+    let mut last_side_effects_enabled_instruction: Option<InstructionId> = None;
+
+    // The condition of the last `Instruction::EnableSideEffects` inserted into this block,
+    // or the one in effect on entry.
+    let mut active_condition: Option<ValueId> = entry_condition;
+
+    // When the last `Instruction::EnableSideEffects` inserted is an `enable_side_effects u1 1` to which
+    // no instruction has responded yet, the condition which was active before it.
+    let mut unobserved_enable: Option<Option<ValueId>> = None;
+
+    let mut new_instructions = HoistingInstructions::new();
+    for instruction_id in instructions {
+        let instruction = &function.dfg[instruction_id];
+
+        // If we run into another `Instruction::EnableSideEffects` before encountering any
+        // instructions with side effects then we can drop the instruction we're holding and
+        // continue with the new `Instruction::EnableSideEffects`.
+        if let Instruction::EnableSideEffects { condition } = instruction {
+            // If this condition is already in effect then this instruction is a no-op, as is any
+            // `Instruction::EnableSideEffects` we're holding as it would be immediately superseded.
+            if active_condition
+                .map_or(false, |active| is_same_condition(&function.dfg, active, *condition))
+            {
+                last_side_effects_enabled_instruction = None;
+                continue;
+            }
+
+            // An `enable_side_effects u1 1` which is superseded before anything responds to it is a no-op,
+            // so it is removed and the condition before it is in effect again.
+            if let Some(previous_condition) = unobserved_enable.take() {
+                new_instructions.pop_enable_side_effects();
+                active_condition = previous_condition;
+                if active_condition
+                    .map_or(false, |active| is_same_condition(&function.dfg, active, *condition))
+                {
+                    last_side_effects_enabled_instruction = None;
+                    continue;
+                }
+            }
+
+            // If we're seeing an `enable_side_effects u1 1` instruction then we must insert it immediately.
+            // This is because we want to maximize the effect it will have.
+            if function.dfg.get_numeric_constant(*condition).map_or(false, |c| c.is_one()) {
+                new_instructions.push_enable_side_effects(instruction_id);
+                unobserved_enable = Some(active_condition);
+                active_condition = Some(*condition);
+                last_side_effects_enabled_instruction = None;
+                continue;
+            }
+
+            // If the same condition is already pending then this instruction is a duplicate of it.
+            let is_pending = last_side_effects_enabled_instruction.map_or(false, |pending| {
+                matches!(
+                    function.dfg[pending],
+                    Instruction::EnableSideEffects { condition: pending_condition }
+                        if is_same_condition(&function.dfg, pending_condition, *condition)
+                )
+            });
+            if !is_pending {
+                last_side_effects_enabled_instruction = Some(instruction_id);
+            }
+            continue;
+        }
+
+        // If we hit an instruction which is affected by the side effects var then we must insert the
+        // `Instruction::EnableSideEffects` before we insert this new instruction.
+        if responds_to_side_effects_var(&function.dfg, instruction) {
+            unobserved_enable = None;
+            if let Some(enable_side_effect_instruction_id) =
+                last_side_effects_enabled_instruction.take()
+            {
+                new_instructions.push_enable_side_effects(enable_side_effect_instruction_id);
+                if let Instruction::EnableSideEffects { condition } =
+                    function.dfg[enable_side_effect_instruction_id]
+                {
+                    active_condition = Some(condition);
+                }
+            }
+        }
+
+        if can_be_hoisted(&function.dfg, instruction) {
+            new_instructions.hoist(&function.dfg, instruction_id);
+        } else {
+            new_instructions.push(&function.dfg, instruction_id);
+        }
+    }
+
+    // The side effects condition carries over into any successor blocks,
+    // so it can only be dropped if none of the blocks which follow respond to it.
+    if let Some(enable_side_effect_instruction_id) = last_side_effects_enabled_instruction {
+        if let Some(successor) = successor_needing_side_effects(function, block) {
+            function.dfg[successor].instructions_mut().insert(0, enable_side_effect_instruction_id);
+        } else if function.dfg[block]
+            .successors()
+            .any(|successor| reaches_side_effects(function, block, successor))
+        {
+            new_instructions.push_enable_side_effects(enable_side_effect_instruction_id);
+            if let Instruction::EnableSideEffects { condition } =
+                function.dfg[enable_side_effect_instruction_id]
+            {
+                active_condition = Some(condition);
+            }
+        }
+    }
+
+    *function.dfg[block].instructions_mut() = new_instructions.finish();
+    active_condition
+}
+
+/// Returns the successor of `block` into which a pending [Instruction::EnableSideEffects] can be moved.
+///
+/// This is the case for a branch when only one side reaches instructions which respond to the side effects
+/// condition, and `block` is the sole predecessor of that side so that no other path sees the condition.
+fn successor_needing_side_effects(
+    function: &Function,
+    block: BasicBlockId,
+) -> Option<BasicBlockId> {
+    let Some(TerminatorInstruction::JmpIf { then_destination, else_destination, .. }) =
+        function.dfg[block].terminator()
+    else {
+        return None;
+    };
+    if then_destination == else_destination {
+        return None;
+    }
+
+    let cfg = ControlFlowGraph::with_function(function);
+    [(*then_destination, *else_destination), (*else_destination, *then_destination)]
+        .into_iter()
+        .find(|(successor, other)| {
+            cfg.predecessors(*successor).len() == 1
+                && reaches_side_effects(function, block, *successor)
+                && !reaches_side_effects(function, block, *other)
+        })
+        .map(|(successor, _)| successor)
+}
+
+/// Returns whether any block reachable from `start` may contain an instruction which responds
+/// to the side effects condition.
+///
+/// The instructions of `block` are being rewritten, so reaching it back is conservatively treated as such.
+fn reaches_side_effects(function: &Function, block: BasicBlockId, start: BasicBlockId) -> bool {
+    let mut visited = HashSet::default();
+    let mut stack = vec![start];
+    while let Some(current) = stack.pop() {
+        if current == block {
+            return true;
+        }
+        if !visited.insert(current) {
+            continue;
+        }
+        let current = &function.dfg[current];
+        if current.instructions().iter().any(|instruction| {
+            responds_to_side_effects_var(&function.dfg, &function.dfg[*instruction])
+        }) {
+            return true;
+        }
+        stack.extend(current.successors());
+    }
+    false
+}
+
+/// Returns whether any instruction of `function`, other than an [Instruction::EnableSideEffects],
+/// responds to the side effects condition.
+fn has_side_effects(function: &Function) -> bool {
+    function.reachable_blocks().into_iter().any(|block| {
+        function.dfg[block].instructions().iter().any(|instruction| {
+            let instruction = &function.dfg[*instruction];
+            !matches!(instruction, Instruction::EnableSideEffects { .. })
+                && responds_to_side_effects_var(&function.dfg, instruction)
+        })
+    })
+}
+
+/// Returns whether `lhs` and `rhs` are the same side effects condition.
+fn is_same_condition(dfg: &DataFlowGraph, lhs: ValueId, rhs: ValueId) -> bool {
+    if dfg.resolve(lhs) == dfg.resolve(rhs) {
+        return true;
+    }
+    match (dfg.get_numeric_constant(lhs), dfg.get_numeric_constant(rhs)) {
+        (Some(lhs), Some(rhs)) => lhs == rhs,
+        _ => false,
+    }
+}
+
+/// The instructions of a block split into segments, each of which (apart from the first)
+/// starts with an [Instruction::EnableSideEffects].
+struct HoistingInstructions {
+    segments: Vec<Vec<InstructionId>>,
+    /// Maps the results of the instructions pushed so far to the index of their segment.
+    segment_of_value: HashMap<ValueId, usize>,
+}
+
+impl HoistingInstructions {
+    fn new() -> Self {
+        HoistingInstructions { segments: vec![Vec::new()], segment_of_value: HashMap::default() }
+    }
+
+    /// Starts a new segment with the given [Instruction::EnableSideEffects].
+    fn push_enable_side_effects(&mut self, instruction_id: InstructionId) {
+        self.segments.push(vec![instruction_id]);
+    }
+
+    /// Removes the [Instruction::EnableSideEffects] which starts the last segment, merging the rest of
+    /// that segment into the one before it.
+    fn pop_enable_side_effects(&mut self) {
+        let mut segment = self.segments.pop().expect("the first segment is never popped");
+        segment.remove(0);
+        let previous = self.segments.len() - 1;
+        for value_segment in self.segment_of_value.values_mut() {
+            *value_segment = (*value_segment).min(previous);
+        }
+        self.segments[previous].extend(segment);
+    }
+
+    /// Appends an instruction to the last segment.
+    fn push(&mut self, dfg: &DataFlowGraph, instruction_id: InstructionId) {
+        let segment = self.segments.len() - 1;
+        self.push_to_segment(dfg, instruction_id, segment);
+    }
+
+    /// Appends an instruction to the earliest segment which already holds all of its operands.
+    fn hoist(&mut self, dfg: &DataFlowGraph, instruction_id: InstructionId) {
+        let mut segment = 0;
+        dfg[instruction_id].for_each_value(|value| {
+            if let Some(operand_segment) = self.segment_of_value.get(&dfg.resolve(value)) {
+                segment = segment.max(*operand_segment);
+            }
+        });
+        self.push_to_segment(dfg, instruction_id, segment);
+    }
+
+    fn push_to_segment(
+        &mut self,
+        dfg: &DataFlowGraph,
+        instruction_id: InstructionId,
+        segment: usize,
+    ) {
+        self.segments[segment].push(instruction_id);
+        for result in dfg.instruction_results(instruction_id) {
+            self.segment_of_value.insert(*result, segment);
+        }
+    }
+
+    fn finish(self) -> Vec<InstructionId> {
+        self.segments.into_iter().flatten().collect()
+    }
+}
+
+/// Returns whether `instruction` has neither side effects nor any dependency on the side effects condition,
+/// so that it can safely be moved above an [Instruction::EnableSideEffects].
+fn can_be_hoisted(dfg: &DataFlowGraph, instruction: &Instruction) -> bool {
+    use Instruction::*;
+    match instruction {
+        Binary(_) => !responds_to_side_effects_var(dfg, instruction),
+        Cast(_, _) | Not(_) | Truncate { .. } => true,
+        _ => false,
+    }
+}
+
+/// Returns whether the behaviour of `instruction` depends on the current side effects condition.
+fn responds_to_side_effects_var(dfg: &DataFlowGraph, instruction: &Instruction) -> bool {
+    use Instruction::*;
+    match instruction {
+        Binary(binary) => {
+            if matches!(binary.operator, BinaryOp::Div | BinaryOp::Mod) {
+                if let Some(rhs) = dfg.get_numeric_constant(binary.rhs) {
+                    rhs == FieldElement::zero()
+                } else {
+                    true
+                }
+            } else {
+                false
+            }
+        }
+
+        Cast(_, _)
+        | Not(_)
+        | Truncate { .. }
+        | Constrain(..)
+        | RangeCheck { .. }
+        | IncrementRc { .. } => false,
+
+        EnableSideEffects { .. }
+        | ArrayGet { .. }
+        | ArraySet { .. }
+        | Allocate
+        | Store { .. }
+        | Load { .. } => true,
+
+        // Some `Intrinsic`s have side effects so we must check what kind of `Call` this is.
+        Call { func, .. } => match dfg[*func] {
+            Value::Intrinsic(intrinsic) => match intrinsic {
+                Intrinsic::SlicePushBack
+                | Intrinsic::SlicePushFront
+                | Intrinsic::SlicePopBack
+                | Intrinsic::SlicePopFront
+                | Intrinsic::SliceInsert
+                | Intrinsic::SliceRemove => true,
+
+                Intrinsic::ArrayLen
+                | Intrinsic::AssertConstant
+                | Intrinsic::ApplyRangeConstraint
+                | Intrinsic::StrAsBytes
+                | Intrinsic::ToBits(_)
+                | Intrinsic::ToRadix(_)
+                | Intrinsic::BlackBox(_)
+                | Intrinsic::FromField
+                | Intrinsic::AsField => false,
+            },
+
+            // We must assume that functions contain a side effect as we cannot inspect more deeply.
+            Value::Function(_) | Value::ForeignFunction(_) => true,
+
+            _ => false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use acvm::FieldElement;
+
+    use crate::ssa::{
+        function_builder::FunctionBuilder,
+        ir::{
+            function::RuntimeType,
+            instruction::{BinaryOp, Instruction, InstructionId},
+            map::Id,
+            types::Type,
+        },
+        ssa_gen::Ssa,
+    };
+
+    fn main_instructions(ssa: &Ssa) -> Vec<InstructionId> {
+        let main = ssa.main();
+        main.dfg[main.entry_block()].instructions().to_vec()
+    }
+
+    fn assert_idempotent(ssa: Ssa) {
+        let once = ssa.remove_enable_side_effects();
+        let instructions_after_once = main_instructions(&once);
+
+        let twice = once.remove_enable_side_effects();
+        assert_eq!(main_instructions(&twice), instructions_after_once);
+    }
+
+    #[test]
+    fn delays_enable_side_effects_until_a_division() {
+        // fn main f0 {
+        //   b0(v0: u1, v1: Field, v2: Field):
+        //     enable_side_effects v0
+        //     v3 = add v1, v2
+        //     v4 = div v1, v2
+        //     enable_side_effects u1 1
+        //     return v3, v4
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::bool());
+        let v1 = builder.add_parameter(Type::field());
+        let v2 = builder.add_parameter(Type::field());
+        let one = builder.numeric_constant(1u128, Type::bool());
+
+        builder.insert_enable_side_effects_if(v0);
+        let v3 = builder.insert_binary(v1, BinaryOp::Add, v2);
+        let v4 = builder.insert_binary(v1, BinaryOp::Div, v2);
+        builder.insert_enable_side_effects_if(one);
+        builder.terminate_with_return(vec![v3, v4]);
+
+        // Expected output:
+        //
+        // fn main f0 {
+        //   b0(v0: u1, v1: Field, v2: Field):
+        //     v3 = add v1, v2
+        //     enable_side_effects v0
+        //     v4 = div v1, v2
+        //     enable_side_effects u1 1
+        //     return v3, v4
+        // }
+        let ssa = builder.finish().remove_enable_side_effects();
+        let main = ssa.main();
+        let instructions = main.dfg[main.entry_block()].instructions();
+        assert_eq!(instructions.len(), 4);
+
+        assert!(matches!(main.dfg[instructions[0]], Instruction::Binary(_)));
+        assert_eq!(main.dfg[instructions[1]], Instruction::EnableSideEffects { condition: v0 });
+        assert!(matches!(main.dfg[instructions[2]], Instruction::Binary(_)));
+        assert_eq!(main.dfg[instructions[3]], Instruction::EnableSideEffects { condition: one });
+    }
+
+    #[test]
+    fn coalesces_enables_only_when_nothing_responds_in_between() {
+        // fn main f0 {
+        //   b0(v0: u1, v1: u1, v2: Field, v3: Field):
+        //     enable_side_effects v0
+        //     v4 = and v0, v1
+        //     enable_side_effects v4
+        //     v5 = div v2, v3
+        //     enable_side_effects v0
+        //     v6 = div v3, v2
+        //     enable_side_effects v4
+        //     v7 = div v2, v3
+        //     return v5, v6, v7
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::bool());
+        let v1 = builder.add_parameter(Type::bool());
+        let v2 = builder.add_parameter(Type::field());
+        let v3 = builder.add_parameter(Type::field());
+
+        builder.insert_enable_side_effects_if(v0);
+        let v4 = builder.insert_binary(v0, BinaryOp::And, v1);
+        builder.insert_enable_side_effects_if(v4);
+        let v5 = builder.insert_binary(v2, BinaryOp::Div, v3);
+        builder.insert_enable_side_effects_if(v0);
+        let v6 = builder.insert_binary(v3, BinaryOp::Div, v2);
+        builder.insert_enable_side_effects_if(v4);
+        let v7 = builder.insert_binary(v2, BinaryOp::Div, v3);
+        builder.terminate_with_return(vec![v5, v6, v7]);
+
+        // Expected output:
+        //
+        // fn main f0 {
+        //   b0(v0: u1, v1: u1, v2: Field, v3: Field):
+        //     v4 = and v0, v1
+        //     enable_side_effects v4
+        //     v5 = div v2, v3
+        //     enable_side_effects v0
+        //     v6 = div v3, v2
+        //     enable_side_effects v4
+        //     v7 = div v2, v3
+        //     return v5, v6, v7
+        // }
+        //
+        // The first `enable_side_effects v0` is dropped as it is refined before anything responds to it,
+        // but the second one must stay as `v6` is only guarded by `v0`.
+        let ssa = builder.finish().remove_enable_side_effects();
+        let main = ssa.main();
+        let instructions = main.dfg[main.entry_block()].instructions();
+        let enabled_conditions: Vec<_> = instructions
+            .iter()
+            .filter_map(|instruction| match main.dfg[*instruction] {
+                Instruction::EnableSideEffects { condition } => Some(condition),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(instructions.len(), 7);
+        assert_eq!(enabled_conditions, vec![v4, v0, v4]);
+        assert_eq!(main.dfg.instruction_results(instructions[0]), [v4]);
+    }
+
+    #[test]
+    fn removes_all_enables_from_functions_without_side_effects() {
+        // fn main f0 {
+        //   b0(v0: u1, v1: Field, v2: Field):
+        //     enable_side_effects v0
+        //     v3 = add v1, v2
+        //     jmp b1()
+        //   b1():
+        //     v4 = not v0
+        //     enable_side_effects v4
+        //     v5 = mul v3, v1
+        //     enable_side_effects u1 1
+        //     return v5
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::bool());
+        let v1 = builder.add_parameter(Type::field());
+        let v2 = builder.add_parameter(Type::field());
+        let one = builder.numeric_constant(1u128, Type::bool());
+        let b1 = builder.insert_block();
+
+        builder.insert_enable_side_effects_if(v0);
+        let v3 = builder.insert_binary(v1, BinaryOp::Add, v2);
+        builder.terminate_with_jmp(b1, vec![]);
+
+        builder.switch_to_block(b1);
+        let v4 = builder.insert_not(v0);
+        builder.insert_enable_side_effects_if(v4);
+        let v5 = builder.insert_binary(v3, BinaryOp::Mul, v1);
+        builder.insert_enable_side_effects_if(one);
+        builder.terminate_with_return(vec![v5]);
+
+        // Expected output:
+        //
+        // fn main f0 {
+        //   b0(v0: u1, v1: Field, v2: Field):
+        //     v3 = add v1, v2
+        //     jmp b1()
+        //   b1():
+        //     v4 = not v0
+        //     v5 = mul v3, v1
+        //     return v5
+        // }
+        let ssa = builder.finish().remove_enable_side_effects();
+        let main = ssa.main();
+        assert_eq!(main.dfg[main.entry_block()].instructions().len(), 1);
+        assert_eq!(main.dfg[b1].instructions().len(), 2);
+        for block in main.reachable_blocks() {
+            for instruction in main.dfg[block].instructions() {
+                assert!(!matches!(main.dfg[*instruction], Instruction::EnableSideEffects { .. }));
+            }
+        }
+    }
+
+    #[test]
+    fn hoists_independent_pure_instructions_above_enables() {
+        // fn main f0 {
+        //   b0(v0: u1, v1: Field, v2: Field, v3: u1):
+        //     enable_side_effects v0
+        //     v4 = div v1, v2
+        //     v5 = add v1, v2
+        //     enable_side_effects v3
+        //     v6 = div v2, v1
+        //     return v4, v5, v6
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::bool());
+        let v1 = builder.add_parameter(Type::field());
+        let v2 = builder.add_parameter(Type::field());
+        let v3 = builder.add_parameter(Type::bool());
+
+        builder.insert_enable_side_effects_if(v0);
+        let v4 = builder.insert_binary(v1, BinaryOp::Div, v2);
+        let v5 = builder.insert_binary(v1, BinaryOp::Add, v2);
+        builder.insert_enable_side_effects_if(v3);
+        let v6 = builder.insert_binary(v2, BinaryOp::Div, v1);
+        builder.terminate_with_return(vec![v4, v5, v6]);
+
+        // Expected output:
+        //
+        // fn main f0 {
+        //   b0(v0: u1, v1: Field, v2: Field, v3: u1):
+        //     v5 = add v1, v2
+        //     enable_side_effects v0
+        //     v4 = div v1, v2
+        //     enable_side_effects v3
+        //     v6 = div v2, v1
+        //     return v4, v5, v6
+        // }
+        let ssa = builder.finish().remove_enable_side_effects();
+        let main = ssa.main();
+        let instructions = main.dfg[main.entry_block()].instructions();
+        assert_eq!(instructions.len(), 5);
+
+        assert_eq!(main.dfg.instruction_results(instructions[0]), &[v5]);
+        assert_eq!(main.dfg[instructions[1]], Instruction::EnableSideEffects { condition: v0 });
+        assert_eq!(main.dfg.instruction_results(instructions[2]), &[v4]);
+        assert_eq!(main.dfg[instructions[3]], Instruction::EnableSideEffects { condition: v3 });
+        assert_eq!(main.dfg.instruction_results(instructions[4]), &[v6]);
+
+        assert_idempotent(ssa);
+    }
+
+    #[test]
+    fn does_not_hoist_instructions_above_their_operands() {
+        // fn main f0 {
+        //   b0(v0: u1, v1: Field, v2: Field, v3: u1):
+        //     enable_side_effects v0
+        //     v4 = div v1, v2
+        //     v5 = add v4, v2
+        //     v6 = mul v5, v1
+        //     enable_side_effects v3
+        //     v7 = div v6, v1
+        //     return v7
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::bool());
+        let v1 = builder.add_parameter(Type::field());
+        let v2 = builder.add_parameter(Type::field());
+        let v3 = builder.add_parameter(Type::bool());
+
+        builder.insert_enable_side_effects_if(v0);
+        let v4 = builder.insert_binary(v1, BinaryOp::Div, v2);
+        let v5 = builder.insert_binary(v4, BinaryOp::Add, v2);
+        let v6 = builder.insert_binary(v5, BinaryOp::Mul, v1);
+        builder.insert_enable_side_effects_if(v3);
+        let v7 = builder.insert_binary(v6, BinaryOp::Div, v1);
+        builder.terminate_with_return(vec![v7]);
+
+        // `v5` and `v6` depend on the division after the first enable so they must stay where they are.
+        let ssa = builder.finish();
+        let instructions_before = main_instructions(&ssa);
+        let ssa = ssa.remove_enable_side_effects();
+        assert_eq!(main_instructions(&ssa), instructions_before);
+    }
+
+    #[test]
+    fn removes_enables_of_the_active_condition_after_folding() {
+        // fn main f0 {
+        //   b0(v0: u1, v1: Field, v2: Field):
+        //     constrain v0 == u1 1
+        //     enable_side_effects v0
+        //     v3 = div v1, v2
+        //     enable_side_effects u1 1
+        //     v4 = div v2, v1
+        //     return v3, v4
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::bool());
+        let v1 = builder.add_parameter(Type::field());
+        let v2 = builder.add_parameter(Type::field());
+        let one = builder.numeric_constant(1u128, Type::bool());
+
+        builder.insert_constrain(v0, one, None);
+        builder.insert_enable_side_effects_if(v0);
+        let v3 = builder.insert_binary(v1, BinaryOp::Div, v2);
+        builder.insert_enable_side_effects_if(one);
+        let v4 = builder.insert_binary(v2, BinaryOp::Div, v1);
+        builder.terminate_with_return(vec![v3, v4]);
+
+        // Folding resolves `v0` into `u1 1` which leaves the second enable redundant.
+        //
+        // fn main f0 {
+        //   b0(v0: u1, v1: Field, v2: Field):
+        //     constrain v0 == u1 1
+        //     enable_side_effects u1 1
+        //     v3 = div v1, v2
+        //     v4 = div v2, v1
+        //     return v3, v4
+        // }
+        let ssa = builder.finish().fold_constants_using_constraints().remove_enable_side_effects();
+        let main = ssa.main();
+        let instructions = main.dfg[main.entry_block()].instructions();
+        assert_eq!(instructions.len(), 4);
+
+        let enables: Vec<_> = instructions
+            .iter()
+            .filter_map(|instruction| match main.dfg[*instruction] {
+                Instruction::EnableSideEffects { condition } => Some(condition),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(enables.len(), 1);
+        assert_eq!(main.dfg.get_numeric_constant(enables[0]), Some(FieldElement::one()));
+
+        assert_idempotent(ssa);
+    }
+
+    #[test]
+    fn folds_consecutive_enables_of_the_same_condition() {
+        // fn main f0 {
+        //   b0(v0: u1, v1: Field, v2: Field):
+        //     enable_side_effects v0
+        //     v3 = div v1, v2
+        //     enable_side_effects u1 1
+        //     enable_side_effects v0
+        //     v4 = div v2, v1
+        //     enable_side_effects v0
+        //     enable_side_effects v0
+        //     v5 = div v3, v4
+        //     return v5
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::bool());
+        let v1 = builder.add_parameter(Type::field());
+        let v2 = builder.add_parameter(Type::field());
+        let one = builder.numeric_constant(1u128, Type::bool());
+
+        builder.insert_enable_side_effects_if(v0);
+        let v3 = builder.insert_binary(v1, BinaryOp::Div, v2);
+        builder.insert_enable_side_effects_if(one);
+        builder.insert_enable_side_effects_if(v0);
+        let v4 = builder.insert_binary(v2, BinaryOp::Div, v1);
+        builder.insert_enable_side_effects_if(v0);
+        builder.insert_enable_side_effects_if(v0);
+        let v5 = builder.insert_binary(v3, BinaryOp::Div, v4);
+        builder.terminate_with_return(vec![v5]);
+
+        // Expected output:
+        //
+        // fn main f0 {
+        //   b0(v0: u1, v1: Field, v2: Field):
+        //     enable_side_effects v0
+        //     v3 = div v1, v2
+        //     v4 = div v2, v1
+        //     v5 = div v3, v4
+        //     return v5
+        // }
+        let ssa = builder.finish().remove_enable_side_effects();
+        let main = ssa.main();
+        let instructions = main_instructions(&ssa);
+        assert_eq!(instructions.len(), 4);
+        assert_eq!(main.dfg[instructions[0]], Instruction::EnableSideEffects { condition: v0 });
+        for instruction in &instructions[1..] {
+            assert!(matches!(main.dfg[*instruction], Instruction::Binary(_)));
+        }
+        assert_idempotent(ssa);
+    }
+
+    #[test]
+    fn keeps_enables_of_one_which_instructions_respond_to() {
+        // fn main f0 {
+        //   b0(v0: u1, v1: Field, v2: Field):
+        //     enable_side_effects v0
+        //     v3 = div v1, v2
+        //     enable_side_effects u1 1
+        //     v4 = div v2, v1
+        //     enable_side_effects v0
+        //     v5 = div v3, v4
+        //     return v5
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::bool());
+        let v1 = builder.add_parameter(Type::field());
+        let v2 = builder.add_parameter(Type::field());
+        let one = builder.numeric_constant(1u128, Type::bool());
+
+        builder.insert_enable_side_effects_if(v0);
+        let v3 = builder.insert_binary(v1, BinaryOp::Div, v2);
+        builder.insert_enable_side_effects_if(one);
+        let v4 = builder.insert_binary(v2, BinaryOp::Div, v1);
+        builder.insert_enable_side_effects_if(v0);
+        let v5 = builder.insert_binary(v3, BinaryOp::Div, v4);
+        builder.terminate_with_return(vec![v5]);
+
+        let ssa = builder.finish().remove_enable_side_effects();
+        let main = ssa.main();
+        let enabled_conditions: Vec<_> = main_instructions(&ssa)
+            .into_iter()
+            .filter_map(|instruction| match main.dfg[instruction] {
+                Instruction::EnableSideEffects { condition } => Some(condition),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(enabled_conditions, vec![v0, one, v0]);
+    }
+
+    #[test]
+    fn is_idempotent_with_a_single_pending_enable() {
+        // fn main f0 {
+        //   b0(v0: u1, v1: Field, v2: Field):
+        //     enable_side_effects v0
+        //     v3 = add v1, v2
+        //     v4 = div v1, v2
+        //     return v3, v4
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::bool());
+        let v1 = builder.add_parameter(Type::field());
+        let v2 = builder.add_parameter(Type::field());
+
+        builder.insert_enable_side_effects_if(v0);
+        let v3 = builder.insert_binary(v1, BinaryOp::Add, v2);
+        let v4 = builder.insert_binary(v1, BinaryOp::Div, v2);
+        builder.terminate_with_return(vec![v3, v4]);
+
+        assert_idempotent(builder.finish());
+    }
+
+    #[test]
+    fn is_idempotent_with_superseded_enables() {
+        // fn main f0 {
+        //   b0(v0: u1, v1: u1, v2: [Field; 2], v3: u32):
+        //     enable_side_effects v0
+        //     v4 = not v1
+        //     enable_side_effects v1
+        //     v5 = mul v0, v1
+        //     enable_side_effects v5
+        //     v6 = array_get v2, index v3
+        //     enable_side_effects u1 1
+        //     v7 = array_get v2, index v3
+        //     return v4, v6, v7
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::bool());
+        let v1 = builder.add_parameter(Type::bool());
+        let array_type = Type::Array(std::rc::Rc::new(vec![Type::field()]), 2);
+        let v2 = builder.add_parameter(array_type);
+        let v3 = builder.add_parameter(Type::unsigned(32));
+        let one = builder.numeric_constant(1u128, Type::bool());
+
+        builder.insert_enable_side_effects_if(v0);
+        let v4 = builder.insert_not(v1);
+        builder.insert_enable_side_effects_if(v1);
+        let v5 = builder.insert_binary(v0, BinaryOp::Mul, v1);
+        builder.insert_enable_side_effects_if(v5);
+        let v6 = builder.insert_array_get(v2, v3, Type::field());
+        builder.insert_enable_side_effects_if(one);
+        let v7 = builder.insert_array_get(v2, v3, Type::field());
+        builder.terminate_with_return(vec![v4, v6, v7]);
+
+        assert_idempotent(builder.finish());
+    }
+
+    #[test]
+    fn sinks_enable_into_the_branch_with_side_effects() {
+        // brillig fn main f0 {
+        //   b0(v0: u1, v1: u1, v2: Field, v3: Field):
+        //     enable_side_effects v0
+        //     jmpif v1 then: b1, else: b2
+        //   b1():
+        //     v4 = div v2, v3
+        //     return v4
+        //   b2():
+        //     v5 = add v2, v3
+        //     return v5
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Brillig);
+        let v0 = builder.add_parameter(Type::bool());
+        let v1 = builder.add_parameter(Type::bool());
+        let v2 = builder.add_parameter(Type::field());
+        let v3 = builder.add_parameter(Type::field());
+        let b1 = builder.insert_block();
+        let b2 = builder.insert_block();
+
+        builder.insert_enable_side_effects_if(v0);
+        builder.terminate_with_jmpif(v1, b1, b2);
+
+        builder.switch_to_block(b1);
+        let v4 = builder.insert_binary(v2, BinaryOp::Div, v3);
+        builder.terminate_with_return(vec![v4]);
+
+        builder.switch_to_block(b2);
+        let v5 = builder.insert_binary(v2, BinaryOp::Add, v3);
+        builder.terminate_with_return(vec![v5]);
+
+        // Expected output:
+        //
+        // brillig fn main f0 {
+        //   b0(v0: u1, v1: u1, v2: Field, v3: Field):
+        //     jmpif v1 then: b1, else: b2
+        //   b1():
+        //     enable_side_effects v0
+        //     v4 = div v2, v3
+        //     return v4
+        //   b2():
+        //     v5 = add v2, v3
+        //     return v5
+        // }
+        let ssa = builder.finish().remove_enable_side_effects();
+        let main = ssa.main();
+        assert!(main.dfg[main.entry_block()].instructions().is_empty());
+
+        let b1_instructions = main.dfg[b1].instructions();
+        assert_eq!(b1_instructions.len(), 2);
+        assert_eq!(main.dfg[b1_instructions[0]], Instruction::EnableSideEffects { condition: v0 });
+        assert!(matches!(main.dfg[b1_instructions[1]], Instruction::Binary(_)));
+
+        let b2_instructions = main.dfg[b2].instructions();
+        assert_eq!(b2_instructions.len(), 1);
+        assert!(matches!(main.dfg[b2_instructions[0]], Instruction::Binary(_)));
+    }
+
+    #[test]
+    fn removes_trailing_enable_when_successors_do_not_need_it() {
+        // fn main f0 {
+        //   b0(v0: u1, v1: Field, v2: Field):
+        //     v3 = div v1, v2
+        //     enable_side_effects v0
+        //     jmp b1()
+        //   b1():
+        //     v4 = add v1, v2
+        //     return v3, v4
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::bool());
+        let v1 = builder.add_parameter(Type::field());
+        let v2 = builder.add_parameter(Type::field());
+        let b1 = builder.insert_block();
+
+        let v3 = builder.insert_binary(v1, BinaryOp::Div, v2);
+        builder.insert_enable_side_effects_if(v0);
+        builder.terminate_with_jmp(b1, vec![]);
+
+        builder.switch_to_block(b1);
+        let v4 = builder.insert_binary(v1, BinaryOp::Add, v2);
+        builder.terminate_with_return(vec![v3, v4]);
+
+        // Expected output:
+        //
+        // fn main f0 {
+        //   b0(v0: u1, v1: Field, v2: Field):
+        //     v3 = div v1, v2
+        //     jmp b1()
+        //   b1():
+        //     v4 = add v1, v2
+        //     return v3, v4
+        // }
+        let ssa = builder.finish().remove_enable_side_effects();
+        let main = ssa.main();
+        let entry_instructions = main.dfg[main.entry_block()].instructions();
+        assert_eq!(entry_instructions.len(), 1);
+        assert!(matches!(main.dfg[entry_instructions[0]], Instruction::Binary(_)));
+        assert_eq!(main.dfg[b1].instructions().len(), 1);
+    }
+
+    #[test]
+    fn hoists_enables_shared_by_both_successors() {
+        // brillig fn main f0 {
+        //   b0(v0: u1, v1: u1, v2: Field, v3: Field):
+        //     jmpif v0 then: b1, else: b2
+        //   b1():
+        //     enable_side_effects v1
+        //     v4 = div v2, v3
+        //     return v4
+        //   b2():
+        //     enable_side_effects v1
+        //     v5 = div v3, v2
+        //     return v5
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Brillig);
+        let v0 = builder.add_parameter(Type::bool());
+        let v1 = builder.add_parameter(Type::bool());
+        let v2 = builder.add_parameter(Type::field());
+        let v3 = builder.add_parameter(Type::field());
+        let b1 = builder.insert_block();
+        let b2 = builder.insert_block();
+
+        builder.terminate_with_jmpif(v0, b1, b2);
+
+        builder.switch_to_block(b1);
+        builder.insert_enable_side_effects_if(v1);
+        let v4 = builder.insert_binary(v2, BinaryOp::Div, v3);
+        builder.terminate_with_return(vec![v4]);
+
+        builder.switch_to_block(b2);
+        builder.insert_enable_side_effects_if(v1);
+        let v5 = builder.insert_binary(v3, BinaryOp::Div, v2);
+        builder.terminate_with_return(vec![v5]);
+
+        // Expected output:
+        //
+        // brillig fn main f0 {
+        //   b0(v0: u1, v1: u1, v2: Field, v3: Field):
+        //     enable_side_effects v1
+        //     jmpif v0 then: b1, else: b2
+        //   b1():
+        //     v4 = div v2, v3
+        //     return v4
+        //   b2():
+        //     v5 = div v3, v2
+        //     return v5
+        // }
+        let ssa = builder.finish().remove_enable_side_effects();
+        let main = ssa.main();
+        let entry_instructions = main.dfg[main.entry_block()].instructions();
+        assert_eq!(entry_instructions.len(), 1);
+        assert_eq!(
+            main.dfg[entry_instructions[0]],
+            Instruction::EnableSideEffects { condition: v1 }
+        );
+        for block in [b1, b2] {
+            let instructions = main.dfg[block].instructions();
+            assert_eq!(instructions.len(), 1);
+            assert!(matches!(main.dfg[instructions[0]], Instruction::Binary(_)));
+        }
+    }
+
+    #[test]
+    fn removes_enables_of_the_active_condition_across_linear_blocks() {
+        // fn main f0 {
+        //   b0(v0: u1, v1: Field, v2: Field):
+        //     enable_side_effects v0
+        //     v3 = div v1, v2
+        //     jmp b1()
+        //   b1():
+        //     enable_side_effects v0
+        //     v4 = div v2, v1
+        //     jmp b2()
+        //   b2():
+        //     enable_side_effects v0
+        //     v5 = div v3, v4
+        //     return v5
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::bool());
+        let v1 = builder.add_parameter(Type::field());
+        let v2 = builder.add_parameter(Type::field());
+        let b1 = builder.insert_block();
+        let b2 = builder.insert_block();
+
+        builder.insert_enable_side_effects_if(v0);
+        let v3 = builder.insert_binary(v1, BinaryOp::Div, v2);
+        builder.terminate_with_jmp(b1, vec![]);
+
+        builder.switch_to_block(b1);
+        builder.insert_enable_side_effects_if(v0);
+        let v4 = builder.insert_binary(v2, BinaryOp::Div, v1);
+        builder.terminate_with_jmp(b2, vec![]);
+
+        builder.switch_to_block(b2);
+        builder.insert_enable_side_effects_if(v0);
+        let v5 = builder.insert_binary(v3, BinaryOp::Div, v4);
+        builder.terminate_with_return(vec![v5]);
+
+        // Expected output:
+        //
+        // fn main f0 {
+        //   b0(v0: u1, v1: Field, v2: Field):
+        //     enable_side_effects v0
+        //     v3 = div v1, v2
+        //     jmp b1()
+        //   b1():
+        //     v4 = div v2, v1
+        //     jmp b2()
+        //   b2():
+        //     v5 = div v3, v4
+        //     return v5
+        // }
+        let ssa = builder.finish().remove_enable_side_effects();
+        let main = ssa.main();
+        assert_eq!(main_instructions(&ssa).len(), 2);
+        for block in [b1, b2] {
+            let instructions = main.dfg[block].instructions();
+            assert_eq!(instructions.len(), 1);
+            assert!(matches!(main.dfg[instructions[0]], Instruction::Binary(_)));
+        }
+        assert_idempotent(ssa);
+    }
+
+    #[test]
+    fn keeps_enables_in_blocks_with_several_predecessors() {
+        // fn main f0 {
+        //   b0(v0: u1, v1: u1, v2: Field, v3: Field):
+        //     enable_side_effects v0
+        //     v4 = div v2, v3
+        //     jmpif v1 then: b1, else: b2
+        //   b1():
+        //     jmp b3()
+        //   b2():
+        //     jmp b3()
+        //   b3():
+        //     enable_side_effects v0
+        //     v5 = div v3, v2
+        //     return v4, v5
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::bool());
+        let v1 = builder.add_parameter(Type::bool());
+        let v2 = builder.add_parameter(Type::field());
+        let v3 = builder.add_parameter(Type::field());
+        let b1 = builder.insert_block();
+        let b2 = builder.insert_block();
+        let b3 = builder.insert_block();
+
+        builder.insert_enable_side_effects_if(v0);
+        let v4 = builder.insert_binary(v2, BinaryOp::Div, v3);
+        builder.terminate_with_jmpif(v1, b1, b2);
+
+        for block in [b1, b2] {
+            builder.switch_to_block(block);
+            builder.terminate_with_jmp(b3, vec![]);
+        }
+
+        builder.switch_to_block(b3);
+        builder.insert_enable_side_effects_if(v0);
+        let v5 = builder.insert_binary(v3, BinaryOp::Div, v2);
+        builder.terminate_with_return(vec![v4, v5]);
+
+        // The condition is only tracked along unconditional jumps, so the enable in `b3` is kept.
+        let ssa = builder.finish().remove_enable_side_effects();
+        let main = ssa.main();
+        let instructions = main.dfg[b3].instructions();
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(main.dfg[instructions[0]], Instruction::EnableSideEffects { condition: v0 });
+    }
+
+    #[test]
+    fn is_idempotent_across_blocks() {
+        // fn main f0 {
+        //   b0(v0: u1, v1: Field, v2: Field):
+        //     enable_side_effects v0
+        //     v3 = add v1, v2
+        //     jmp b1()
+        //   b1():
+        //     v4 = div v1, v2
+        //     return v3, v4
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::bool());
+        let v1 = builder.add_parameter(Type::field());
+        let v2 = builder.add_parameter(Type::field());
+        let b1 = builder.insert_block();
+
+        builder.insert_enable_side_effects_if(v0);
+        let v3 = builder.insert_binary(v1, BinaryOp::Add, v2);
+        builder.terminate_with_jmp(b1, vec![]);
+
+        builder.switch_to_block(b1);
+        let v4 = builder.insert_binary(v1, BinaryOp::Div, v2);
+        builder.terminate_with_return(vec![v3, v4]);
+
+        let ssa = builder.finish().remove_enable_side_effects();
+
+        // The enable must not be dropped as it still applies to the division in `b1`.
+        let main = ssa.main();
+        let entry_instructions = main.dfg[main.entry_block()].instructions();
+        assert_eq!(entry_instructions.len(), 2);
+        assert_eq!(
+            main.dfg[entry_instructions[1]],
+            Instruction::EnableSideEffects { condition: v0 }
+        );
+
+        let once = main.dfg[b1].instructions().to_vec();
+        let ssa = ssa.remove_enable_side_effects();
+        assert_eq!(main_instructions(&ssa).len(), 2);
+        assert_eq!(ssa.main().dfg[b1].instructions(), once);
+    }
+}
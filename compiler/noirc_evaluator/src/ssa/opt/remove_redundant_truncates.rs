@@ -0,0 +1,112 @@
+//! Instructions are simplified as they are inserted, but passes which later replace values in place
+//! (e.g. the merging of values which happens around `EnableSideEffects` instructions) can leave a
+//! `Truncate` operating directly on the result of another `Truncate` to the same or a smaller bit size.
+//!
+//! This pass re-simplifies each `Truncate` instruction against its current inputs
+//! and removes the ones which have become redundant.
+use crate::ssa::{
+    ir::{
+        function::Function,
+        instruction::{Instruction, SimplifyResult},
+    },
+    ssa_gen::Ssa,
+};
+
+impl Ssa {
+    /// See [`remove_redundant_truncates`][self] module for more information.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub(crate) fn remove_redundant_truncates(mut self) -> Ssa {
+        for function in self.functions.values_mut() {
+            remove_redundant_truncates(function);
+        }
+        self
+    }
+}
+
+fn remove_redundant_truncates(function: &mut Function) {
+    for block in function.reachable_blocks() {
+        let instructions = function.dfg[block].take_instructions();
+        let mut remaining_instructions = Vec::with_capacity(instructions.len());
+
+        for instruction_id in instructions {
+            let instruction = &function.dfg[instruction_id];
+            if matches!(instruction, Instruction::Truncate { .. }) {
+                let instruction = instruction.clone();
+                let call_stack = function.dfg.get_call_stack(instruction_id);
+                if let SimplifyResult::SimplifiedTo(value) =
+                    instruction.simplify(&mut function.dfg, block, None, &call_stack)
+                {
+                    let result = function.dfg.instruction_results(instruction_id)[0];
+                    function.dfg.set_value_from_id(result, value);
+                    continue;
+                }
+            }
+            remaining_instructions.push(instruction_id);
+        }
+
+        *function.dfg[block].instructions_mut() = remaining_instructions;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ssa::{
+        function_builder::FunctionBuilder,
+        ir::{
+            function::RuntimeType,
+            instruction::{Instruction, TerminatorInstruction},
+            map::Id,
+            types::Type,
+        },
+    };
+
+    #[test]
+    fn removes_truncate_of_truncated_value() {
+        // fn main f0 {
+        //   b0(v0: u16, v1: u16):
+        //     v2 = truncate v0 to 8 bits, max_bit_size: 16
+        //     v3 = truncate v1 to 8 bits, max_bit_size: 16
+        //     return v3
+        // }
+        //
+        // After constructing this IR, we replace v1 with v2 as passes which merge values do.
+        // v3 then truncates a value which already fits in 8 bits.
+        let main_id = Id::test_new(0);
+
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::unsigned(16));
+        let v1 = builder.add_parameter(Type::unsigned(16));
+
+        let v2 = builder.insert_truncate(v0, 8, 16);
+        let v3 = builder.insert_truncate(v1, 8, 16);
+        builder.terminate_with_return(vec![v3]);
+
+        let mut ssa = builder.finish();
+        let main = ssa.main_mut();
+        assert_eq!(main.dfg[main.entry_block()].instructions().len(), 2);
+
+        main.dfg.set_value_from_id(v1, v2);
+
+        // Expected output:
+        //
+        // fn main f0 {
+        //   b0(v0: u16, v1: u16):
+        //     v2 = truncate v0 to 8 bits, max_bit_size: 16
+        //     return v2
+        // }
+        let ssa = ssa.remove_redundant_truncates();
+        let main = ssa.main();
+        let block = &main.dfg[main.entry_block()];
+
+        assert_eq!(block.instructions().len(), 1);
+        let instruction = &main.dfg[block.instructions()[0]];
+        assert!(matches!(instruction, Instruction::Truncate { value, .. } if *value == v0));
+
+        match block.terminator() {
+            Some(TerminatorInstruction::Return { return_values, .. }) => {
+                assert_eq!(main.dfg.resolve(return_values[0]), v2);
+            }
+            _ => unreachable!("b0 should have a return terminator"),
+        }
+    }
+}
@@ -0,0 +1,239 @@
+//! Reorders the instructions of each block of the Brillig functions so that values are computed
+//! just before their first use, shortening their live ranges and so the number of registers
+//! which Brillig gen needs to hold them.
+//!
+//! Only instructions which can neither fail nor observe memory are moved. Every other instruction
+//! keeps its position relative to the others, so side effects happen in their original order.
+use std::collections::HashMap;
+
+use crate::ssa::{
+    ir::{
+        basic_block::BasicBlockId,
+        dfg::DataFlowGraph,
+        function::{Function, RuntimeType},
+        instruction::{BinaryOp, Instruction, InstructionId},
+        types::Type,
+        value::ValueId,
+    },
+    ssa_gen::Ssa,
+};
+
+impl Ssa {
+    /// See [`schedule_instructions`][self] module for more information.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub(crate) fn schedule_instructions(mut self) -> Ssa {
+        for function in self.functions.values_mut() {
+            if function.runtime() == RuntimeType::Brillig {
+                schedule_instructions(function);
+            }
+        }
+        self
+    }
+}
+
+fn schedule_instructions(function: &mut Function) {
+    for block in function.reachable_blocks() {
+        let instructions = function.dfg[block].take_instructions();
+        let scheduled = Scheduler::default().schedule_block(&function.dfg, block, instructions);
+        *function.dfg[block].instructions_mut() = scheduled;
+    }
+}
+
+#[derive(Default)]
+struct Scheduler {
+    /// Movable instructions which have not been scheduled yet, by the value they compute.
+    pending: HashMap<ValueId, InstructionId>,
+    scheduled: Vec<InstructionId>,
+}
+
+impl Scheduler {
+    fn schedule_block(
+        mut self,
+        dfg: &DataFlowGraph,
+        block: BasicBlockId,
+        instructions: Vec<InstructionId>,
+    ) -> Vec<InstructionId> {
+        self.scheduled.reserve(instructions.len());
+
+        for instruction in &instructions {
+            if is_movable(dfg, *instruction) {
+                self.pending.insert(dfg.instruction_results(*instruction)[0], *instruction);
+            } else {
+                self.schedule(dfg, *instruction);
+            }
+        }
+
+        // Values used by the terminator or by other blocks are computed at the end of the block.
+        dfg[block].unwrap_terminator().for_each_value(|value| {
+            if let Some(instruction) = self.pending.remove(&dfg.resolve(value)) {
+                self.schedule(dfg, instruction);
+            }
+        });
+        for instruction in instructions {
+            let result = dfg.instruction_results(instruction).first();
+            if let Some(instruction) = result.and_then(|result| self.pending.remove(result)) {
+                self.schedule(dfg, instruction);
+            }
+        }
+
+        self.scheduled
+    }
+
+    /// Schedules `instruction` after any of the pending instructions computing its arguments.
+    fn schedule(&mut self, dfg: &DataFlowGraph, instruction: InstructionId) {
+        let mut stack = vec![(instruction, false)];
+        while let Some((instruction, arguments_scheduled)) = stack.pop() {
+            if arguments_scheduled {
+                self.scheduled.push(instruction);
+                continue;
+            }
+
+            stack.push((instruction, true));
+            dfg[instruction].for_each_value(|value| {
+                if let Some(argument) = self.pending.remove(&dfg.resolve(value)) {
+                    stack.push((argument, false));
+                }
+            });
+        }
+    }
+}
+
+/// Returns whether `instruction` can be moved past any other instruction without changing
+/// the behavior of the program.
+fn is_movable(dfg: &DataFlowGraph, instruction: InstructionId) -> bool {
+    match &dfg[instruction] {
+        Instruction::Cast(..) | Instruction::Not(_) => true,
+        // Truncations are range checked when compiled with `--brillig-checked-truncation`.
+        Instruction::Truncate { .. } => false,
+        Instruction::Binary(binary) => match binary.operator {
+            BinaryOp::Eq | BinaryOp::Lt | BinaryOp::And | BinaryOp::Or | BinaryOp::Xor => true,
+            // Integer arithmetic is checked for overflow in Brillig.
+            BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul => {
+                dfg.type_of_value(binary.lhs) == Type::field()
+            }
+            BinaryOp::Div | BinaryOp::Mod | BinaryOp::Shl | BinaryOp::Shr => false,
+        },
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::ssa::{
+        function_builder::FunctionBuilder,
+        ir::{
+            function::{Function, RuntimeType},
+            instruction::{BinaryOp, Instruction},
+            map::Id,
+            types::Type,
+        },
+    };
+
+    /// Returns the largest number of instruction results of the entry block which are live at once.
+    fn peak_live_values(function: &Function) -> usize {
+        let block = &function.dfg[function.entry_block()];
+        let instructions = block.instructions();
+
+        let mut last_uses = HashMap::new();
+        for (position, instruction) in instructions.iter().enumerate() {
+            function.dfg[*instruction].for_each_value(|value| last_uses.insert(value, position));
+        }
+        block
+            .unwrap_terminator()
+            .for_each_value(|value| last_uses.insert(value, instructions.len()));
+
+        (0..instructions.len())
+            .map(|position| {
+                instructions[..=position]
+                    .iter()
+                    .flat_map(|instruction| function.dfg.instruction_results(*instruction))
+                    .filter(|value| last_uses.get(value).map_or(false, |last| *last > position))
+                    .count()
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn computes_values_next_to_their_uses() {
+        // brillig fn main f0 {
+        //   b0(v0: Field, v1: Field):
+        //     v2 = add v0, v1
+        //     v3 = mul v0, v1
+        //     v4 = sub v0, v1
+        //     constrain v2 == v0
+        //     constrain v3 == v0
+        //     constrain v4 == v0
+        //     return
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Brillig);
+        let v0 = builder.add_parameter(Type::field());
+        let v1 = builder.add_parameter(Type::field());
+        let v2 = builder.insert_binary(v0, BinaryOp::Add, v1);
+        let v3 = builder.insert_binary(v0, BinaryOp::Mul, v1);
+        let v4 = builder.insert_binary(v0, BinaryOp::Sub, v1);
+        for value in [v2, v3, v4] {
+            builder.insert_constrain(value, v0, None);
+        }
+        builder.terminate_with_return(vec![]);
+
+        let ssa = builder.finish();
+        assert_eq!(peak_live_values(ssa.main()), 3);
+
+        // Expected output:
+        //
+        // brillig fn main f0 {
+        //   b0(v0: Field, v1: Field):
+        //     v2 = add v0, v1
+        //     constrain v2 == v0
+        //     v3 = mul v0, v1
+        //     constrain v3 == v0
+        //     v4 = sub v0, v1
+        //     constrain v4 == v0
+        //     return
+        // }
+        let ssa = ssa.schedule_instructions();
+        let main = ssa.main();
+        assert_eq!(peak_live_values(main), 1);
+
+        let instructions = main.dfg[main.entry_block()].instructions();
+        for (pair, value) in instructions.chunks(2).zip([v2, v3, v4]) {
+            assert_eq!(main.dfg.instruction_results(pair[0]), [value]);
+            assert!(matches!(main.dfg[pair[1]], Instruction::Constrain(lhs, ..) if lhs == value));
+        }
+    }
+
+    #[test]
+    fn keeps_side_effects_in_order() {
+        // brillig fn main f0 {
+        //   b0(v0: u32, v1: u32):
+        //     v2 = truncate v0 to 8 bits, max_bit_size: 32
+        //     v3 = div v0, v1
+        //     v4 = add v0, v1
+        //     constrain v4 == v0
+        //     return v3, v2
+        // }
+        //
+        // The truncation, the division and the integer addition may all fail, so none can be moved.
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Brillig);
+        let v0 = builder.add_parameter(Type::unsigned(32));
+        let v1 = builder.add_parameter(Type::unsigned(32));
+        let v2 = builder.insert_truncate(v0, 8, 32);
+        let v3 = builder.insert_binary(v0, BinaryOp::Div, v1);
+        let v4 = builder.insert_binary(v0, BinaryOp::Add, v1);
+        builder.insert_constrain(v4, v0, None);
+        builder.terminate_with_return(vec![v3, v2]);
+
+        let ssa = builder.finish();
+        let main = ssa.main();
+        let original = main.dfg[main.entry_block()].instructions().to_vec();
+
+        let ssa = ssa.schedule_instructions();
+        let main = ssa.main();
+        assert_eq!(main.dfg[main.entry_block()].instructions(), original);
+    }
+}
@@ -0,0 +1,219 @@
+//! This module converts the self-recursive tail calls of Brillig functions into loops.
+//!
+//! A block ends with a tail call when its last instruction is a call to the function it is in
+//! and it immediately returns the results of this call. Rather than pushing a new frame for the call,
+//! the body of the function is placed in a loop which restarts with the arguments of the call:
+//!
+//! - A header block is created with parameters of the same types as the parameters of the function.
+//! - The instructions and terminator of the entry block are moved into the header and the entry block
+//!   jumps to the header, passing the parameters of the function along.
+//! - Each tail call is replaced by a jump back to the header with the arguments of the call.
+//! - Uses of the parameters of the function outside of the entry block are replaced by the parameters
+//!   of the header, so that they hold the arguments of the current iteration.
+use fxhash::FxHashMap as HashMap;
+
+use crate::ssa::{
+    ir::{
+        basic_block::BasicBlockId,
+        dfg::{CallStack, DataFlowGraph},
+        function::{Function, RuntimeType},
+        instruction::{Instruction, InstructionId, TerminatorInstruction},
+        value::{Value, ValueId},
+    },
+    ssa_gen::Ssa,
+};
+
+impl Ssa {
+    /// See [`tail_calls`][self] module for more information.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub(crate) fn convert_tail_calls(mut self) -> Ssa {
+        for function in self.functions.values_mut() {
+            // ACIR functions are fully inlined, so only Brillig functions can call themselves.
+            if function.runtime() == RuntimeType::Brillig {
+                convert_tail_calls(function);
+            }
+        }
+        self
+    }
+}
+
+fn convert_tail_calls(function: &mut Function) {
+    let tail_calls: Vec<_> = function
+        .reachable_blocks()
+        .into_iter()
+        .filter_map(|block| find_tail_call(function, block).map(|call| (block, call)))
+        .collect();
+    if tail_calls.is_empty() {
+        return;
+    }
+
+    let entry_block = function.entry_block();
+    let header = function.dfg.make_block_with_parameters_from_block(entry_block);
+    let parameters = function.parameters().to_vec();
+    let parameter_mapping: HashMap<ValueId, ValueId> = parameters
+        .iter()
+        .copied()
+        .zip(function.dfg.block_parameters(header).iter().copied())
+        .collect();
+
+    let instructions = function.dfg[entry_block].take_instructions();
+    let terminator = function.dfg[entry_block].take_terminator();
+    *function.dfg[header].instructions_mut() = instructions;
+    function.dfg[header].set_terminator(terminator);
+    function.dfg[entry_block].set_terminator(TerminatorInstruction::Jmp {
+        destination: header,
+        arguments: parameters,
+        call_stack: CallStack::new(),
+    });
+
+    for (block, call) in tail_calls {
+        // The instructions of the entry block now live in the header
+        let block = if block == entry_block { header } else { block };
+        let Instruction::Call { arguments, .. } = &function.dfg[call] else {
+            unreachable!("tail calls are call instructions");
+        };
+        let arguments = arguments.clone();
+        let call_stack = function.dfg.get_call_stack(call);
+
+        function.dfg[block].instructions_mut().pop();
+        function.dfg[block].set_terminator(TerminatorInstruction::Jmp {
+            destination: header,
+            arguments,
+            call_stack,
+        });
+    }
+
+    let map_parameter = |dfg: &DataFlowGraph, value: ValueId| {
+        let value = dfg.resolve(value);
+        parameter_mapping.get(&value).copied().unwrap_or(value)
+    };
+    for block in function.reachable_blocks() {
+        if block == entry_block {
+            continue;
+        }
+        for instruction in function.dfg[block].instructions().to_vec() {
+            let new_instruction =
+                function.dfg[instruction].map_values(|value| map_parameter(&function.dfg, value));
+            function.dfg[instruction] = new_instruction;
+        }
+        let terminator = function.dfg[block]
+            .unwrap_terminator()
+            .map_values(|value| map_parameter(&function.dfg, value));
+        function.dfg[block].set_terminator(terminator);
+    }
+}
+
+/// Returns the last instruction of `block` if it is a call to `function` itself
+/// whose results are directly returned.
+fn find_tail_call(function: &Function, block: BasicBlockId) -> Option<InstructionId> {
+    let dfg = &function.dfg;
+    let call = *dfg[block].instructions().last()?;
+    let Instruction::Call { func, .. } = &dfg[call] else {
+        return None;
+    };
+    if !matches!(dfg[*func], Value::Function(id) if id == function.id()) {
+        return None;
+    }
+
+    let Some(TerminatorInstruction::Return { return_values, .. }) = dfg[block].terminator() else {
+        return None;
+    };
+    let results = dfg.instruction_results(call);
+    let returns_results = return_values.len() == results.len()
+        && return_values.iter().zip(results).all(|(value, result)| dfg.resolve(*value) == *result);
+    returns_results.then_some(call)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ssa::{
+        function_builder::FunctionBuilder,
+        ir::{
+            function::RuntimeType,
+            instruction::{BinaryOp, Instruction},
+            loops::LoopReport,
+            map::Id,
+            types::Type,
+        },
+    };
+
+    #[test]
+    fn converts_self_tail_call_into_loop() {
+        // brillig fn main f0 {
+        //   b0(v0: u32, v1: Field):
+        //     v2 = eq v0, u32 0
+        //     jmpif v2 then: b1, else: b2
+        //   b1():
+        //     return v1
+        //   b2():
+        //     v3 = sub v0, u32 1
+        //     v4 = mul v1, Field 2
+        //     v5 = call f0(v3, v4)
+        //     return v5
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Brillig);
+        let v0 = builder.add_parameter(Type::unsigned(32));
+        let v1 = builder.add_parameter(Type::field());
+        let b1 = builder.insert_block();
+        let b2 = builder.insert_block();
+
+        let zero = builder.numeric_constant(0u128, Type::unsigned(32));
+        let one = builder.numeric_constant(1u128, Type::unsigned(32));
+        let two = builder.field_constant(2u128);
+
+        let v2 = builder.insert_binary(v0, BinaryOp::Eq, zero);
+        builder.terminate_with_jmpif(v2, b1, b2);
+
+        builder.switch_to_block(b1);
+        builder.terminate_with_return(vec![v1]);
+
+        builder.switch_to_block(b2);
+        let v3 = builder.insert_binary(v0, BinaryOp::Sub, one);
+        let v4 = builder.insert_binary(v1, BinaryOp::Mul, two);
+        let main = builder.import_function(main_id);
+        let v5 = builder.insert_call(main, vec![v3, v4], vec![Type::field()])[0];
+        builder.terminate_with_return(vec![v5]);
+
+        let ssa = builder.finish();
+        assert_eq!(LoopReport::with_function(ssa.main()), LoopReport { count: 0, max_depth: 0 });
+
+        // Expected output:
+        //
+        // brillig fn main f0 {
+        //   b0(v0: u32, v1: Field):
+        //     jmp b3(v0, v1)
+        //   b3(v6: u32, v7: Field):
+        //     v2 = eq v6, u32 0
+        //     jmpif v2 then: b1, else: b2
+        //   b1():
+        //     return v7
+        //   b2():
+        //     v3 = sub v6, u32 1
+        //     v4 = mul v7, Field 2
+        //     jmp b3(v3, v4)
+        // }
+        let ssa = ssa.convert_tail_calls();
+        let main = ssa.main();
+        assert_eq!(LoopReport::with_function(main), LoopReport { count: 1, max_depth: 1 });
+
+        for block in main.reachable_blocks() {
+            for instruction in main.dfg[block].instructions() {
+                assert!(!matches!(main.dfg[*instruction], Instruction::Call { .. }));
+            }
+        }
+
+        // The parameters of the function are only used to enter the loop
+        let parameters = main.parameters();
+        for block in main.reachable_blocks() {
+            if block == main.entry_block() {
+                continue;
+            }
+            for instruction in main.dfg[block].instructions() {
+                main.dfg[*instruction].for_each_value(|value| {
+                    assert!(!parameters.contains(&value));
+                });
+            }
+        }
+    }
+}
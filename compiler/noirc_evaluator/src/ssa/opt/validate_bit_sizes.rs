@@ -0,0 +1,109 @@
+//! Brillig compiles each binary operation with a single bit size, taken from its operands.
+//! An integer binary operation whose operands have different bit sizes is then silently
+//! computed at the wrong width, so this pass checks that no such operation reaches code generation.
+use crate::{
+    errors::{InternalError, RuntimeError},
+    ssa::{
+        ir::{
+            function::{Function, RuntimeType},
+            instruction::Instruction,
+            types::{NumericType, Type},
+        },
+        ssa_gen::Ssa,
+    },
+};
+
+impl Ssa {
+    /// See [`validate_bit_sizes`][self] module for more information.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub(crate) fn validate_bit_sizes(&self) -> Result<(), RuntimeError> {
+        for function in self.functions.values() {
+            if function.runtime() == RuntimeType::Brillig {
+                validate_bit_sizes(function)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn validate_bit_sizes(function: &Function) -> Result<(), RuntimeError> {
+    for block in function.reachable_blocks() {
+        for instruction_id in function.dfg[block].instructions() {
+            let Instruction::Binary(binary) = &function.dfg[*instruction_id] else {
+                continue;
+            };
+            let lhs_type = function.dfg.type_of_value(binary.lhs);
+            let rhs_type = function.dfg.type_of_value(binary.rhs);
+            let (Type::Numeric(lhs_type), Type::Numeric(rhs_type)) = (lhs_type, rhs_type) else {
+                continue;
+            };
+            let is_integer_operation =
+                lhs_type != NumericType::NativeField || rhs_type != NumericType::NativeField;
+
+            if is_integer_operation && lhs_type.bit_size() != rhs_type.bit_size() {
+                return Err(RuntimeError::InternalError(InternalError::MismatchedBitSizes {
+                    operator: binary.operator.to_string(),
+                    lhs_bit_size: lhs_type.bit_size(),
+                    rhs_bit_size: rhs_type.bit_size(),
+                    call_stack: function.dfg.get_call_stack(*instruction_id),
+                }));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        errors::{InternalError, RuntimeError},
+        ssa::{
+            function_builder::FunctionBuilder,
+            ir::{function::RuntimeType, instruction::BinaryOp, map::Id, types::Type},
+        },
+    };
+
+    #[test]
+    fn accepts_operands_of_the_same_bit_size() {
+        // brillig fn main f0 {
+        //   b0(v0: u32, v1: u32):
+        //     v2 = add v0, v1
+        //     return v2
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Brillig);
+        let v0 = builder.add_parameter(Type::unsigned(32));
+        let v1 = builder.add_parameter(Type::unsigned(32));
+        let v2 = builder.insert_binary(v0, BinaryOp::Add, v1);
+        builder.terminate_with_return(vec![v2]);
+
+        let ssa = builder.finish();
+        assert_eq!(ssa.validate_bit_sizes(), Ok(()));
+    }
+
+    #[test]
+    fn rejects_operands_of_different_bit_sizes() {
+        // brillig fn main f0 {
+        //   b0(v0: u32, v1: u8):
+        //     v2 = add v0, v1
+        //     return v2
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Brillig);
+        let v0 = builder.add_parameter(Type::unsigned(32));
+        let v1 = builder.add_parameter(Type::unsigned(8));
+        let v2 = builder.insert_binary(v0, BinaryOp::Add, v1);
+        builder.terminate_with_return(vec![v2]);
+
+        let ssa = builder.finish();
+        let error = ssa.validate_bit_sizes().expect_err("operands of add have different bit sizes");
+        assert!(matches!(
+            error,
+            RuntimeError::InternalError(InternalError::MismatchedBitSizes {
+                lhs_bit_size: 32,
+                rhs_bit_size: 8,
+                ..
+            })
+        ));
+    }
+}
@@ -2,7 +2,7 @@ use std::path::{Path, PathBuf};
 
 use crate::BackendError;
 
-use super::string_from_stderr;
+use super::{backend_command, command_failure};
 
 /// VerifyCommand will call the barretenberg binary
 /// to return a solidity library with the verification key
@@ -17,8 +17,8 @@ pub(crate) struct ContractCommand {
 }
 
 impl ContractCommand {
-    pub(crate) fn run(self, binary_path: &Path) -> Result<String, BackendError> {
-        let mut command = std::process::Command::new(binary_path);
+    pub(crate) fn run(self, binary_path: &Path, sandboxed: bool) -> Result<String, BackendError> {
+        let mut command = backend_command(binary_path, sandboxed);
 
         command
             .arg("contract")
@@ -35,7 +35,7 @@ impl ContractCommand {
             String::from_utf8(output.stdout)
                 .map_err(|error| BackendError::InvalidUTF8Vector(error.into_bytes()))
         } else {
-            Err(BackendError::CommandFailed(string_from_stderr(&output.stderr)))
+            Err(command_failure(&output))
         }
     }
 }
@@ -60,10 +60,10 @@ fn contract_command() -> Result<(), BackendError> {
         vk_path_output: vk_path.clone(),
         crs_path: crs_path.clone(),
     };
-    write_vk_command.run(backend.binary_path())?;
+    write_vk_command.run(backend.binary_path(), false)?;
 
     let contract_command = ContractCommand { vk_path, crs_path };
-    contract_command.run(backend.binary_path())?;
+    contract_command.run(backend.binary_path(), false)?;
 
     drop(temp_directory);
 
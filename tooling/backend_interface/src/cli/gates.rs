@@ -2,7 +2,7 @@ use std::path::{Path, PathBuf};
 
 use crate::BackendError;
 
-use super::string_from_stderr;
+use super::{backend_command, command_failure};
 
 /// GatesCommand will call the barretenberg binary
 /// to return the number of gates needed to create a proof
@@ -13,8 +13,8 @@ pub(crate) struct GatesCommand {
 }
 
 impl GatesCommand {
-    pub(crate) fn run(self, binary_path: &Path) -> Result<u32, BackendError> {
-        let output = std::process::Command::new(binary_path)
+    pub(crate) fn run(self, binary_path: &Path, sandboxed: bool) -> Result<u32, BackendError> {
+        let output = backend_command(binary_path, sandboxed)
             .arg("gates")
             .arg("-c")
             .arg(self.crs_path)
@@ -23,7 +23,7 @@ impl GatesCommand {
             .output()?;
 
         if !output.status.success() {
-            return Err(BackendError::CommandFailed(string_from_stderr(&output.stderr)));
+            return Err(command_failure(&output));
         }
         // Note: barretenberg includes the newline, so that subsequent prints to stdout
         // are not on the same line as the gates output.
@@ -56,7 +56,7 @@ fn gate_command() -> Result<(), BackendError> {
 
     let gate_command = GatesCommand { crs_path, bytecode_path };
 
-    let output = gate_command.run(backend.binary_path())?;
+    let output = gate_command.run(backend.binary_path(), false)?;
     // Mock backend always returns zero gates.
     assert_eq!(output, 0);
 
@@ -5,7 +5,7 @@ use std::path::{Path, PathBuf};
 
 use crate::BackendError;
 
-use super::string_from_stderr;
+use super::{backend_command, command_failure};
 
 pub(crate) struct InfoCommand {
     pub(crate) crs_path: PathBuf,
@@ -23,15 +23,19 @@ struct LanguageResponse {
 }
 
 impl InfoCommand {
-    pub(crate) fn run(self, binary_path: &Path) -> Result<ExpressionWidth, BackendError> {
-        let mut command = std::process::Command::new(binary_path);
+    pub(crate) fn run(
+        self,
+        binary_path: &Path,
+        sandboxed: bool,
+    ) -> Result<ExpressionWidth, BackendError> {
+        let mut command = backend_command(binary_path, sandboxed);
 
         command.arg("info").arg("-c").arg(self.crs_path).arg("-o").arg("-");
 
         let output = command.output()?;
 
         if !output.status.success() {
-            return Err(BackendError::CommandFailed(string_from_stderr(&output.stderr)));
+            return Err(command_failure(&output));
         }
 
         let backend_info: InfoResponse =
@@ -54,7 +58,7 @@ fn info_command() -> Result<(), BackendError> {
     let backend = crate::get_mock_backend()?;
     let crs_path = backend.backend_directory();
 
-    let expression_width = InfoCommand { crs_path }.run(backend.binary_path())?;
+    let expression_width = InfoCommand { crs_path }.run(backend.binary_path(), false)?;
 
     assert!(matches!(expression_width, ExpressionWidth::Bounded { width: 3 }));
 
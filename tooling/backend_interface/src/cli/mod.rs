@@ -1,5 +1,8 @@
 // Reference: https://github.com/AztecProtocol/aztec-packages/blob/master/barretenberg/cpp/src/barretenberg/bb/main.cpp
 
+use std::path::Path;
+use std::process::{Command, ExitStatus, Output};
+
 mod contract;
 mod gates;
 mod info;
@@ -35,7 +38,119 @@ fn no_command_provided_works() -> Result<(), crate::BackendError> {
     Ok(())
 }
 
+#[cfg(unix)]
+#[test]
+fn signal_termination_is_reported() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_directory = tempfile::tempdir().expect("could not create a temporary directory");
+    let stub_backend = temp_directory.path().join("stub_backend");
+    std::fs::write(&stub_backend, "#!/bin/sh\nkill -TERM $$\n").expect("file should be written");
+    std::fs::set_permissions(&stub_backend, std::fs::Permissions::from_mode(0o755))
+        .expect("file should be executable");
+
+    let error = VersionCommand.run(&stub_backend, false).expect_err("backend should be terminated");
+    assert!(matches!(error, crate::BackendError::TerminatedBySignal(_)));
+    assert!(error.to_string().contains("SIGTERM"), "unexpected error: {error}");
+}
+
+#[cfg(unix)]
+#[test]
+fn sandboxed_backend_only_inherits_allowed_env_vars() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_directory = tempfile::tempdir().expect("could not create a temporary directory");
+    let stub_backend = temp_directory.path().join("stub_backend");
+    std::fs::write(&stub_backend, "#!/bin/sh\nenv\n").expect("file should be written");
+    std::fs::set_permissions(&stub_backend, std::fs::Permissions::from_mode(0o755))
+        .expect("file should be executable");
+
+    let env_vars = |sandboxed: bool| {
+        // The secret is only set for the stub so that the environment of other tests is left untouched.
+        let mut command = Command::new(&stub_backend);
+        command.env("NARGO_SANDBOX_TEST_SECRET", "secret");
+        let output = sandbox_command(command, sandboxed).output().expect("stub backend should run");
+        let stdout = String::from_utf8(output.stdout).expect("env should output valid UTF8");
+        stdout
+            .lines()
+            .filter_map(|line| line.split_once('=').map(|(name, _)| name.to_owned()))
+            .collect::<Vec<_>>()
+    };
+
+    let env_vars_of_backend = env_vars(false);
+    assert!(env_vars_of_backend.contains(&"NARGO_SANDBOX_TEST_SECRET".to_owned()));
+    assert!(env_vars_of_backend.contains(&"PATH".to_owned()));
+
+    let env_vars_of_sandboxed_backend = env_vars(true);
+    assert!(!env_vars_of_sandboxed_backend.contains(&"NARGO_SANDBOX_TEST_SECRET".to_owned()));
+    assert!(env_vars_of_sandboxed_backend.contains(&"PATH".to_owned()));
+}
+
 // Converts a stderr byte array to a string (including invalid characters)
 fn string_from_stderr(stderr: &[u8]) -> String {
     String::from_utf8_lossy(stderr).to_string()
 }
+
+/// Environment variables which a sandboxed backend still inherits.
+const SANDBOX_ENV_ALLOWLIST: &[&str] =
+    &["PATH", "HOME", "TMPDIR", "SYSTEMROOT", "NARGO_BACKEND_PATH", "BB_BINARY_URL"];
+
+/// Returns the names of the variables of [`SANDBOX_ENV_ALLOWLIST`] which are set,
+/// and so are passed to a sandboxed backend.
+pub(crate) fn sandboxed_env_vars() -> Vec<&'static str> {
+    SANDBOX_ENV_ALLOWLIST.iter().copied().filter(|name| std::env::var_os(name).is_some()).collect()
+}
+
+/// Creates a [`Command`] which runs the backend binary at `binary_path`.
+fn backend_command(binary_path: &Path, sandboxed: bool) -> Command {
+    sandbox_command(Command::new(binary_path), sandboxed)
+}
+
+/// Restricts the backend run by `command` if it is `sandboxed`.
+///
+/// A `sandboxed` backend only inherits the environment variables in [`SANDBOX_ENV_ALLOWLIST`].
+/// Its access to the filesystem is not currently restricted.
+fn sandbox_command(mut command: Command, sandboxed: bool) -> Command {
+    if sandboxed {
+        command.env_clear();
+        for name in SANDBOX_ENV_ALLOWLIST {
+            if let Some(value) = std::env::var_os(name) {
+                command.env(name, value);
+            }
+        }
+    }
+    command
+}
+
+/// Converts the output of a backend command which did not succeed into a [`BackendError`][crate::BackendError].
+fn command_failure(output: &Output) -> crate::BackendError {
+    match termination_signal(&output.status) {
+        Some(signal) => crate::BackendError::TerminatedBySignal(signal),
+        None => crate::BackendError::CommandFailed(string_from_stderr(&output.stderr)),
+    }
+}
+
+/// Returns the name of the signal which terminated the backend, if it did not exit by itself.
+#[cfg(unix)]
+fn termination_signal(status: &ExitStatus) -> Option<String> {
+    use std::os::unix::process::ExitStatusExt;
+
+    let signal = status.signal()?;
+    let name = match signal {
+        1 => "SIGHUP".to_owned(),
+        2 => "SIGINT".to_owned(),
+        3 => "SIGQUIT".to_owned(),
+        6 => "SIGABRT".to_owned(),
+        9 => "SIGKILL".to_owned(),
+        11 => "SIGSEGV".to_owned(),
+        13 => "SIGPIPE".to_owned(),
+        15 => "SIGTERM".to_owned(),
+        _ => format!("signal {signal}"),
+    };
+    Some(name)
+}
+
+#[cfg(not(unix))]
+fn termination_signal(_status: &ExitStatus) -> Option<String> {
+    None
+}
@@ -4,7 +4,7 @@ use acvm::FieldElement;
 
 use crate::BackendError;
 
-use super::string_from_stderr;
+use super::{backend_command, command_failure};
 
 /// `ProofAsFieldsCommand` will call the barretenberg binary
 /// to split a proof into a representation as [`FieldElement`]s.
@@ -14,8 +14,12 @@ pub(crate) struct ProofAsFieldsCommand {
 }
 
 impl ProofAsFieldsCommand {
-    pub(crate) fn run(self, binary_path: &Path) -> Result<Vec<FieldElement>, BackendError> {
-        let mut command = std::process::Command::new(binary_path);
+    pub(crate) fn run(
+        self,
+        binary_path: &Path,
+        sandboxed: bool,
+    ) -> Result<Vec<FieldElement>, BackendError> {
+        let mut command = backend_command(binary_path, sandboxed);
 
         command
             .arg("proof_as_fields")
@@ -32,7 +36,7 @@ impl ProofAsFieldsCommand {
             serde_json::from_str(&string_output)
                 .map_err(|err| BackendError::CommandFailed(err.to_string()))
         } else {
-            Err(BackendError::CommandFailed(string_from_stderr(&output.stderr)))
+            Err(command_failure(&output))
         }
     }
 }
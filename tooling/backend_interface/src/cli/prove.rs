@@ -2,7 +2,7 @@ use std::path::{Path, PathBuf};
 
 use crate::BackendError;
 
-use super::string_from_stderr;
+use super::{backend_command, command_failure};
 
 /// ProveCommand will call the barretenberg binary
 /// to create a proof, given the witness and the bytecode.
@@ -11,6 +11,7 @@ use super::string_from_stderr;
 /// proving key, so this is not returned.
 ///
 /// The proof will be written to the specified output file.
+/// The log output of the backend is returned alongside it.
 pub(crate) struct ProveCommand {
     pub(crate) crs_path: PathBuf,
     pub(crate) bytecode_path: PathBuf,
@@ -18,8 +19,12 @@ pub(crate) struct ProveCommand {
 }
 
 impl ProveCommand {
-    pub(crate) fn run(self, binary_path: &Path) -> Result<Vec<u8>, BackendError> {
-        let mut command = std::process::Command::new(binary_path);
+    pub(crate) fn run(
+        self,
+        binary_path: &Path,
+        sandboxed: bool,
+    ) -> Result<(Vec<u8>, String), BackendError> {
+        let mut command = backend_command(binary_path, sandboxed);
 
         command
             .arg("prove")
@@ -34,9 +39,9 @@ impl ProveCommand {
 
         let output = command.output()?;
         if output.status.success() {
-            Ok(output.stdout)
+            Ok((output.stdout, String::from_utf8_lossy(&output.stderr).into_owned()))
         } else {
-            Err(BackendError::CommandFailed(string_from_stderr(&output.stderr)))
+            Err(command_failure(&output))
         }
     }
 }
@@ -58,7 +63,7 @@ fn prove_command() -> Result<(), BackendError> {
     let crs_path = backend.backend_directory();
     let prove_command = ProveCommand { crs_path, bytecode_path, witness_path };
 
-    let proof = prove_command.run(backend.binary_path())?;
+    let (proof, _) = prove_command.run(backend.binary_path(), false)?;
     assert_eq!(proof, "proof".as_bytes());
     drop(temp_directory);
 
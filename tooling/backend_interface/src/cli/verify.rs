@@ -2,6 +2,8 @@ use std::path::{Path, PathBuf};
 
 use crate::BackendError;
 
+use super::{backend_command, termination_signal};
+
 /// VerifyCommand will call the barretenberg binary
 /// to verify a proof
 pub(crate) struct VerifyCommand {
@@ -11,8 +13,8 @@ pub(crate) struct VerifyCommand {
 }
 
 impl VerifyCommand {
-    pub(crate) fn run(self, binary_path: &Path) -> Result<bool, BackendError> {
-        let mut command = std::process::Command::new(binary_path);
+    pub(crate) fn run(self, binary_path: &Path, sandboxed: bool) -> Result<bool, BackendError> {
+        let mut command = backend_command(binary_path, sandboxed);
 
         command
             .arg("verify")
@@ -24,6 +26,9 @@ impl VerifyCommand {
             .arg(self.vk_path);
 
         let output = command.output()?;
+        if let Some(signal) = termination_signal(&output.status) {
+            return Err(BackendError::TerminatedBySignal(signal));
+        }
 
         // We currently do not distinguish between an invalid proof and an error inside the backend.
         Ok(output.status.success())
@@ -57,16 +62,16 @@ fn verify_command() -> Result<(), BackendError> {
         vk_path_output: vk_path_output.clone(),
     };
 
-    write_vk_command.run(backend.binary_path())?;
+    write_vk_command.run(backend.binary_path(), false)?;
 
     let prove_command = ProveCommand { crs_path: crs_path.clone(), bytecode_path, witness_path };
-    let proof = prove_command.run(backend.binary_path())?;
+    let (proof, _) = prove_command.run(backend.binary_path(), false)?;
 
     write_to_file(&proof, &proof_path);
 
     let verify_command = VerifyCommand { crs_path, proof_path, vk_path: vk_path_output };
 
-    let verified = verify_command.run(backend.binary_path())?;
+    let verified = verify_command.run(backend.binary_path(), false)?;
     assert!(verified);
 
     drop(temp_directory);
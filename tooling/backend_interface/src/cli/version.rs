@@ -2,15 +2,15 @@ use std::path::Path;
 
 use crate::BackendError;
 
-use super::string_from_stderr;
+use super::{backend_command, command_failure};
 
 /// VersionCommand will call the backend binary
 /// to query installed version.
 pub(crate) struct VersionCommand;
 
 impl VersionCommand {
-    pub(crate) fn run(self, binary_path: &Path) -> Result<String, BackendError> {
-        let mut command = std::process::Command::new(binary_path);
+    pub(crate) fn run(self, binary_path: &Path, sandboxed: bool) -> Result<String, BackendError> {
+        let mut command = backend_command(binary_path, sandboxed);
 
         command.arg("--version");
 
@@ -23,7 +23,7 @@ impl VersionCommand {
                 )),
             }
         } else {
-            Err(BackendError::CommandFailed(string_from_stderr(&output.stderr)))
+            Err(command_failure(&output))
         }
     }
 }
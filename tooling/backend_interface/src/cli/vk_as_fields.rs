@@ -4,7 +4,7 @@ use acvm::FieldElement;
 
 use crate::BackendError;
 
-use super::string_from_stderr;
+use super::{backend_command, command_failure};
 
 /// VkAsFieldsCommand will call the barretenberg binary
 /// to split a verification key into a representation as [`FieldElement`]s.
@@ -18,8 +18,9 @@ impl VkAsFieldsCommand {
     pub(crate) fn run(
         self,
         binary_path: &Path,
+        sandboxed: bool,
     ) -> Result<(FieldElement, Vec<FieldElement>), BackendError> {
-        let mut command = std::process::Command::new(binary_path);
+        let mut command = backend_command(binary_path, sandboxed);
 
         command.arg("vk_as_fields").arg("-k").arg(self.vk_path).arg("-o").arg("-");
 
@@ -33,7 +34,7 @@ impl VkAsFieldsCommand {
             let hash = fields.remove(0);
             Ok((hash, fields))
         } else {
-            Err(BackendError::CommandFailed(string_from_stderr(&output.stderr)))
+            Err(command_failure(&output))
         }
     }
 }
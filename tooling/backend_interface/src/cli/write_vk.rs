@@ -1,6 +1,6 @@
 use std::path::{Path, PathBuf};
 
-use super::string_from_stderr;
+use super::{backend_command, command_failure};
 use crate::BackendError;
 
 /// WriteCommand will call the barretenberg binary
@@ -13,8 +13,8 @@ pub(crate) struct WriteVkCommand {
 
 impl WriteVkCommand {
     #[tracing::instrument(level = "trace", name = "vk_generation", skip_all)]
-    pub(crate) fn run(self, binary_path: &Path) -> Result<(), BackendError> {
-        let mut command = std::process::Command::new(binary_path);
+    pub(crate) fn run(self, binary_path: &Path, sandboxed: bool) -> Result<(), BackendError> {
+        let mut command = backend_command(binary_path, sandboxed);
 
         command
             .arg("write_vk")
@@ -29,7 +29,7 @@ impl WriteVkCommand {
         if output.status.success() {
             Ok(())
         } else {
-            Err(BackendError::CommandFailed(string_from_stderr(&output.stderr)))
+            Err(command_failure(&output))
         }
     }
 }
@@ -51,7 +51,7 @@ fn write_vk_command() -> Result<(), BackendError> {
 
     let write_vk_command = WriteVkCommand { bytecode_path, crs_path, vk_path_output };
 
-    write_vk_command.run(backend.binary_path())?;
+    write_vk_command.run(backend.binary_path(), false)?;
     drop(temp_directory);
 
     Ok(())
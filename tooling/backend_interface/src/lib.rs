@@ -1,17 +1,19 @@
 #![warn(unused_crate_dependencies, unused_extern_crates)]
 #![warn(unreachable_pub)]
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 mod cli;
 mod download;
 mod proof_system;
 mod smart_contract;
+mod timings;
 
 pub use bb_abstraction_leaks::ACVM_BACKEND_BARRETENBERG;
 use bb_abstraction_leaks::BB_VERSION;
 use cli::VersionCommand;
 pub use download::download_backend;
+pub use timings::ProvingTimings;
 use tracing::warn;
 
 const BACKENDS_DIR: &str = ".nargo/backends";
@@ -39,8 +41,11 @@ pub enum BackendError {
     #[error(transparent)]
     IoError(#[from] std::io::Error),
 
-    #[error("Backend binary does not exist")]
-    MissingBinary,
+    #[error("Backend binary does not exist at {}. Install a backend with `nargo backend install` or point NARGO_BACKEND_PATH to an existing one", .0.display())]
+    MissingBinary(PathBuf),
+
+    #[error("Backend binary at {} is not executable", .0.display())]
+    BinaryNotExecutable(PathBuf),
 
     #[error("The backend responded with a malformed UTF8 byte vector: {0:?}")]
     InvalidUTF8Vector(Vec<u8>),
@@ -52,12 +57,17 @@ pub enum BackendError {
 
     #[error("The backend encountered an error: {0:?}")]
     CommandFailed(String),
+
+    #[error("The backend was terminated by {0}")]
+    TerminatedBySignal(String),
 }
 
 #[derive(Debug)]
 pub struct Backend {
     name: String,
     binary_path: PathBuf,
+    /// Whether the backend binary is run with a restricted environment.
+    sandboxed: bool,
 }
 
 impl Backend {
@@ -69,20 +79,49 @@ impl Backend {
 
             backends_directory().join(&name).join(BINARY_NAME)
         };
-        Backend { name, binary_path }
+        Backend { name, binary_path, sandboxed: false }
+    }
+
+    /// Uses the backend binary at `binary_path` rather than the installed one.
+    pub fn with_binary_path(mut self, binary_path: PathBuf) -> Backend {
+        self.binary_path = binary_path;
+        self
+    }
+
+    /// Runs the backend binary with only the environment variables it requires,
+    /// rather than inheriting the full environment of `nargo`.
+    pub fn sandboxed(mut self, sandboxed: bool) -> Backend {
+        self.sandboxed = sandboxed;
+        self
     }
 
     pub fn name(&self) -> &str {
         &self.name
     }
 
-    fn binary_path(&self) -> &PathBuf {
+    /// Queries the backend binary for its version.
+    pub fn version(&self) -> Result<String, BackendError> {
+        let binary_path = self.assert_binary_exists()?;
+        VersionCommand.run(binary_path, self.sandboxed).map(|version| version.trim().to_owned())
+    }
+
+    /// The path of the backend binary, which may not have been installed yet.
+    pub fn binary_path(&self) -> &PathBuf {
         &self.binary_path
     }
 
+    /// Returns the names of the environment variables passed to the backend binary,
+    /// or `None` if it inherits the full environment of `nargo`.
+    pub fn passed_env_vars(&self) -> Option<Vec<&'static str>> {
+        self.sandboxed.then(cli::sandboxed_env_vars)
+    }
+
     fn assert_binary_exists(&self) -> Result<&PathBuf, BackendError> {
         let binary_path = self.binary_path();
         if binary_path.is_file() {
+            if !is_executable(binary_path) {
+                return Err(BackendError::BinaryNotExecutable(binary_path.clone()));
+            }
             Ok(binary_path)
         } else {
             if self.name == ACVM_BACKEND_BARRETENBERG {
@@ -92,7 +131,7 @@ impl Backend {
                 download_backend(&bb_url, binary_path)?;
                 return Ok(binary_path);
             }
-            Err(BackendError::MissingBinary)
+            Err(BackendError::MissingBinary(binary_path.clone()))
         }
     }
 
@@ -110,7 +149,7 @@ impl Backend {
     fn assert_correct_version(&self) -> Result<&PathBuf, BackendError> {
         let binary_path = self.binary_path();
         if binary_path.to_string_lossy().contains(ACVM_BACKEND_BARRETENBERG) {
-            match VersionCommand.run(binary_path) {
+            match VersionCommand.run(binary_path, self.sandboxed) {
                 // If version matches then do nothing.
                 Ok(version_string) if version_string == BB_VERSION => (),
 
@@ -135,6 +174,18 @@ impl Backend {
     }
 }
 
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata().map(|metadata| metadata.permissions().mode() & 0o111 != 0).unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    true
+}
+
 #[cfg(test)]
 mod backend {
     use crate::{Backend, BackendError};
@@ -145,6 +196,28 @@ mod backend {
 
         let binary_path = bad_backend.assert_binary_exists();
 
-        assert!(matches!(binary_path, Err(BackendError::MissingBinary)));
+        match binary_path {
+            Err(error @ BackendError::MissingBinary(_)) => {
+                let message = error.to_string();
+                assert!(message.contains(&bad_backend.binary_path.display().to_string()));
+                assert!(message.contains("nargo backend install"));
+            }
+            _ => panic!("expected a missing binary error"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn raises_error_on_non_executable_binary() {
+        let temp_directory = tempfile::tempdir().unwrap();
+        let binary_path = temp_directory.path().join("backend_binary");
+        std::fs::write(&binary_path, "").unwrap();
+
+        let backend = Backend { name: "not_executable".to_string(), binary_path, sandboxed: false };
+
+        assert!(matches!(
+            backend.assert_binary_exists(),
+            Err(BackendError::BinaryNotExecutable(path)) if path == backend.binary_path
+        ));
     }
 }
@@ -14,7 +14,7 @@ use crate::cli::{
     GatesCommand, InfoCommand, ProofAsFieldsCommand, ProveCommand, VerifyCommand,
     VkAsFieldsCommand, WriteVkCommand,
 };
-use crate::{Backend, BackendError};
+use crate::{Backend, BackendError, ProvingTimings};
 
 impl Backend {
     pub fn get_exact_circuit_size(&self, circuit: &Circuit) -> Result<u32, BackendError> {
@@ -30,13 +30,13 @@ impl Backend {
         write_to_file(&serialized_circuit, &circuit_path);
 
         GatesCommand { crs_path: self.crs_directory(), bytecode_path: circuit_path }
-            .run(binary_path)
+            .run(binary_path, self.sandboxed)
     }
 
     pub fn get_backend_info(&self) -> Result<ExpressionWidth, BackendError> {
         let binary_path = self.assert_binary_exists()?;
         self.assert_correct_version()?;
-        InfoCommand { crs_path: self.crs_directory() }.run(binary_path)
+        InfoCommand { crs_path: self.crs_directory() }.run(binary_path, self.sandboxed)
     }
 
     /// If we cannot get a valid backend, returns `ExpressionWidth::Bound { width: 3 }``
@@ -52,12 +52,21 @@ impl Backend {
         }
     }
 
-    #[tracing::instrument(level = "trace", skip_all)]
     pub fn prove(
         &self,
         circuit: &Circuit,
         witness_values: WitnessMap,
     ) -> Result<Vec<u8>, BackendError> {
+        self.prove_with_timings(circuit, witness_values).map(|(proof, _)| proof)
+    }
+
+    /// Creates a proof, along with the time spent in each phase if the backend reports it.
+    #[tracing::instrument(level = "trace", skip_all)]
+    pub fn prove_with_timings(
+        &self,
+        circuit: &Circuit,
+        witness_values: WitnessMap,
+    ) -> Result<(Vec<u8>, Option<ProvingTimings>), BackendError> {
         let binary_path = self.assert_binary_exists()?;
         self.assert_correct_version()?;
 
@@ -77,15 +86,15 @@ impl Backend {
         write_to_file(&serialized_circuit, &bytecode_path);
 
         // Create proof and store it in the specified path
-        let proof_with_public_inputs =
+        let (proof_with_public_inputs, log) =
             ProveCommand { crs_path: self.crs_directory(), bytecode_path, witness_path }
-                .run(binary_path)?;
+                .run(binary_path, self.sandboxed)?;
 
         let proof = bb_abstraction_leaks::remove_public_inputs(
             circuit.public_inputs().0.len(),
             &proof_with_public_inputs,
         );
-        Ok(proof)
+        Ok((proof, ProvingTimings::parse(&log)))
     }
 
     #[tracing::instrument(level = "trace", skip_all)]
@@ -120,10 +129,11 @@ impl Backend {
             bytecode_path,
             vk_path_output: vk_path.clone(),
         }
-        .run(binary_path)?;
+        .run(binary_path, self.sandboxed)?;
 
         // Verify the proof
-        VerifyCommand { crs_path: self.crs_directory(), proof_path, vk_path }.run(binary_path)
+        VerifyCommand { crs_path: self.crs_directory(), proof_path, vk_path }
+            .run(binary_path, self.sandboxed)
     }
 
     pub fn get_intermediate_proof_artifacts(
@@ -152,7 +162,7 @@ impl Backend {
             bytecode_path,
             vk_path_output: vk_path.clone(),
         }
-        .run(binary_path)?;
+        .run(binary_path, self.sandboxed)?;
 
         // Create a temporary file for the proof
 
@@ -163,10 +173,11 @@ impl Backend {
 
         // Now ready to generate intermediate artifacts.
 
-        let proof_as_fields =
-            ProofAsFieldsCommand { proof_path, vk_path: vk_path.clone() }.run(binary_path)?;
+        let proof_as_fields = ProofAsFieldsCommand { proof_path, vk_path: vk_path.clone() }
+            .run(binary_path, self.sandboxed)?;
 
-        let (vk_hash, vk_as_fields) = VkAsFieldsCommand { vk_path }.run(binary_path)?;
+        let (vk_hash, vk_as_fields) =
+            VkAsFieldsCommand { vk_path }.run(binary_path, self.sandboxed)?;
 
         Ok((proof_as_fields, vk_hash, vk_as_fields))
     }
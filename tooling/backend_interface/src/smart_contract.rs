@@ -27,9 +27,9 @@ impl Backend {
             bytecode_path,
             vk_path_output: vk_path.clone(),
         }
-        .run(binary_path)?;
+        .run(binary_path, self.sandboxed)?;
 
-        ContractCommand { crs_path: self.crs_directory(), vk_path }.run(binary_path)
+        ContractCommand { crs_path: self.crs_directory(), vk_path }.run(binary_path, self.sandboxed)
     }
 }
 
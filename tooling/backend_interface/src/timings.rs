@@ -0,0 +1,93 @@
+use std::fmt::Display;
+use std::time::Duration;
+
+/// How long the backend spent in each phase of generating a proof.
+///
+/// Backends may report these as lines of the form `<phase>: <milliseconds>ms` in their log output,
+/// where `<phase>` is one of `key generation`, `witness` or `proof`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ProvingTimings {
+    pub key_generation: Option<Duration>,
+    pub witness: Option<Duration>,
+    pub proof: Option<Duration>,
+}
+
+impl ProvingTimings {
+    /// Collects the phase timings reported in `log`, returning `None` if the backend didn't report any.
+    pub fn parse(log: &str) -> Option<ProvingTimings> {
+        let mut timings = ProvingTimings::default();
+        for line in log.lines() {
+            let Some((phase, time)) = line.split_once(':') else {
+                continue;
+            };
+            let Some(milliseconds) =
+                time.trim().strip_suffix("ms").and_then(|time| time.trim().parse::<u64>().ok())
+            else {
+                continue;
+            };
+            let duration = Some(Duration::from_millis(milliseconds));
+            match phase.trim().to_lowercase().as_str() {
+                "key generation" => timings.key_generation = duration,
+                "witness" => timings.witness = duration,
+                "proof" => timings.proof = duration,
+                _ => (),
+            }
+        }
+
+        (timings != ProvingTimings::default()).then_some(timings)
+    }
+}
+
+impl Display for ProvingTimings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let phases = [
+            ("key generation", self.key_generation),
+            ("witness", self.witness),
+            ("proof", self.proof),
+        ];
+        write!(f, "Backend timings:")?;
+        for (phase, duration) in phases {
+            if let Some(duration) = duration {
+                write!(f, "\n  {phase}: {}ms", duration.as_millis())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::ProvingTimings;
+
+    #[test]
+    fn parses_reported_phase_timings() {
+        let log = "\
+Loading circuit
+key generation: 1250ms
+witness: 40ms
+computing commitments
+proof: 3100 ms
+";
+        let timings = ProvingTimings::parse(log).expect("timings should be reported");
+        assert_eq!(
+            timings,
+            ProvingTimings {
+                key_generation: Some(Duration::from_millis(1250)),
+                witness: Some(Duration::from_millis(40)),
+                proof: Some(Duration::from_millis(3100)),
+            }
+        );
+        assert_eq!(
+            timings.to_string(),
+            "Backend timings:\n  key generation: 1250ms\n  witness: 40ms\n  proof: 3100ms"
+        );
+    }
+
+    #[test]
+    fn returns_none_without_phase_timings() {
+        assert_eq!(ProvingTimings::parse(""), None);
+        assert_eq!(ProvingTimings::parse("Loading circuit\nproof: done\n"), None);
+    }
+}
@@ -1,11 +1,12 @@
 use std::collections::BTreeMap;
 
-use acvm::acir::circuit::Circuit;
+use acvm::acir::{acir_field::CHOSEN_FIELD, circuit::Circuit};
 use fm::FileId;
 use noirc_abi::Abi;
 use noirc_driver::CompiledProgram;
 use noirc_driver::DebugFile;
 use noirc_errors::debug_info::DebugInfo;
+use noirc_evaluator::LoopReport;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -34,6 +35,17 @@ pub struct ProgramArtifact {
 
     /// Map of file Id to the source code so locations in debug info can be mapped to source code they point to.
     pub file_map: BTreeMap<FileId, DebugFile>,
+
+    /// The field the circuit is defined over, e.g. `bn254`.
+    ///
+    /// Artifacts written by older versions of nargo do not record it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+
+    /// The loops left in the program once it is optimized, kept so that `nargo info` can report them
+    /// when the artifact is reused. Artifacts written by older versions of nargo report no loops.
+    #[serde(default)]
+    pub loops: LoopReport,
 }
 
 impl From<CompiledProgram> for ProgramArtifact {
@@ -45,6 +57,8 @@ impl From<CompiledProgram> for ProgramArtifact {
             bytecode: program.circuit,
             debug_symbols: program.debug,
             file_map: program.file_map,
+            field: Some(CHOSEN_FIELD.to_string()),
+            loops: program.loops,
         }
     }
 }
@@ -59,6 +73,7 @@ impl From<ProgramArtifact> for CompiledProgram {
             debug: program.debug_symbols,
             file_map: program.file_map,
             warnings: vec![],
+            loops: program.loops,
         }
     }
 }
@@ -1,7 +1,13 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use backend_interface::backends_directory;
 pub(crate) use backend_interface::Backend;
+use serde::Deserialize;
+
+use crate::errors::BackendError;
+
+/// Name of the file at the root of a package from which backend settings are read.
+const BACKEND_CONFIG_FILE: &str = "backend.toml";
 
 fn active_backend_file_path() -> PathBuf {
     backends_directory().join(".selected_backend")
@@ -37,3 +43,152 @@ pub(crate) fn get_active_backend() -> String {
 
     std::fs::read_to_string(active_backend_file).expect("Could not read active backend file")
 }
+
+/// Settings for the backend, which can be shared by a team through a `backend.toml` file
+/// at the root of a package. Settings passed on the command line take precedence over the file.
+///
+/// Only the backend to use, its binary and whether it is sandboxed can be set. Subcommand names,
+/// default arguments and environment variables are not supported: the subcommands which `nargo` runs,
+/// their arguments and the environment passed to the backend are fixed by `backend_interface`,
+/// and the file is rejected if it sets anything else.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct BackendConfig {
+    /// Name of the backend to use instead of the one selected with `nargo backend use`.
+    pub(crate) name: Option<String>,
+    /// Path to the backend binary, relative to the package root in `backend.toml`.
+    pub(crate) path: Option<PathBuf>,
+    /// Whether to run the backend with a restricted environment.
+    pub(crate) sandbox: Option<bool>,
+}
+
+impl BackendConfig {
+    /// Reads the `backend.toml` file in `program_dir`, if there is one.
+    pub(crate) fn read(program_dir: &Path) -> Result<BackendConfig, BackendError> {
+        let config_path = program_dir.join(BACKEND_CONFIG_FILE);
+        if !config_path.is_file() {
+            return Ok(BackendConfig::default());
+        }
+
+        let invalid_config =
+            |error: String| BackendError::InvalidConfig(config_path.clone(), error);
+        let contents = std::fs::read_to_string(&config_path)
+            .map_err(|error| invalid_config(error.to_string()))?;
+        let config: BackendConfig =
+            toml::from_str(&contents).map_err(|error| invalid_config(error.to_string()))?;
+
+        Ok(BackendConfig { path: config.path.map(|path| program_dir.join(path)), ..config })
+    }
+
+    /// Fills in the settings missing from `self` with those of `defaults`.
+    pub(crate) fn or(self, defaults: BackendConfig) -> BackendConfig {
+        BackendConfig {
+            name: self.name.or(defaults.name),
+            path: self.path.or(defaults.path),
+            sandbox: self.sandbox.or(defaults.sandbox),
+        }
+    }
+
+    /// Explains, one line per setting, where the backend settings resolved by `self.or(file_config)`
+    /// come from, when `self` holds the settings passed on the command line and through the environment.
+    pub(crate) fn explain_resolution(&self, file_config: &BackendConfig) -> Vec<String> {
+        let name = match (&self.name, &file_config.name) {
+            (Some(name), _) => format!("backend `{name}` was passed on the command line"),
+            (None, Some(name)) => format!("backend `{name}` was read from {BACKEND_CONFIG_FILE}"),
+            (None, None) => format!(
+                "backend `{}` is the one selected with `nargo backend use`",
+                get_active_backend()
+            ),
+        };
+        let path = match (&self.path, &file_config.path) {
+            (Some(path), _) => {
+                format!("backend binary {} was set by NARGO_BACKEND_PATH", path.display())
+            }
+            (None, Some(path)) => {
+                format!("backend binary {} was read from {BACKEND_CONFIG_FILE}", path.display())
+            }
+            (None, None) => "backend binary is the installed one in the backends directory".into(),
+        };
+        let enabled = |sandbox: bool| if sandbox { "enabled" } else { "disabled" };
+        let sandbox = match (self.sandbox, file_config.sandbox) {
+            (Some(sandbox), _) => format!("sandbox was {} by --sandbox", enabled(sandbox)),
+            (None, Some(sandbox)) => {
+                format!("sandbox was {} in {BACKEND_CONFIG_FILE}", enabled(sandbox))
+            }
+            (None, None) => "sandbox is disabled by default".into(),
+        };
+        vec![name, path, sandbox]
+    }
+
+    pub(crate) fn into_backend(self) -> Backend {
+        let backend = Backend::new(self.name.unwrap_or_else(get_active_backend));
+        let backend = match self.path {
+            Some(binary_path) => backend.with_binary_path(binary_path),
+            None => backend,
+        };
+        backend.sandboxed(self.sandbox.unwrap_or(false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{BackendConfig, BACKEND_CONFIG_FILE};
+
+    #[test]
+    fn reads_backend_config_beneath_cli_flags() {
+        let program_dir = tempfile::tempdir().unwrap();
+        let program_dir = program_dir.path();
+        assert_eq!(BackendConfig::read(program_dir).unwrap(), BackendConfig::default());
+
+        std::fs::write(
+            program_dir.join(BACKEND_CONFIG_FILE),
+            "name = \"team_backend\"\npath = \"bin/backend\"\nsandbox = true\n",
+        )
+        .unwrap();
+        let file_config = BackendConfig::read(program_dir).unwrap();
+        let expected_config = BackendConfig {
+            name: Some("team_backend".to_string()),
+            path: Some(program_dir.join("bin/backend")),
+            sandbox: Some(true),
+        };
+        assert_eq!(file_config, expected_config);
+
+        // Settings are taken from the file when no flags are passed
+        assert_eq!(BackendConfig::default().or(file_config.clone()), expected_config);
+
+        // Flags take precedence over the file
+        let cli_config = BackendConfig {
+            name: None,
+            path: Some(PathBuf::from("/opt/backend")),
+            sandbox: Some(false),
+        };
+        assert_eq!(
+            cli_config.or(file_config),
+            BackendConfig {
+                name: Some("team_backend".to_string()),
+                path: Some(PathBuf::from("/opt/backend")),
+                sandbox: Some(false),
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_backend_settings() {
+        let program_dir = tempfile::tempdir().unwrap();
+        for setting in [
+            "binary = \"bb\"",
+            "args = [\"-v\"]",
+            "[env]\nCRS_PATH = \"/tmp\"",
+            "[commands]\nprove = \"prove\"",
+        ] {
+            std::fs::write(program_dir.path().join(BACKEND_CONFIG_FILE), format!("{setting}\n"))
+                .unwrap();
+            assert!(
+                BackendConfig::read(program_dir.path()).is_err(),
+                "{setting} should be rejected"
+            );
+        }
+    }
+}
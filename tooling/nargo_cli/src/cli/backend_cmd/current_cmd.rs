@@ -1,13 +1,66 @@
+use backend_interface::Backend;
 use clap::Args;
 
 use crate::{backends::get_active_backend, errors::CliError};
 
 /// Prints the name of the currently active backend
 #[derive(Debug, Clone, Args)]
-pub(crate) struct CurrentCommand;
+pub(crate) struct CurrentCommand {
+    /// Also query the backend binary for its version
+    #[clap(long)]
+    show_backend_version: bool,
+}
 
-pub(crate) fn run(_args: CurrentCommand) -> Result<(), CliError> {
+pub(crate) fn run(backend: &Backend, args: CurrentCommand) -> Result<(), CliError> {
     println!("{}", get_active_backend());
 
+    if args.show_backend_version {
+        println!("{}", backend_version_message(backend));
+    }
+
     Ok(())
 }
+
+/// Describes the version reported by `backend`.
+///
+/// Not every backend supports being queried for its version, so a failure is reported
+/// as part of the message rather than as an error.
+fn backend_version_message(backend: &Backend) -> String {
+    match backend.version() {
+        Ok(version) if !version.is_empty() => format!("version: {version}"),
+        Ok(_) => "version: unknown (backend reported an empty version)".to_owned(),
+        Err(error) => format!("version: unknown ({error})"),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+
+    use backend_interface::Backend;
+
+    use super::backend_version_message;
+
+    fn stub_backend(directory: &std::path::Path, script: &str) -> Backend {
+        let binary_path = directory.join("stub_backend");
+        std::fs::write(&binary_path, script).unwrap();
+        std::fs::set_permissions(&binary_path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        Backend::new("stub_backend".to_owned()).with_binary_path(binary_path)
+    }
+
+    #[test]
+    fn prints_version_reported_by_backend() {
+        let temp_directory = tempfile::tempdir().unwrap();
+        let backend = stub_backend(temp_directory.path(), "#!/bin/sh\necho \"stub 1.2.3\"\n");
+
+        assert_eq!(backend_version_message(&backend), "version: stub 1.2.3");
+    }
+
+    #[test]
+    fn reports_backends_without_version_support() {
+        let temp_directory = tempfile::tempdir().unwrap();
+        let backend = stub_backend(temp_directory.path(), "#!/bin/sh\nexit 1\n");
+
+        assert!(backend_version_message(&backend).starts_with("version: unknown"));
+    }
+}
@@ -1,3 +1,4 @@
+use backend_interface::Backend;
 use clap::{Args, Subcommand};
 
 use crate::errors::CliError;
@@ -26,11 +27,11 @@ pub(crate) enum BackendCommands {
     Uninstall(uninstall_cmd::UninstallCommand),
 }
 
-pub(crate) fn run(cmd: BackendCommand) -> Result<(), CliError> {
+pub(crate) fn run(backend: &Backend, cmd: BackendCommand) -> Result<(), CliError> {
     let BackendCommand { command } = cmd;
 
     match command {
-        BackendCommands::Current(args) => current_cmd::run(args),
+        BackendCommands::Current(args) => current_cmd::run(backend, args),
         BackendCommands::Ls(args) => ls_cmd::run(args),
         BackendCommands::Use(args) => use_cmd::run(args),
         BackendCommands::Install(args) => install_cmd::run(args),
@@ -0,0 +1,217 @@
+use std::path::PathBuf;
+
+use acvm::acir::circuit::{brillig::Brillig, Circuit, Opcode};
+use clap::Args;
+use nargo::artifacts::program::ProgramArtifact;
+use noirc_abi::Abi;
+
+use super::fs::program::read_program_from_file;
+use crate::errors::CliError;
+
+/// Compares two compiled program artifacts, reporting the differences between their ABIs,
+/// ACIR opcodes and Brillig bytecode
+#[derive(Debug, Clone, Args)]
+pub(crate) struct DiffArtifactsCommand {
+    /// The program artifact to compare from
+    old: PathBuf,
+
+    /// The program artifact to compare to
+    new: PathBuf,
+}
+
+pub(crate) fn run(args: DiffArtifactsCommand) -> Result<(), CliError> {
+    let old = read_program_from_file(&args.old)?;
+    let new = read_program_from_file(&args.new)?;
+
+    let diff = diff_programs(&old, &new);
+    if diff.is_empty() {
+        println!("The artifacts are identical");
+    }
+    for line in diff {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// Lists the differences between two program artifacts, section by section.
+/// Lines starting with `-` are only in `old` and those starting with `+` are only in `new`.
+///
+/// Opcodes are compared by position, so an inserted opcode shows up as a change of every opcode after it.
+fn diff_programs(old: &ProgramArtifact, new: &ProgramArtifact) -> Vec<String> {
+    let mut diff = Vec::new();
+
+    let abi_diff = diff_abis(&old.abi, &new.abi);
+    if !abi_diff.is_empty() {
+        diff.push("ABI:".to_string());
+        diff.extend(abi_diff);
+    }
+
+    diff_circuits(&mut diff, &old.bytecode, &new.bytecode);
+    diff
+}
+
+fn diff_abis(old: &Abi, new: &Abi) -> Vec<String> {
+    let mut diff = Vec::new();
+    for old_parameter in &old.parameters {
+        let new_parameter = new.parameters.iter().find(|param| param.name == old_parameter.name);
+        match new_parameter {
+            Some(new_parameter) if new_parameter == old_parameter => (),
+            Some(new_parameter) => {
+                diff.push(format!(
+                    "- parameter `{}`: {:?} {:?}",
+                    old_parameter.name, old_parameter.visibility, old_parameter.typ
+                ));
+                diff.push(format!(
+                    "+ parameter `{}`: {:?} {:?}",
+                    new_parameter.name, new_parameter.visibility, new_parameter.typ
+                ));
+            }
+            None => diff.push(format!("- parameter `{}`", old_parameter.name)),
+        }
+    }
+    for new_parameter in &new.parameters {
+        if !old.parameters.iter().any(|param| param.name == new_parameter.name) {
+            diff.push(format!(
+                "+ parameter `{}`: {:?} {:?}",
+                new_parameter.name, new_parameter.visibility, new_parameter.typ
+            ));
+        }
+    }
+
+    let return_type = |abi: &Abi| {
+        serde_json::to_string(&abi.return_type).expect("ABI return types should serialize")
+    };
+    if return_type(old) != return_type(new) {
+        diff.push(format!("- return type: {}", return_type(old)));
+        diff.push(format!("+ return type: {}", return_type(new)));
+    }
+    diff
+}
+
+fn diff_circuits(diff: &mut Vec<String>, old: &Circuit, new: &Circuit) {
+    let mut acir_diff = Vec::new();
+    if old.opcodes.len() != new.opcodes.len() {
+        acir_diff.push(format!("opcode count: {} -> {}", old.opcodes.len(), new.opcodes.len()));
+    }
+
+    let mut brillig_diffs = Vec::new();
+    for index in 0..old.opcodes.len().max(new.opcodes.len()) {
+        match (old.opcodes.get(index), new.opcodes.get(index)) {
+            (Some(old_opcode), Some(new_opcode)) if old_opcode == new_opcode => (),
+            (Some(Opcode::Brillig(old_brillig)), Some(Opcode::Brillig(new_brillig))) => {
+                brillig_diffs.push((index, diff_brillig(index, old_brillig, new_brillig)));
+            }
+            (old_opcode, new_opcode) => {
+                if let Some(old_opcode) = old_opcode {
+                    acir_diff.push(format!("- {index}: {}", render_acir_opcode(old_opcode)));
+                }
+                if let Some(new_opcode) = new_opcode {
+                    acir_diff.push(format!("+ {index}: {}", render_acir_opcode(new_opcode)));
+                }
+            }
+        }
+    }
+
+    if !acir_diff.is_empty() {
+        diff.push("ACIR:".to_string());
+        diff.append(&mut acir_diff);
+    }
+    for (index, mut brillig_diff) in brillig_diffs {
+        diff.push(format!("Brillig opcode {index}:"));
+        diff.append(&mut brillig_diff);
+    }
+}
+
+/// Renders an ACIR opcode on a single line, summarizing the bytecode of Brillig opcodes.
+fn render_acir_opcode(opcode: &Opcode) -> String {
+    match opcode {
+        Opcode::Brillig(brillig) => format!("BRILLIG ({} opcodes)", brillig.bytecode.len()),
+        opcode => opcode.to_string(),
+    }
+}
+
+/// Lists the differences between the Brillig opcodes at the ACIR position `acir_index`,
+/// with the Brillig opcodes at the same `acir_index.brillig_index` addresses as in the debugger.
+fn diff_brillig(acir_index: usize, old: &Brillig, new: &Brillig) -> Vec<String> {
+    let mut diff = Vec::new();
+    if old.inputs != new.inputs || old.outputs != new.outputs || old.predicate != new.predicate {
+        diff.push("inputs, outputs or predicate changed".to_string());
+    }
+    if old.bytecode.len() != new.bytecode.len() {
+        diff.push(format!("opcode count: {} -> {}", old.bytecode.len(), new.bytecode.len()));
+    }
+    for index in 0..old.bytecode.len().max(new.bytecode.len()) {
+        let (old_opcode, new_opcode) = (old.bytecode.get(index), new.bytecode.get(index));
+        if old_opcode == new_opcode {
+            continue;
+        }
+        if let Some(old_opcode) = old_opcode {
+            diff.push(format!("- {acir_index}.{index}: {old_opcode:?}"));
+        }
+        if let Some(new_opcode) = new_opcode {
+            diff.push(format!("+ {acir_index}.{index}: {new_opcode:?}"));
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use acvm::acir::{
+        brillig::{MemoryAddress, Opcode as BrilligOpcode, Value},
+        circuit::{brillig::Brillig, Circuit, Opcode},
+        native_types::Expression,
+    };
+    use nargo::artifacts::program::ProgramArtifact;
+    use noirc_abi::Abi;
+
+    use super::diff_programs;
+
+    fn program(constant: u128) -> ProgramArtifact {
+        let brillig = Brillig {
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            bytecode: vec![
+                BrilligOpcode::Const {
+                    destination: MemoryAddress::from(0),
+                    bit_size: 32,
+                    value: Value::from(constant),
+                },
+                BrilligOpcode::Stop { return_data_offset: 0, return_data_size: 0 },
+            ],
+            predicate: None,
+        };
+        ProgramArtifact {
+            noir_version: "0.0.0".to_string(),
+            hash: 0,
+            abi: Abi {
+                parameters: Vec::new(),
+                param_witnesses: BTreeMap::new(),
+                return_type: None,
+                return_witnesses: Vec::new(),
+            },
+            bytecode: Circuit {
+                opcodes: vec![Opcode::AssertZero(Expression::default()), Opcode::Brillig(brillig)],
+                ..Circuit::default()
+            },
+            debug_symbols: Default::default(),
+            file_map: BTreeMap::new(),
+            field: None,
+            loops: Default::default(),
+        }
+    }
+
+    #[test]
+    fn reports_changed_brillig_opcodes() {
+        assert!(diff_programs(&program(1), &program(1)).is_empty());
+
+        let diff = diff_programs(&program(1), &program(2));
+        assert_eq!(diff.len(), 3, "unexpected diff: {diff:#?}");
+        assert_eq!(diff[0], "Brillig opcode 1:");
+        assert!(diff[1].starts_with("- 1.0: Const") && diff[1].contains("bit_size: 32"));
+        assert!(diff[2].starts_with("+ 1.0: Const"));
+        assert_ne!(diff[1][1..], diff[2][1..]);
+    }
+}
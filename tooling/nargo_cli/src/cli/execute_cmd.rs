@@ -1,6 +1,7 @@
-use acvm::acir::native_types::WitnessMap;
+use acvm::acir::{circuit::ExpressionWidth, native_types::WitnessMap};
 use bn254_blackbox_solver::Bn254BlackBoxSolver;
 use clap::Args;
+use std::path::PathBuf;
 
 use nargo::artifacts::debug::DebugArtifact;
 use nargo::constants::PROVER_INPUT_FILE;
@@ -16,7 +17,10 @@ use noirc_driver::{
 };
 use noirc_frontend::graph::CrateName;
 
-use super::fs::{inputs::read_inputs_from_file, witness::save_witness_to_dir};
+use super::fs::{
+    inputs::read_inputs_from_file,
+    witness::{save_witness_map_as_json, save_witness_to_dir},
+};
 use super::NargoConfig;
 use crate::backends::Backend;
 use crate::errors::CliError;
@@ -31,6 +35,14 @@ pub(crate) struct ExecuteCommand {
     #[clap(long, short, default_value = PROVER_INPUT_FILE)]
     prover_name: String,
 
+    /// Only solve and save the witness, without interacting with the proving backend
+    #[clap(long)]
+    output_witness_only: bool,
+
+    /// Write the full solved witness map as human-readable JSON to the given file
+    #[clap(long)]
+    witness_map_output: Option<PathBuf>,
+
     /// The name of the package to execute
     #[clap(long, conflicts_with = "workspace")]
     package: Option<CrateName>,
@@ -45,6 +57,11 @@ pub(crate) struct ExecuteCommand {
     /// JSON RPC url to solve oracle calls
     #[clap(long)]
     oracle_resolver: Option<String>,
+
+    /// Also compile the program with every function forced into Brillig, and check that it returns
+    /// the same value as the circuit for the same inputs
+    #[clap(long)]
+    compare_brillig: bool,
 }
 
 pub(crate) fn run(
@@ -67,10 +84,12 @@ pub(crate) fn run(
     insert_all_files_for_workspace_into_file_manager(&workspace, &mut workspace_file_manager);
     let parsed_files = parse_all(&workspace_file_manager);
 
-    let expression_width = args
-        .compile_options
-        .expression_width
-        .unwrap_or_else(|| backend.get_backend_info_or_default());
+    let expression_width = match args.compile_options.expression_width {
+        Some(expression_width) => expression_width,
+        // Use the same width as when no valid backend can be found.
+        None if args.output_witness_only => ExpressionWidth::Bounded { width: 3 },
+        None => backend.get_backend_info_or_default(),
+    };
     let binary_packages = workspace.into_iter().filter(|package| package.is_binary());
     for package in binary_packages {
         let compilation_result = compile_program(
@@ -90,6 +109,39 @@ pub(crate) fn run(
 
         let compiled_program = nargo::ops::transform_program(compiled_program, expression_width);
 
+        if args.compare_brillig {
+            let brillig_options =
+                CompileOptions { force_brillig: true, ..args.compile_options.clone() };
+            let brillig_program = report_errors(
+                compile_program(
+                    &workspace_file_manager,
+                    &parsed_files,
+                    package,
+                    &brillig_options,
+                    None,
+                ),
+                &workspace_file_manager,
+                brillig_options.deny_warnings,
+                // The warnings were already reported when compiling the circuit.
+                true,
+            )?;
+            let brillig_program = nargo::ops::transform_program(brillig_program, expression_width);
+
+            let (inputs_map, _) = read_inputs_from_file(
+                &package.root_dir,
+                &args.prover_name,
+                Format::Toml,
+                &compiled_program.abi,
+            )?;
+            compare_brillig_execution(
+                &compiled_program,
+                &brillig_program,
+                &inputs_map,
+                args.oracle_resolver.as_deref(),
+            )?;
+            println!("[{}] Brillig output matches the circuit output", package.name);
+        }
+
         let (return_value, solved_witness) = execute_program_and_decode(
             compiled_program,
             package,
@@ -101,8 +153,18 @@ pub(crate) fn run(
         if let Some(return_value) = return_value {
             println!("[{}] Circuit output: {return_value:?}", package.name);
         }
-        if let Some(witness_name) = &args.witness_name {
-            let witness_path = save_witness_to_dir(solved_witness, witness_name, target_dir)?;
+        if let Some(witness_map_output) = &args.witness_map_output {
+            let output_path = save_witness_map_as_json(&solved_witness, witness_map_output);
+
+            println!("[{}] Witness map written to {}", package.name, output_path.display());
+        }
+        let witness_name = match &args.witness_name {
+            Some(witness_name) => Some(witness_name.clone()),
+            None if args.output_witness_only => Some(String::from(&package.name)),
+            None => None,
+        };
+        if let Some(witness_name) = witness_name {
+            let witness_path = save_witness_to_dir(solved_witness, &witness_name, target_dir)?;
 
             println!("[{}] Witness saved to {}", package.name, witness_path.display());
         }
@@ -126,6 +188,34 @@ fn execute_program_and_decode(
     Ok((return_value, solved_witness))
 }
 
+/// Executes `program` and `brillig_program`, the same program compiled with every function forced into
+/// Brillig, with the same inputs and checks that they return the same value.
+///
+/// This catches differences between the semantics of the circuit and those of Brillig,
+/// e.g. in how integer overflows are handled.
+fn compare_brillig_execution(
+    program: &CompiledProgram,
+    brillig_program: &CompiledProgram,
+    inputs_map: &InputMap,
+    foreign_call_resolver_url: Option<&str>,
+) -> Result<(), CliError> {
+    let return_value = |program: &CompiledProgram| -> Result<Option<InputValue>, CliError> {
+        let solved_witness = execute_program(program, inputs_map, foreign_call_resolver_url)?;
+        let (_, return_value) = program.abi.public_abi().decode(&solved_witness)?;
+        Ok(return_value)
+    };
+
+    let acir = return_value(program)?;
+    let brillig = return_value(brillig_program)?;
+    if acir != brillig {
+        return Err(CliError::BrilligMismatch {
+            acir: format!("{acir:?}"),
+            brillig: format!("{brillig:?}"),
+        });
+    }
+    Ok(())
+}
+
 pub(crate) fn execute_program(
     compiled_program: &CompiledProgram,
     inputs_map: &InputMap,
@@ -158,3 +248,57 @@ pub(crate) fn execute_program(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::path::Path;
+
+    use acvm::FieldElement;
+    use noirc_abi::input_parser::InputValue;
+    use noirc_driver::{
+        compile_main, file_manager_with_stdlib, prepare_crate, CompileOptions, CompiledProgram,
+    };
+    use noirc_frontend::hir::{def_map::parse_file, Context};
+
+    use super::compare_brillig_execution;
+
+    fn compile(source: &str, options: &CompileOptions) -> CompiledProgram {
+        let root = Path::new("/project");
+        let file_name = root.join("src/main.nr");
+        let mut file_manager = file_manager_with_stdlib(root);
+        file_manager
+            .add_file_with_source(&file_name, source.to_owned())
+            .expect("adding the source to an empty file manager should not fail");
+        let parsed_files = file_manager
+            .as_file_map()
+            .all_file_ids()
+            .map(|&file_id| (file_id, parse_file(&file_manager, file_id)))
+            .collect();
+
+        let mut context = Context::new(file_manager, parsed_files);
+        let crate_id = prepare_crate(&mut context, &file_name);
+        let (program, _) =
+            compile_main(&mut context, crate_id, options, None).expect("program should compile");
+        program
+    }
+
+    #[test]
+    fn circuit_and_brillig_agree_on_integer_arithmetic() {
+        let source = "
+            fn main(x: u8, y: u8) -> pub u8 {
+                let z = x * 3 + y;
+                z / 2 - y % 7
+            }
+        ";
+        let program = compile(source, &CompileOptions::default());
+        let brillig_program =
+            compile(source, &CompileOptions { force_brillig: true, ..Default::default() });
+
+        let inputs_map = BTreeMap::from([
+            ("x".to_string(), InputValue::Field(FieldElement::from(20_u128))),
+            ("y".to_string(), InputValue::Field(FieldElement::from(11_u128))),
+        ]);
+        compare_brillig_execution(&program, &brillig_program, &inputs_map, None).unwrap();
+    }
+}
@@ -46,11 +46,43 @@ fn save_build_artifact_to_file<P: AsRef<Path>, T: ?Sized + serde::Serialize>(
     create_named_dir(circuit_dir.as_ref(), "target");
     let circuit_path = circuit_dir.as_ref().join(artifact_name).with_extension("json");
 
-    write_to_file(&serde_json::to_vec(build_artifact).unwrap(), &circuit_path);
+    let bytes = serde_json::to_vec(build_artifact).unwrap();
+    write_to_file(&bytes, &circuit_path);
+    write_to_file(artifact_checksum(&bytes).as_bytes(), &checksum_path(&circuit_path));
 
     circuit_path
 }
 
+/// Returns the path of the checksum file written alongside the build artifact at `circuit_path`.
+fn checksum_path(circuit_path: &Path) -> PathBuf {
+    circuit_path.with_extension("json.sha256")
+}
+
+fn artifact_checksum(bytes: &[u8]) -> String {
+    let hash = acvm::blackbox_solver::sha256(bytes).expect("sha256 should not fail");
+    hex::encode(hash)
+}
+
+/// Checks the build artifact at `circuit_path` against the checksum written when it was saved,
+/// to detect artifacts which were corrupted or truncated since.
+///
+/// Artifacts without a checksum, e.g. written by an older `nargo`, are not checked.
+pub(crate) fn verify_artifact_checksum<P: AsRef<Path>>(
+    circuit_path: P,
+) -> Result<(), FilesystemError> {
+    let file_path = circuit_path.as_ref().with_extension("json");
+    let Ok(expected_checksum) = std::fs::read_to_string(checksum_path(&file_path)) else {
+        return Ok(());
+    };
+
+    let bytes =
+        std::fs::read(&file_path).map_err(|_| FilesystemError::PathNotValid(file_path.clone()))?;
+    if artifact_checksum(&bytes) != expected_checksum.trim() {
+        return Err(FilesystemError::ArtifactChecksumMismatch(file_path));
+    }
+    Ok(())
+}
+
 pub(crate) fn read_program_from_file<P: AsRef<Path>>(
     circuit_path: P,
 ) -> Result<ProgramArtifact, FilesystemError> {
@@ -63,3 +95,36 @@ pub(crate) fn read_program_from_file<P: AsRef<Path>>(
 
     Ok(program)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{save_build_artifact_to_file, verify_artifact_checksum};
+    use crate::errors::FilesystemError;
+
+    #[test]
+    fn detects_corrupted_artifacts() {
+        let circuit_dir = tempfile::tempdir().unwrap();
+        let artifact = serde_json::json!({ "bytecode": "H4sIAAAAAAAA" });
+        let artifact_path = save_build_artifact_to_file(&artifact, "main", circuit_dir.path());
+        let circuit_path = artifact_path.with_extension("");
+        verify_artifact_checksum(&circuit_path).unwrap();
+
+        let mut bytes = std::fs::read(&artifact_path).unwrap();
+        bytes.truncate(bytes.len() / 2);
+        std::fs::write(&artifact_path, bytes).unwrap();
+
+        let error = verify_artifact_checksum(&circuit_path).unwrap_err();
+        assert!(
+            matches!(error, FilesystemError::ArtifactChecksumMismatch(path) if path == artifact_path)
+        );
+    }
+
+    #[test]
+    fn skips_artifacts_without_checksum() {
+        let circuit_dir = tempfile::tempdir().unwrap();
+        let artifact_path = circuit_dir.path().join("main.json");
+        std::fs::write(&artifact_path, "{}").unwrap();
+
+        verify_artifact_checksum(artifact_path.with_extension("")).unwrap();
+    }
+}
@@ -1,6 +1,12 @@
-use std::path::{Path, PathBuf};
+use std::{
+    fs::{File, OpenOptions},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
 
+use fd_lock::RwLock;
 use nargo::constants::PROOF_EXT;
+use serde::{Deserialize, Serialize};
 
 use crate::errors::FilesystemError;
 
@@ -18,3 +24,137 @@ pub(crate) fn save_proof_to_dir<P: AsRef<Path>>(
 
     Ok(proof_path)
 }
+
+/// An advisory lock on a proof path, which prevents concurrent `nargo prove` runs from writing
+/// the same proof.
+///
+/// The lock is taken by the OS on a `<proof>.lock` file, which is left in place. It is released
+/// when this is dropped, or when the process exits even if it is killed, so it is never left stale.
+#[derive(Debug)]
+pub(crate) struct ProofLock {
+    _file: RwLock<File>,
+}
+
+impl ProofLock {
+    /// Acquires the lock on the proof named `proof_name` in `proof_dir`.
+    ///
+    /// If another process holds the lock this fails, unless `wait` is set in which case it blocks
+    /// until the lock is released.
+    pub(crate) fn acquire<P: AsRef<Path>>(
+        proof_name: &str,
+        proof_dir: P,
+        wait: bool,
+    ) -> Result<Self, FilesystemError> {
+        create_named_dir(proof_dir.as_ref(), "proof");
+        let path = proof_dir.as_ref().join(format!("{proof_name}.{PROOF_EXT}.lock"));
+
+        let Ok(file) = OpenOptions::new().write(true).create(true).truncate(false).open(&path)
+        else {
+            return Err(FilesystemError::PathNotValid(path));
+        };
+        let mut file = RwLock::new(file);
+
+        match if wait { file.write() } else { file.try_write() } {
+            // The OS releases the lock when the file is closed, so the guard does not need to be kept.
+            Ok(guard) => std::mem::forget(guard),
+            Err(error) if error.kind() == ErrorKind::WouldBlock => {
+                return Err(FilesystemError::ProofLocked(path));
+            }
+            Err(_) => return Err(FilesystemError::PathNotValid(path)),
+        }
+        Ok(ProofLock { _file: file })
+    }
+}
+
+/// Describes how a proof was generated so that it can be audited independently of the backend.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct ProofMetadata {
+    /// Hash of the compiled program artifact which the proof was generated for.
+    pub(crate) artifact_hash: u64,
+    pub(crate) backend_name: String,
+    pub(crate) backend_version: Option<String>,
+    /// Seconds since the UNIX epoch at which the proof was generated.
+    pub(crate) timestamp: u64,
+    pub(crate) num_public_inputs: usize,
+}
+
+/// Writes `metadata` into a `<proof>.meta.json` file next to the proof with the same name.
+pub(crate) fn save_proof_metadata_to_dir<P: AsRef<Path>>(
+    metadata: &ProofMetadata,
+    proof_name: &str,
+    proof_dir: P,
+) -> PathBuf {
+    create_named_dir(proof_dir.as_ref(), "proof");
+    let metadata_path = proof_dir.as_ref().join(format!("{proof_name}.{PROOF_EXT}.meta.json"));
+
+    write_to_file(&serde_json::to_vec_pretty(metadata).unwrap(), &metadata_path);
+
+    metadata_path
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{save_proof_metadata_to_dir, ProofLock, ProofMetadata};
+    use crate::errors::FilesystemError;
+
+    #[test]
+    fn fails_to_acquire_held_proof_lock() {
+        let proof_dir = tempfile::tempdir().unwrap();
+        let lock = ProofLock::acquire("main", proof_dir.path(), false).unwrap();
+
+        let error = ProofLock::acquire("main", proof_dir.path(), false).unwrap_err();
+        assert!(matches!(error, FilesystemError::ProofLocked(_)));
+
+        // Other proofs are unaffected and the lock can be taken again once released.
+        ProofLock::acquire("other", proof_dir.path(), false).unwrap();
+        drop(lock);
+        ProofLock::acquire("main", proof_dir.path(), false).unwrap();
+    }
+
+    #[test]
+    fn waits_for_held_proof_lock() {
+        let proof_dir = tempfile::tempdir().unwrap();
+        let lock = ProofLock::acquire("main", proof_dir.path(), false).unwrap();
+
+        let waiter = {
+            let proof_dir = proof_dir.path().to_path_buf();
+            std::thread::spawn(move || ProofLock::acquire("main", proof_dir, true).map(|_| ()))
+        };
+        std::thread::sleep(Duration::from_millis(500));
+        assert!(!waiter.is_finished(), "should wait while the lock is held");
+
+        drop(lock);
+        assert!(waiter.join().unwrap().is_ok());
+    }
+
+    #[test]
+    fn acquires_proof_lock_left_behind_by_killed_prove() {
+        let proof_dir = tempfile::tempdir().unwrap();
+        // A prove which was killed leaves its lock file behind, but not the lock on it.
+        std::fs::write(proof_dir.path().join("main.proof.lock"), "").unwrap();
+
+        ProofLock::acquire("main", proof_dir.path(), false).unwrap();
+    }
+
+    #[test]
+    fn proof_metadata_sidecar_contains_artifact_hash() {
+        let proof_dir = tempfile::tempdir().unwrap();
+        let metadata = ProofMetadata {
+            artifact_hash: 0x1234_5678_9abc_def0,
+            backend_name: "mock_backend".to_string(),
+            backend_version: Some("0.1.0".to_string()),
+            timestamp: 1_700_000_000,
+            num_public_inputs: 2,
+        };
+
+        let metadata_path = save_proof_metadata_to_dir(&metadata, "hello_world", proof_dir.path());
+        assert_eq!(metadata_path, proof_dir.path().join("hello_world.proof.meta.json"));
+
+        let written: ProofMetadata =
+            serde_json::from_slice(&std::fs::read(metadata_path).unwrap()).unwrap();
+        assert_eq!(written.artifact_hash, metadata.artifact_hash);
+        assert_eq!(written.num_public_inputs, 2);
+    }
+}
@@ -0,0 +1,148 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+/// The modification time and length of a file, or `None` if it could not be read.
+type FileStamp = Option<(SystemTime, u64)>;
+
+/// A set of files which are polled to detect when any of them is modified, created or deleted.
+#[derive(Debug, Default)]
+pub(crate) struct WatchedFiles {
+    stamps: BTreeMap<PathBuf, FileStamp>,
+}
+
+impl WatchedFiles {
+    pub(crate) fn new(paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        let stamps = paths
+            .into_iter()
+            .map(|path| {
+                let stamp = file_stamp(&path);
+                (path, stamp)
+            })
+            .collect();
+        WatchedFiles { stamps }
+    }
+
+    /// Starts watching `path` if it is not watched yet.
+    pub(crate) fn watch(&mut self, path: PathBuf) {
+        let stamp = file_stamp(&path);
+        self.stamps.entry(path).or_insert(stamp);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.stamps.is_empty()
+    }
+
+    /// Returns whether any of the watched files changed since they were last checked.
+    pub(crate) fn changed(&mut self) -> bool {
+        let mut changed = false;
+        for (path, stamp) in &mut self.stamps {
+            let current = file_stamp(path);
+            if current != *stamp {
+                *stamp = current;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Blocks until one of the watched files changes.
+    pub(crate) fn wait_for_change(&mut self, poll_interval: Duration) {
+        while !self.changed() {
+            std::thread::sleep(poll_interval);
+        }
+    }
+}
+
+/// Calls `run` with the files to watch, which it may update, and calls it again each time one of them changes.
+///
+/// Errors are printed and the files are watched for a fix. If `run` leaves no file to watch, as it failed
+/// before finding any, nothing could make it run again so its result is returned instead.
+pub(crate) fn rerun_on_change<E: Display>(
+    poll_interval: Duration,
+    mut run: impl FnMut(&mut WatchedFiles) -> Result<(), E>,
+) -> Result<(), E> {
+    let mut watched_files = WatchedFiles::default();
+    loop {
+        let result = run(&mut watched_files);
+        if watched_files.is_empty() {
+            return result;
+        }
+        if let Err(error) = result {
+            eprintln!("{error}");
+        }
+
+        println!("Watching for changes...");
+        watched_files.wait_for_change(poll_interval);
+    }
+}
+
+fn file_stamp(path: &Path) -> FileStamp {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{rerun_on_change, WatchedFiles};
+
+    #[test]
+    fn detects_changes_to_dependency_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = dir.path().join("main.nr");
+        let dependency = dir.path().join("dep").join("lib.nr");
+        std::fs::create_dir_all(dependency.parent().unwrap()).unwrap();
+        std::fs::write(&main, "use dep::foo;\nfn main() { foo(); }\n").unwrap();
+        std::fs::write(&dependency, "fn foo() {}\n").unwrap();
+
+        let mut watched = WatchedFiles::new([main, dependency.clone()]);
+        assert!(!watched.changed());
+
+        std::fs::write(&dependency, "fn foo() { assert(true); }\n").unwrap();
+        assert!(watched.changed());
+        assert!(!watched.changed(), "a change should only be reported once");
+
+        std::fs::remove_file(&dependency).unwrap();
+        assert!(watched.changed());
+    }
+
+    #[test]
+    fn reruns_when_a_dependency_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let dependency = dir.path().join("lib.nr");
+        std::fs::write(&dependency, "fn foo() {}\n").unwrap();
+
+        let mut runs = Vec::new();
+        let result = rerun_on_change(Duration::from_millis(1), |watched_files| {
+            runs.push(std::fs::read_to_string(&dependency).unwrap());
+            if runs.len() == 1 {
+                *watched_files = WatchedFiles::new([dependency.clone()]);
+                std::fs::write(&dependency, "fn foo() { assert(true); }\n").unwrap();
+                Err("the first run fails")
+            } else {
+                // Stop watching so that the loop returns.
+                *watched_files = WatchedFiles::default();
+                Ok(())
+            }
+        });
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(runs, ["fn foo() {}\n", "fn foo() { assert(true); }\n"]);
+    }
+
+    #[test]
+    fn returns_errors_when_nothing_is_watched() {
+        let mut runs = 0;
+        let result = rerun_on_change(Duration::from_millis(1), |_| {
+            runs += 1;
+            Err("missing manifest")
+        });
+        assert_eq!(result, Err("missing manifest"));
+        assert_eq!(runs, 1);
+    }
+}
@@ -1,4 +1,7 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
 
 use acvm::acir::native_types::WitnessMap;
 use nargo::constants::WITNESS_EXT;
@@ -20,3 +23,89 @@ pub(crate) fn save_witness_to_dir<P: AsRef<Path>>(
 
     Ok(witness_path)
 }
+
+/// Writes the witness map to `output_path` as a human-readable JSON object
+/// mapping each witness index to its hex-encoded field value.
+pub(crate) fn save_witness_map_as_json<P: AsRef<Path>>(
+    witnesses: &WitnessMap,
+    output_path: P,
+) -> PathBuf {
+    let readable_witnesses: BTreeMap<u32, String> = witnesses
+        .clone()
+        .into_iter()
+        .map(|(witness, value)| (witness.witness_index(), format!("0x{}", value.to_hex())))
+        .collect();
+    let json = serde_json::to_string_pretty(&readable_witnesses)
+        .expect("witness map should always be serializable to JSON");
+
+    let output_path = output_path.as_ref();
+    if let Some(parent) = output_path.parent() {
+        create_named_dir(parent, "witness map output");
+    }
+    write_to_file(json.as_bytes(), output_path);
+
+    output_path.to_path_buf()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use acvm::acir::{
+        circuit::{Circuit, Opcode},
+        native_types::{Expression, Witness, WitnessMap},
+    };
+    use acvm::FieldElement;
+    use bn254_blackbox_solver::Bn254BlackBoxSolver;
+    use nargo::ops::{execute_circuit, DefaultForeignCallExecutor};
+    use tempfile::TempDir;
+
+    use super::save_witness_map_as_json;
+
+    #[test]
+    fn witness_map_output_contains_solved_assignments() {
+        // w2 = w0 + w1
+        let circuit = Circuit {
+            current_witness_index: 2,
+            opcodes: vec![Opcode::AssertZero(Expression {
+                mul_terms: Vec::new(),
+                linear_combinations: vec![
+                    (FieldElement::one(), Witness(0)),
+                    (FieldElement::one(), Witness(1)),
+                    (-FieldElement::one(), Witness(2)),
+                ],
+                q_c: FieldElement::zero(),
+            })],
+            ..Circuit::default()
+        };
+        let initial_witness = WitnessMap::from(BTreeMap::from([
+            (Witness(0), FieldElement::from(2_u128)),
+            (Witness(1), FieldElement::from(3_u128)),
+        ]));
+
+        let solved_witness = execute_circuit(
+            &circuit,
+            initial_witness,
+            &Bn254BlackBoxSolver::new(),
+            &mut DefaultForeignCallExecutor::new(false, None),
+        )
+        .unwrap();
+
+        let output_dir = TempDir::new().unwrap();
+        let output_path =
+            save_witness_map_as_json(&solved_witness, output_dir.path().join("witness.json"));
+
+        let contents = std::fs::read_to_string(output_path).unwrap();
+        let written: BTreeMap<u32, String> = serde_json::from_str(&contents).unwrap();
+
+        let expected_value = |value: u128| format!("0x{}", FieldElement::from(value).to_hex());
+        assert_eq!(
+            written,
+            BTreeMap::from([
+                (0, expected_value(2)),
+                (1, expected_value(3)),
+                (2, expected_value(5)),
+            ])
+        );
+    }
+}
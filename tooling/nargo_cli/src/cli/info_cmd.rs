@@ -29,6 +29,7 @@ use super::{compile_cmd::compile_workspace, NargoConfig};
 /// Current information provided:
 /// 1. The number of ACIR opcodes
 /// 2. Counts the final number gates in the circuit used by a backend
+/// 3. The number of loops left after optimization, and how deeply they are nested
 #[derive(Debug, Clone, Args)]
 pub(crate) struct InfoCommand {
     /// The name of the package to detail
@@ -133,7 +134,7 @@ pub(crate) fn run(
     } else {
         // Otherwise print human-readable table.
         if !info_report.programs.is_empty() {
-            let mut program_table = table!([Fm->"Package", Fm->"Expression Width", Fm->"ACIR Opcodes", Fm->"Backend Circuit Size"]);
+            let mut program_table = table!([Fm->"Package", Fm->"Expression Width", Fm->"ACIR Opcodes", Fm->"Backend Circuit Size", Fm->"Loops", Fm->"Max Loop Depth"]);
 
             for program in info_report.programs {
                 program_table.add_row(program.into());
@@ -146,7 +147,9 @@ pub(crate) fn run(
                 Fm->"Function",
                 Fm->"Expression Width",
                 Fm->"ACIR Opcodes",
-                Fm->"Backend Circuit Size"
+                Fm->"Backend Circuit Size",
+                Fm->"Loops",
+                Fm->"Max Loop Depth"
             ]);
             for contract_info in info_report.contracts {
                 let contract_rows: Vec<Row> = contract_info.into();
@@ -224,6 +227,8 @@ struct ProgramInfo {
     expression_width: ExpressionWidth,
     acir_opcodes: usize,
     circuit_size: u32,
+    loops: usize,
+    max_loop_depth: usize,
 }
 
 impl From<ProgramInfo> for Row {
@@ -233,6 +238,8 @@ impl From<ProgramInfo> for Row {
             format!("{:?}", program_info.expression_width),
             Fc->format!("{}", program_info.acir_opcodes),
             Fc->format!("{}", program_info.circuit_size),
+            Fc->format!("{}", program_info.loops),
+            Fc->format!("{}", program_info.max_loop_depth),
         ]
     }
 }
@@ -250,6 +257,8 @@ struct FunctionInfo {
     name: String,
     acir_opcodes: usize,
     circuit_size: u32,
+    loops: usize,
+    max_loop_depth: usize,
 }
 
 impl From<ContractInfo> for Vec<Row> {
@@ -261,6 +270,8 @@ impl From<ContractInfo> for Vec<Row> {
                 format!("{:?}", contract_info.expression_width),
                 Fc->format!("{}", function.acir_opcodes),
                 Fc->format!("{}", function.circuit_size),
+                Fc->format!("{}", function.loops),
+                Fc->format!("{}", function.max_loop_depth),
             ]
         })
     }
@@ -277,6 +288,8 @@ fn count_opcodes_and_gates_in_program(
         expression_width,
         acir_opcodes: compiled_program.circuit.opcodes.len(),
         circuit_size: backend.get_exact_circuit_size(&compiled_program.circuit)?,
+        loops: compiled_program.loops.count,
+        max_loop_depth: compiled_program.loops.max_depth,
     })
 }
 
@@ -293,6 +306,8 @@ fn count_opcodes_and_gates_in_contract(
                 name: function.name,
                 acir_opcodes: function.bytecode.opcodes.len(),
                 circuit_size: backend.get_exact_circuit_size(&function.bytecode)?,
+                loops: function.loops.count,
+                max_loop_depth: function.loops.max_depth,
             })
         })
         .collect::<Result<_, _>>()?;
@@ -6,7 +6,7 @@ use std::path::PathBuf;
 
 use color_eyre::eyre;
 
-use crate::backends::get_active_backend;
+use crate::backends::BackendConfig;
 
 mod fs;
 
@@ -16,6 +16,7 @@ mod codegen_verifier_cmd;
 mod compile_cmd;
 mod dap_cmd;
 mod debug_cmd;
+mod diff_artifacts_cmd;
 mod execute_cmd;
 mod export_cmd;
 mod fmt_cmd;
@@ -55,6 +56,21 @@ pub(crate) struct NargoConfig {
     // REMINDER: Also change this flag in the LSP test lens if renamed
     #[arg(long, hide = true, global = true, default_value = "./")]
     program_dir: PathBuf,
+
+    /// Run the backend with a restricted environment
+    #[arg(long, global = true)]
+    sandbox: bool,
+}
+
+impl NargoConfig {
+    /// The backend settings passed on the command line and through the environment.
+    fn backend_config(&self) -> BackendConfig {
+        BackendConfig {
+            name: None,
+            path: std::env::var_os("NARGO_BACKEND_PATH").map(PathBuf::from),
+            sandbox: self.sandbox.then_some(true),
+        }
+    }
 }
 
 #[non_exhaustive]
@@ -73,6 +89,7 @@ enum NargoCommand {
     Export(export_cmd::ExportCommand),
     #[command(hide = true)] // Hidden while the feature is being built out
     Debug(debug_cmd::DebugCommand),
+    DiffArtifacts(diff_artifacts_cmd::DiffArtifactsCommand),
     Prove(prove_cmd::ProveCommand),
     Verify(verify_cmd::VerifyCommand),
     Test(test_cmd::TestCommand),
@@ -99,12 +116,31 @@ pub(crate) fn start_cli() -> eyre::Result<()> {
             | NargoCommand::Lsp(_)
             | NargoCommand::Backend(_)
             | NargoCommand::Dap(_)
+            | NargoCommand::DiffArtifacts(_)
     ) {
         config.program_dir = find_package_root(&config.program_dir)?;
     }
 
-    let active_backend = get_active_backend();
-    let backend = crate::backends::Backend::new(active_backend);
+    // Settings from the command line and environment take precedence over those in `backend.toml`,
+    // which is only read by the commands which run the backend over a package.
+    let file_config = if matches!(
+        command,
+        NargoCommand::New(_)
+            | NargoCommand::Init(_)
+            | NargoCommand::Check(_)
+            | NargoCommand::Test(_)
+            | NargoCommand::Export(_)
+            | NargoCommand::Lsp(_)
+            | NargoCommand::Backend(_)
+            | NargoCommand::Dap(_)
+            | NargoCommand::DiffArtifacts(_)
+            | NargoCommand::Fmt(_)
+    ) {
+        BackendConfig::default()
+    } else {
+        BackendConfig::read(&config.program_dir)?
+    };
+    let backend = config.backend_config().or(file_config).into_backend();
 
     match command {
         NargoCommand::New(args) => new_cmd::run(&backend, args, config),
@@ -112,6 +148,7 @@ pub(crate) fn start_cli() -> eyre::Result<()> {
         NargoCommand::Check(args) => check_cmd::run(&backend, args, config),
         NargoCommand::Compile(args) => compile_cmd::run(&backend, args, config),
         NargoCommand::Debug(args) => debug_cmd::run(&backend, args, config),
+        NargoCommand::DiffArtifacts(args) => diff_artifacts_cmd::run(args),
         NargoCommand::Execute(args) => execute_cmd::run(&backend, args, config),
         NargoCommand::Export(args) => export_cmd::run(&backend, args, config),
         NargoCommand::Prove(args) => prove_cmd::run(&backend, args, config),
@@ -119,7 +156,7 @@ pub(crate) fn start_cli() -> eyre::Result<()> {
         NargoCommand::Test(args) => test_cmd::run(&backend, args, config),
         NargoCommand::Info(args) => info_cmd::run(&backend, args, config),
         NargoCommand::CodegenVerifier(args) => codegen_verifier_cmd::run(&backend, args, config),
-        NargoCommand::Backend(args) => backend_cmd::run(args),
+        NargoCommand::Backend(args) => backend_cmd::run(&backend, args),
         NargoCommand::Lsp(args) => lsp_cmd::run(&backend, args, config),
         NargoCommand::Dap(args) => dap_cmd::run(&backend, args, config),
         NargoCommand::Fmt(args) => fmt_cmd::run(args, config),
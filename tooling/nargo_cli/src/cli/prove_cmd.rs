@@ -1,11 +1,21 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use acvm::acir::{
+    acir_field::CHOSEN_FIELD,
+    circuit::Circuit,
+    native_types::{Witness, WitnessMap},
+};
 use clap::Args;
-use nargo::constants::{PROVER_INPUT_FILE, VERIFIER_INPUT_FILE};
+use fm::FileManager;
+use nargo::constants::{PKG_FILE, PROOF_EXT, PROVER_INPUT_FILE, VERIFIER_INPUT_FILE};
 use nargo::ops::{compile_program, report_errors};
-use nargo::package::Package;
+use nargo::package::{Dependency, Package};
 use nargo::workspace::Workspace;
 use nargo::{insert_all_files_for_workspace_into_file_manager, parse_all};
 use nargo_toml::{get_package_manifest, resolve_workspace_from_toml, PackageSelection};
-use noirc_abi::input_parser::Format;
+use noirc_abi::{input_parser::Format, Abi, AbiType};
 use noirc_driver::{
     file_manager_with_stdlib, CompileOptions, CompiledProgram, NOIR_ARTIFACT_VERSION_STRING,
 };
@@ -13,10 +23,16 @@ use noirc_frontend::graph::CrateName;
 
 use super::fs::{
     inputs::{read_inputs_from_file, write_inputs_to_file},
-    proof::save_proof_to_dir,
+    program::{read_program_from_file, verify_artifact_checksum},
+    proof::{save_proof_metadata_to_dir, save_proof_to_dir, ProofLock, ProofMetadata},
+    watch::{rerun_on_change, WatchedFiles},
 };
 use super::NargoConfig;
-use crate::{backends::Backend, cli::execute_cmd::execute_program, errors::CliError};
+use crate::{
+    backends::{Backend, BackendConfig},
+    cli::execute_cmd::execute_program,
+    errors::CliError,
+};
 
 /// Create proof for this program. The proof is returned as a hex encoded string.
 #[derive(Debug, Clone, Args)]
@@ -47,17 +63,72 @@ pub(crate) struct ProveCommand {
     /// JSON RPC url to solve oracle calls
     #[clap(long)]
     oracle_resolver: Option<String>,
+
+    /// Write a `.meta.json` file describing how the proof was generated alongside it
+    #[arg(long)]
+    proof_metadata: bool,
+
+    /// Print how long the backend spent in each phase of proving, if it reports it
+    #[arg(long)]
+    timings: bool,
+
+    /// Wait for other `nargo prove` runs writing the same proof to finish instead of failing
+    #[arg(long)]
+    wait: bool,
+
+    /// Keep running and prove again whenever a source file of the workspace changes
+    #[arg(long)]
+    watch: bool,
+
+    /// Additional files to watch alongside the workspace's sources, relative to the program directory
+    #[arg(long, value_delimiter = ',', requires = "watch")]
+    watch_includes: Vec<PathBuf>,
+
+    /// The file name of each proof, where `{name}` is replaced by the package name and `{index}`
+    /// by the position of the package among those being proved
+    #[arg(long, default_value = DEFAULT_OUTPUT_TEMPLATE)]
+    output_template: String,
+
+    /// Print how the backend, the files read and written and the backend's environment were chosen
+    /// before proving each package
+    #[arg(long)]
+    explain: bool,
 }
 
+const DEFAULT_OUTPUT_TEMPLATE: &str = "{name}.proof";
+
+/// How often watched files are checked for changes.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
 pub(crate) fn run(
     backend: &Backend,
     args: ProveCommand,
     config: NargoConfig,
+) -> Result<(), CliError> {
+    if !args.watch {
+        return prove_workspace(backend, &args, &config, &mut WatchedFiles::default());
+    }
+    rerun_on_change(WATCH_POLL_INTERVAL, |watched_files| {
+        prove_workspace(backend, &args, &config, watched_files)
+    })
+}
+
+/// Proves every binary package in the workspace, replacing `watched_files` with the files which
+/// contribute to the proofs before compiling so that a failed build can still be watched.
+fn prove_workspace(
+    backend: &Backend,
+    args: &ProveCommand,
+    config: &NargoConfig,
+    watched_files: &mut WatchedFiles,
 ) -> Result<(), CliError> {
     let toml_path = get_package_manifest(&config.program_dir)?;
+    if args.watch {
+        // A workspace which fails to resolve can still be fixed by editing its manifest.
+        watched_files.watch(toml_path.clone());
+    }
     let default_selection =
         if args.workspace { PackageSelection::All } else { PackageSelection::DefaultOrAll };
-    let selection = args.package.map_or(default_selection, PackageSelection::Selected);
+    let selection = args.package.clone().map_or(default_selection, PackageSelection::Selected);
     let workspace = resolve_workspace_from_toml(
         &toml_path,
         selection,
@@ -68,18 +139,48 @@ pub(crate) fn run(
     insert_all_files_for_workspace_into_file_manager(&workspace, &mut workspace_file_manager);
     let parsed_files = parse_all(&workspace_file_manager);
 
+    if args.watch {
+        *watched_files = WatchedFiles::new(watched_paths(
+            &workspace,
+            &workspace_file_manager,
+            &args.prover_name,
+            &config.program_dir,
+            &args.watch_includes,
+        ));
+    }
+
+    if args.explain {
+        let file_config = BackendConfig::read(&config.program_dir)?;
+        let resolution = config.backend_config().explain_resolution(&file_config);
+        print_explanation(&explain_backend(backend, resolution));
+    }
+
     let expression_width = args
         .compile_options
         .expression_width
         .unwrap_or_else(|| backend.get_backend_info_or_default());
     let binary_packages = workspace.into_iter().filter(|package| package.is_binary());
-    for package in binary_packages {
+    let mut proof_names = HashSet::new();
+    for (index, package) in binary_packages.enumerate() {
+        let proof_name = render_output_template(&args.output_template, &package.name, index)?;
+        if !proof_names.insert(proof_name.clone()) {
+            return Err(CliError::Generic(format!(
+                "Output template `{}` gives the same proof name `{proof_name}` to several packages",
+                args.output_template
+            )));
+        }
+
+        if args.explain {
+            print_explanation(&explain_paths(args, &workspace, package, &proof_name));
+        }
+
+        let cached_program = read_cached_program(&workspace.package_build_path(package))?;
         let compilation_result = compile_program(
             &workspace_file_manager,
             &parsed_files,
             package,
             &args.compile_options,
-            None,
+            cached_program,
         );
 
         let compiled_program = report_errors(
@@ -96,33 +197,145 @@ pub(crate) fn run(
             &workspace,
             package,
             compiled_program,
+            &proof_name,
             &args.prover_name,
             &args.verifier_name,
             args.verify,
             args.oracle_resolver.as_deref(),
+            args.proof_metadata,
+            args.compile_options.deterministic_build,
+            args.timings,
+            args.wait,
         )?;
     }
 
     Ok(())
 }
 
+fn print_explanation(lines: &[String]) {
+    for line in lines {
+        println!("[explain] {line}");
+    }
+}
+
+/// Describes, for `--explain`, the binary and environment the backend runs with,
+/// after the lines of `resolution` explaining how the backend was chosen.
+fn explain_backend(backend: &Backend, mut resolution: Vec<String>) -> Vec<String> {
+    resolution.push(format!("the backend binary is {}", backend.binary_path().display()));
+    resolution.push(match backend.passed_env_vars() {
+        None => "the backend inherits the full environment of nargo".to_string(),
+        Some(names) if names.is_empty() => "the backend runs with an empty environment".to_string(),
+        Some(names) => {
+            format!("the backend only receives the environment variables {}", names.join(", "))
+        }
+    });
+    resolution
+}
+
+/// Describes, for `--explain`, the files which proving `package` reads and writes,
+/// and whether their names are the defaults or were set on the command line.
+fn explain_paths(
+    args: &ProveCommand,
+    workspace: &Workspace,
+    package: &Package,
+    proof_name: &str,
+) -> Vec<String> {
+    let origin = |overridden: bool, flag: &str| {
+        if overridden {
+            format!("set by {flag}")
+        } else {
+            format!("default of {flag}")
+        }
+    };
+    let input_path = |name: &str| package.root_dir.join(name).with_extension(Format::Toml.ext());
+
+    vec![
+        format!("proving package `{}` in {}", package.name, package.root_dir.display()),
+        format!(
+            "the build artifact {} is proved if it is up to date, after checking it against its checksum",
+            workspace.package_build_path(package).display()
+        ),
+        format!(
+            "prover inputs are read from {} ({})",
+            input_path(&args.prover_name).display(),
+            origin(args.prover_name != PROVER_INPUT_FILE, "--prover-name")
+        ),
+        format!(
+            "verifier inputs are written to {} ({})",
+            input_path(&args.verifier_name).display(),
+            origin(args.verifier_name != VERIFIER_INPUT_FILE, "--verifier-name")
+        ),
+        format!(
+            "the proof is written to {} ({})",
+            workspace.proofs_directory_path().join(proof_name).with_extension(PROOF_EXT).display(),
+            origin(args.output_template != DEFAULT_OUTPUT_TEMPLATE, "--output-template")
+        ),
+    ]
+}
+
+/// Collects every file on disk which the proofs of `workspace` depend on: the source files and manifests
+/// of all packages and their dependencies, each package's prover inputs and any extra `includes`.
+fn watched_paths(
+    workspace: &Workspace,
+    file_manager: &FileManager,
+    prover_name: &str,
+    program_dir: &Path,
+    includes: &[PathBuf],
+) -> Vec<PathBuf> {
+    let file_map = file_manager.as_file_map();
+    let source_files = file_map
+        .all_file_ids()
+        .filter_map(|file_id| file_manager.path(*file_id))
+        // The standard library is embedded in the binary so has no files to watch.
+        .filter(|path| path.is_file())
+        .map(Path::to_path_buf);
+    let prover_inputs = workspace
+        .into_iter()
+        .map(|package| package.root_dir.join(prover_name).with_extension("toml"));
+    let includes = includes.iter().map(|path| program_dir.join(path));
+
+    let mut manifests = vec![workspace.root_dir.join(PKG_FILE)];
+    for package in workspace {
+        collect_manifests(package, &mut manifests);
+    }
+
+    source_files.chain(prover_inputs).chain(includes).chain(manifests).collect()
+}
+
+/// Adds the manifests of `package` and of all its dependencies to `manifests`.
+fn collect_manifests(package: &Package, manifests: &mut Vec<PathBuf>) {
+    manifests.push(package.root_dir.join(PKG_FILE));
+    for dependency in package.dependencies.values() {
+        let (Dependency::Local { package } | Dependency::Remote { package }) = dependency;
+        collect_manifests(package, manifests);
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn prove_package(
     backend: &Backend,
     workspace: &Workspace,
     package: &Package,
     compiled_program: CompiledProgram,
+    proof_name: &str,
     prover_name: &str,
     verifier_name: &str,
     check_proof: bool,
     foreign_call_resolver_url: Option<&str>,
+    write_proof_metadata: bool,
+    deterministic_build: bool,
+    show_timings: bool,
+    wait_for_lock: bool,
 ) -> Result<(), CliError> {
+    let _lock = ProofLock::acquire(proof_name, workspace.proofs_directory_path(), wait_for_lock)?;
+
     // Parse the initial witness values from Prover.toml
     let (inputs_map, _) =
         read_inputs_from_file(&package.root_dir, prover_name, Format::Toml, &compiled_program.abi)?;
 
     let solved_witness =
         execute_program(&compiled_program, &inputs_map, foreign_call_resolver_url)?;
+    check_public_inputs(&compiled_program.abi, &compiled_program.circuit, &solved_witness)?;
 
     // Write public inputs into Verifier.toml
     let public_abi = compiled_program.abi.public_abi();
@@ -137,7 +350,13 @@ pub(crate) fn prove_package(
         Format::Toml,
     )?;
 
-    let proof = backend.prove(&compiled_program.circuit, solved_witness)?;
+    let (proof, timings) = backend.prove_with_timings(&compiled_program.circuit, solved_witness)?;
+    if show_timings {
+        match timings {
+            Some(timings) => println!("{timings}"),
+            None => println!("Backend did not report any phase timings"),
+        }
+    }
 
     if check_proof {
         let public_inputs = public_abi.encode(&public_inputs, return_value)?;
@@ -148,7 +367,309 @@ pub(crate) fn prove_package(
         }
     }
 
-    save_proof_to_dir(&proof, &String::from(&package.name), workspace.proofs_directory_path())?;
+    save_proof_to_dir(&proof, proof_name, workspace.proofs_directory_path())?;
+
+    if write_proof_metadata {
+        let metadata = ProofMetadata {
+            artifact_hash: compiled_program.hash,
+            backend_name: backend.name().to_string(),
+            backend_version: backend.version().ok(),
+            // Deterministic builds must not depend on when they were run.
+            timestamp: if deterministic_build {
+                0
+            } else {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .expect("system time should be after the UNIX epoch")
+                    .as_secs()
+            },
+            num_public_inputs: compiled_program.circuit.public_inputs().0.len(),
+        };
+        save_proof_metadata_to_dir(&metadata, proof_name, workspace.proofs_directory_path());
+    }
 
     Ok(())
 }
+
+/// Renders the proof file name `template` for the package `name` at position `index` of the packages
+/// being proved, and returns it without the proof extension, which is always added when saving.
+fn render_output_template(
+    template: &str,
+    name: &CrateName,
+    index: usize,
+) -> Result<String, CliError> {
+    let rendered =
+        template.replace("{name}", &String::from(name)).replace("{index}", &index.to_string());
+    if rendered.contains(['{', '}']) {
+        return Err(CliError::Generic(format!(
+            "Output template `{template}` contains an unknown placeholder, only `{{name}}` and `{{index}}` are supported"
+        )));
+    }
+
+    let proof_name = rendered.strip_suffix(&format!(".{PROOF_EXT}")).unwrap_or(&rendered);
+    if proof_name.is_empty() || proof_name.contains(['/', '\\']) {
+        return Err(CliError::Generic(format!(
+            "Output template `{template}` must give a file name within the proofs directory"
+        )));
+    }
+    Ok(proof_name.to_string())
+}
+
+/// Reads the build artifact at `artifact_path` so that the compiler can prove it as is if the program
+/// has not changed since it was compiled.
+///
+/// Artifacts which were corrupted or truncated since they were saved, or which were compiled for a field
+/// other than the one the witness is solved over, are refused. Missing or unreadable artifacts and those
+/// written by another version of `nargo` are compiled again.
+fn read_cached_program(artifact_path: &Path) -> Result<Option<CompiledProgram>, CliError> {
+    verify_artifact_checksum(artifact_path)?;
+    let Some(artifact) = read_program_from_file(artifact_path)
+        .ok()
+        .filter(|program| program.noir_version == NOIR_ARTIFACT_VERSION_STRING)
+    else {
+        return Ok(None);
+    };
+    check_witness_field(artifact.field.as_deref(), &CHOSEN_FIELD.to_string())?;
+    Ok(Some(artifact.into()))
+}
+
+/// Checks that the field `artifact_field` recorded in a program artifact is `witness_field`.
+///
+/// Artifacts which do not record their field, e.g. written by an older `nargo`, are not checked.
+fn check_witness_field(artifact_field: Option<&str>, witness_field: &str) -> Result<(), CliError> {
+    match artifact_field {
+        Some(artifact_field) if artifact_field != witness_field => Err(CliError::FieldMismatch {
+            artifact: artifact_field.to_string(),
+            witness: witness_field.to_string(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that the public parameters of `circuit` match those described by `abi`, and that `witness`
+/// holds a value of the expected type for each of them, so that mismatches are reported before
+/// they reach the backend.
+fn check_public_inputs(abi: &Abi, circuit: &Circuit, witness: &WitnessMap) -> Result<(), CliError> {
+    let public_parameters: Vec<_> =
+        abi.parameters.iter().filter(|param| param.is_public()).collect();
+
+    let expected = circuit.public_parameters.0.len();
+    let found: u32 = public_parameters.iter().map(|param| param.typ.field_count()).sum();
+    if found as usize != expected {
+        return Err(CliError::PublicInputCountMismatch { expected, found: found as usize });
+    }
+
+    for parameter in public_parameters {
+        let witness_ranges = abi.param_witnesses.get(&parameter.name).into_iter().flatten();
+        for witness_index in witness_ranges.flat_map(|range| range.start.0..range.end.0) {
+            let Some(value) = witness.get(&Witness(witness_index)) else {
+                return Err(CliError::MissingPublicInput {
+                    name: parameter.name.clone(),
+                    witness_index,
+                });
+            };
+
+            let width = match parameter.typ {
+                AbiType::Integer { width, .. } => width,
+                AbiType::Boolean => 1,
+                _ => continue,
+            };
+            if value.num_bits() > width {
+                return Err(CliError::PublicInputTypeMismatch {
+                    name: parameter.name.clone(),
+                    width,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use acvm::{
+        acir::{
+            circuit::{Circuit, PublicInputs},
+            native_types::{Witness, WitnessMap},
+        },
+        FieldElement,
+    };
+    use clap::Parser;
+    use nargo::{
+        package::{Package, PackageType},
+        workspace::Workspace,
+    };
+    use noirc_abi::{Abi, AbiParameter, AbiType, AbiVisibility, Sign};
+
+    use super::{
+        check_public_inputs, check_witness_field, explain_backend, explain_paths,
+        read_cached_program, render_output_template, ProveCommand, DEFAULT_OUTPUT_TEMPLATE,
+    };
+    use crate::backends::{Backend, BackendConfig};
+    use crate::cli::fs::proof::save_proof_to_dir;
+    use crate::errors::CliError;
+
+    fn abi_and_circuit() -> (Abi, Circuit) {
+        let parameter = |name: &str, visibility: AbiVisibility| AbiParameter {
+            name: name.to_string(),
+            typ: AbiType::Integer { sign: Sign::Unsigned, width: 8 },
+            visibility,
+        };
+        let abi = Abi {
+            parameters: vec![
+                parameter("x", AbiVisibility::Private),
+                parameter("y", AbiVisibility::Public),
+            ],
+            param_witnesses: BTreeMap::from([
+                ("x".to_string(), vec![Witness(1)..Witness(2)]),
+                ("y".to_string(), vec![Witness(2)..Witness(3)]),
+            ]),
+            return_type: None,
+            return_witnesses: Vec::new(),
+        };
+        let circuit = Circuit {
+            public_parameters: PublicInputs(BTreeSet::from([Witness(2)])),
+            ..Circuit::default()
+        };
+        (abi, circuit)
+    }
+
+    #[test]
+    fn accepts_witness_with_all_public_inputs() {
+        let (abi, circuit) = abi_and_circuit();
+        let witness = WitnessMap::from(BTreeMap::from([
+            (Witness(1), FieldElement::from(3_u128)),
+            (Witness(2), FieldElement::from(4_u128)),
+        ]));
+
+        assert!(check_public_inputs(&abi, &circuit, &witness).is_ok());
+    }
+
+    #[test]
+    fn reports_missing_public_input() {
+        let (abi, circuit) = abi_and_circuit();
+        let witness = WitnessMap::from(BTreeMap::from([(Witness(1), FieldElement::from(3_u128))]));
+
+        let error = check_public_inputs(&abi, &circuit, &witness).unwrap_err();
+        assert!(matches!(
+            &error,
+            CliError::MissingPublicInput { name, witness_index: 2 } if name == "y"
+        ));
+        assert_eq!(
+            error.to_string(),
+            "The witness has no value for public input `y` at witness index 2"
+        );
+    }
+
+    #[test]
+    fn reports_public_input_out_of_type_range() {
+        let (abi, circuit) = abi_and_circuit();
+        let witness = WitnessMap::from(BTreeMap::from([
+            (Witness(1), FieldElement::from(3_u128)),
+            (Witness(2), FieldElement::from(256_u128)),
+        ]));
+
+        let error = check_public_inputs(&abi, &circuit, &witness).unwrap_err();
+        assert!(matches!(error, CliError::PublicInputTypeMismatch { width: 8, .. }));
+    }
+
+    #[test]
+    fn reports_witness_field_mismatch() {
+        assert!(check_witness_field(Some("bn254"), "bn254").is_ok());
+        assert!(check_witness_field(None, "bn254").is_ok());
+
+        let error = check_witness_field(Some("bls12_381"), "bn254").unwrap_err();
+        assert!(matches!(
+            &error,
+            CliError::FieldMismatch { artifact, witness } if artifact == "bls12_381" && witness == "bn254"
+        ));
+        assert_eq!(
+            error.to_string(),
+            "The circuit is defined over the bls12_381 field but the witness is computed over the bn254 field"
+        );
+    }
+
+    #[test]
+    fn compiles_again_over_unreadable_artifacts() {
+        let circuit_dir = tempfile::tempdir().unwrap();
+        let artifact_path = circuit_dir.path().join("main.json");
+        assert!(read_cached_program(&artifact_path).unwrap().is_none());
+
+        // An artifact written by an older `nargo` which no longer deserializes.
+        std::fs::write(&artifact_path, r#"{"backend": "acvm-backend-barretenberg"}"#).unwrap();
+        assert!(read_cached_program(&artifact_path).unwrap().is_none());
+    }
+
+    #[test]
+    fn names_proofs_after_output_template() {
+        let name = "batch_member".parse().unwrap();
+        let render = |template: &str| render_output_template(template, &name, 3);
+
+        assert_eq!(render(DEFAULT_OUTPUT_TEMPLATE).unwrap(), "batch_member");
+        assert_eq!(render("{index}-{name}.proof").unwrap(), "3-batch_member");
+        assert_eq!(render("proof_{index}").unwrap(), "proof_3");
+
+        let proof_dir = tempfile::tempdir().unwrap();
+        let proof_path =
+            save_proof_to_dir(&[1, 2], &render("{index}-{name}.proof").unwrap(), proof_dir.path())
+                .unwrap();
+        assert_eq!(proof_path, proof_dir.path().join("3-batch_member.proof"));
+
+        assert!(matches!(render("{name}-{witness}"), Err(CliError::Generic(_))));
+        assert!(matches!(render("../{name}"), Err(CliError::Generic(_))));
+    }
+
+    #[derive(Parser)]
+    struct ProveCli {
+        #[clap(flatten)]
+        args: ProveCommand,
+    }
+
+    #[test]
+    fn explains_backend_resolution_and_paths() {
+        let file_config = BackendConfig {
+            name: Some("team_backend".to_string()),
+            path: Some("/opt/team_backend/bb".into()),
+            sandbox: None,
+        };
+        let resolution = BackendConfig::default().explain_resolution(&file_config);
+        let backend = file_config.into_backend();
+        let explanation = explain_backend(&backend, resolution).join("\n");
+        assert!(explanation.contains("backend `team_backend` was read from backend.toml"));
+        assert!(
+            explanation.contains("backend binary /opt/team_backend/bb was read from backend.toml")
+        );
+        assert!(explanation.contains("sandbox is disabled by default"));
+        assert!(explanation.contains("inherits the full environment"));
+
+        let package = Package {
+            version: None,
+            compiler_required_version: None,
+            root_dir: "/workspace/member".into(),
+            package_type: PackageType::Binary,
+            entry_path: "/workspace/member/src/main.nr".into(),
+            name: "member".parse().unwrap(),
+            dependencies: BTreeMap::new(),
+        };
+        let workspace = Workspace {
+            root_dir: "/workspace".into(),
+            members: vec![package.clone()],
+            selected_package_index: Some(0),
+            is_assumed: false,
+        };
+        let ProveCli { args } =
+            ProveCli::parse_from(["prove", "--explain", "--prover-name", "Custom"]);
+        let explanation = explain_paths(&args, &workspace, &package, "member").join("\n");
+        assert!(explanation.contains("/workspace/target/member.json"));
+        assert!(explanation.contains("/workspace/member/Custom.toml (set by --prover-name)"));
+        assert!(
+            explanation.contains("/workspace/member/Verifier.toml (default of --verifier-name)")
+        );
+        assert!(
+            explanation.contains("/workspace/proofs/member.proof (default of --output-template)")
+        );
+    }
+}
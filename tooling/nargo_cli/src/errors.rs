@@ -28,6 +28,12 @@ pub(crate) enum FilesystemError {
 
     #[error("Error: could not deserialize build program: {0}")]
     ProgramSerializationError(String),
+
+    #[error("Error: build artifact {} does not match its checksum, it may be corrupted or truncated\nRun `nargo compile` to regenerate it", .0.display())]
+    ArtifactChecksumMismatch(PathBuf),
+
+    #[error("Error: {} is held by another `nargo prove`\nPass `--wait` to wait for it to finish", .0.display())]
+    ProofLocked(PathBuf),
 }
 
 #[derive(Debug, Error)]
@@ -43,6 +49,21 @@ pub(crate) enum CliError {
     #[error("Invalid package name {0}. Did you mean to use `--name`?")]
     InvalidPackageName(String),
 
+    #[error("The circuit has {expected} public parameters but its ABI describes {found}")]
+    PublicInputCountMismatch { expected: usize, found: usize },
+
+    #[error("The witness has no value for public input `{name}` at witness index {witness_index}")]
+    MissingPublicInput { name: String, witness_index: u32 },
+
+    #[error("The witness value for public input `{name}` does not fit in {width} bits")]
+    PublicInputTypeMismatch { name: String, width: u32 },
+
+    #[error("The program returned {brillig} when compiled to Brillig but {acir} as a circuit")]
+    BrilligMismatch { acir: String, brillig: String },
+
+    #[error("The circuit is defined over the {artifact} field but the witness is computed over the {witness} field")]
+    FieldMismatch { artifact: String, witness: String },
+
     /// ABI encoding/decoding error
     #[error(transparent)]
     AbiError(#[from] AbiError),
@@ -88,4 +109,7 @@ pub(crate) enum BackendError {
 
     #[error("Backend installation failed: {0}")]
     InstallationError(#[from] std::io::Error),
+
+    #[error("Invalid backend configuration in {}: {1}", .0.display())]
+    InvalidConfig(PathBuf, String),
 }
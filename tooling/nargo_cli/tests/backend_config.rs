@@ -0,0 +1,31 @@
+//! This integration test checks that `backend.toml` is only read by the commands which use the backend.
+
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::process::Command;
+
+use assert_fs::prelude::{FileWriteStr, PathChild};
+
+#[test]
+fn invalid_backend_config_only_fails_commands_using_the_backend() {
+    let test_dir = assert_fs::TempDir::new().unwrap();
+
+    let project_name = "backend_config";
+    let project_dir = test_dir.child(project_name);
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&test_dir).arg("new").arg(project_name);
+    cmd.assert().success();
+
+    project_dir.child("backend.toml").write_str("binary = \"bb\"\n").unwrap();
+
+    for command in ["fmt", "check", "test"] {
+        let mut cmd = Command::cargo_bin("nargo").unwrap();
+        cmd.current_dir(&project_dir).arg(command);
+        cmd.assert().success();
+    }
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&project_dir).arg("prove");
+    cmd.assert().failure().stderr(predicate::str::contains("Invalid backend configuration"));
+}
@@ -0,0 +1,71 @@
+//! This integration test checks that `--deny-warnings` turns compiler warnings into a failing exit code.
+
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::process::Command;
+
+use assert_fs::prelude::{FileWriteStr, PathChild};
+
+#[test]
+fn deny_warnings_fails_on_warnings() {
+    let test_dir = assert_fs::TempDir::new().unwrap();
+
+    let project_name = "warnings";
+    let project_dir = test_dir.child(project_name);
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&test_dir).arg("new").arg(project_name);
+    cmd.assert().success();
+
+    project_dir
+        .child("src")
+        .child("main.nr")
+        .write_str(
+            "fn main(x: Field, y: pub Field) {\n    let unused = x;\n    assert(x != y);\n}\n",
+        )
+        .unwrap();
+
+    // Warnings are allowed by default.
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&project_dir).arg("check");
+    cmd.assert().success().stderr(predicate::str::contains("unused variable unused"));
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&project_dir).arg("check").arg("--deny-warnings");
+    cmd.assert().failure().stderr(predicate::str::contains("unused variable unused"));
+}
+
+#[test]
+fn deny_warnings_fails_on_ssa_warnings_of_contracts() {
+    let test_dir = assert_fs::TempDir::new().unwrap();
+
+    let project_name = "contract_warnings";
+    let project_dir = test_dir.child(project_name);
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&test_dir).arg("new").arg("--contract").arg(project_name);
+    cmd.assert().success();
+
+    // Returning a constant is only reported once the contract function is compiled to ACIR.
+    project_dir
+        .child("src")
+        .child("main.nr")
+        .write_str("contract Foo {\n    fn constant() -> pub Field {\n        5\n    }\n}\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&project_dir).arg("compile").arg("--expression-width").arg("3");
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("Return variable contains a constant value"));
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&project_dir)
+        .arg("compile")
+        .arg("--expression-width")
+        .arg("3")
+        .arg("--deny-warnings");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Return variable contains a constant value"));
+}
@@ -0,0 +1,43 @@
+//! This integration test checks that `nargo execute --output-witness-only` solves and saves
+//! the witness of a program without ever invoking the proving backend.
+#![cfg(unix)]
+
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::os::unix::fs::PermissionsExt;
+use std::process::Command;
+
+use assert_fs::prelude::{FileWriteStr, PathAssert, PathChild};
+
+#[test]
+fn output_witness_only_does_not_spawn_backend() {
+    let test_dir = assert_fs::TempDir::new().unwrap();
+
+    let project_name = "hello_world";
+    let project_dir = test_dir.child(project_name);
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&test_dir).arg("new").arg(project_name);
+    cmd.assert().success();
+
+    project_dir.child("Prover.toml").write_str("x = 1\ny = 2").unwrap();
+
+    // A fake backend which leaves a marker behind if it is ever run.
+    let marker = test_dir.child("backend_was_spawned");
+    let fake_backend = test_dir.child("fake_backend");
+    fake_backend.write_str(&format!("#!/bin/sh\ntouch {}\n", marker.path().display())).unwrap();
+    std::fs::set_permissions(fake_backend.path(), std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&project_dir)
+        .env("NARGO_BACKEND_PATH", fake_backend.path())
+        .arg("execute")
+        .arg("--output-witness-only");
+    cmd.assert().success().stdout(predicate::str::contains("Witness saved to"));
+
+    project_dir
+        .child("target")
+        .child(format!("{project_name}.gz"))
+        .assert(predicate::path::is_file());
+    marker.assert(predicate::path::missing());
+}